@@ -0,0 +1,460 @@
+//! Accounts purchases are paid from, transfers between them and their balances.
+//!
+//! Transfers only move money between accounts, so they never show up in spending totals or reports.
+
+use crate::log::{Budgr, LogId, PurchaseId};
+use crate::reconcile::{check_unlocked, Statement};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// A stable id for an [`Account`], purchases refer to their account with it.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(transparent)]
+pub struct AccountId(Uuid);
+
+impl AccountId {
+    /// A new random id.
+    pub fn new() -> Self {
+        AccountId(Uuid::new_v4())
+    }
+}
+
+/// A new random id.
+impl Default for AccountId {
+    fn default() -> Self {
+        AccountId::new()
+    }
+}
+
+impl fmt::Display for AccountId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// What sort of account it is.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AccountKind {
+    /// An everyday bank account.
+    #[default]
+    Checking,
+    /// A savings account.
+    Savings,
+    /// A credit card, its balance is negative while money is owed on it.
+    Credit,
+    /// Cash in hand.
+    Cash,
+}
+
+impl AccountKind {
+    /// Every kind, in the order they are listed in the ui.
+    pub const ALL: [AccountKind; 4] = [
+        AccountKind::Checking,
+        AccountKind::Savings,
+        AccountKind::Credit,
+        AccountKind::Cash,
+    ];
+}
+
+impl fmt::Display for AccountKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountKind::Checking => write!(f, "Checking"),
+            AccountKind::Savings => write!(f, "Savings"),
+            AccountKind::Credit => write!(f, "Credit"),
+            AccountKind::Cash => write!(f, "Cash"),
+        }
+    }
+}
+
+/// Case insensitive, so "cash" and "Cash" both parse.
+impl FromStr for AccountKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AccountKind::ALL
+            .into_iter()
+            .find(|k| k.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or(format!("unknown account kind: {}", s))
+    }
+}
+
+/// Somewhere money is kept and spent from.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Account {
+    /// Identifies the account for [`Budgr`] methods.
+    #[serde(default)]
+    pub id: AccountId,
+    /// Shown in the ui and typed into the purchase form, unique among accounts.
+    pub name: String,
+    /// What sort of account it is.
+    #[serde(default)]
+    pub kind: AccountKind,
+    /// What was in the account before any purchase or transfer budgr knows about.
+    #[serde(default)]
+    pub opening_balance: i64,
+    /// When the opening balance was taken, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opening_date: Option<NaiveDate>,
+    /// The statement being reconciled against, see [`Budgr::start_reconciliation`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statement: Option<Statement>,
+    /// The last statement the account was reconciled to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reconciled: Option<Statement>,
+}
+
+/// Money moved from one account to another, like paying off a credit card from checking.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Transfer {
+    /// The account the money left.
+    pub from: AccountId,
+    /// The account the money went to.
+    pub to: AccountId,
+    /// How much was moved, always positive.
+    pub amount: i64,
+    /// When it was moved.
+    pub date: NaiveDate,
+    /// What the transfer was for.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+}
+
+/// Every account and transfer, stored as `accounts.json` in the data folder.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Accounts {
+    /// The accounts in the order they were added.
+    #[serde(default)]
+    pub accounts: Vec<Account>,
+    /// Transfers in the order they were made.
+    #[serde(default)]
+    pub transfers: Vec<Transfer>,
+}
+
+impl Accounts {
+    /// The account with this id.
+    pub fn get(&self, id: AccountId) -> Option<&Account> {
+        self.accounts.iter().find(|a| a.id == id)
+    }
+
+    /// The account with this name, ignoring case.
+    pub fn find(&self, name: &str) -> Option<&Account> {
+        self.accounts.iter().find(|a| a.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// The account's name, or a placeholder for one that has been deleted.
+    pub fn name(&self, id: AccountId) -> String {
+        self.get(id)
+            .map(|a| a.name.clone())
+            .unwrap_or("unknown account".to_string())
+    }
+}
+
+/// One line of an account's history, see [`Budgr::ledger`].
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct LedgerEntry {
+    /// When it happened, purchases without a date come straight after the opening balance.
+    pub date: Option<NaiveDate>,
+    /// The purchase name, or where a transfer went or came from.
+    pub description: String,
+    /// How much the balance changed, negative for money going out.
+    pub amount: i64,
+    /// The balance after this entry.
+    pub balance: i64,
+    /// The purchase this entry is for, `None` for the opening balance and transfers.
+    pub purchase: Option<(LogId, PurchaseId)>,
+}
+
+impl Budgr {
+    /// Add an account, or replace the one with the same id. Account names have to be unique.
+    pub fn save_account(&mut self, account: Account) -> Result<(), String> {
+        if account.name.trim().is_empty() {
+            return Err("an account needs a name".to_string());
+        }
+        let taken = self
+            .accounts
+            .accounts
+            .iter()
+            .any(|a| a.name.eq_ignore_ascii_case(&account.name) && a.id != account.id);
+        if taken {
+            return Err(format!("there is already an account called {}", account.name));
+        }
+        match self.accounts.accounts.iter_mut().find(|a| a.id == account.id) {
+            Some(existing) => *existing = account,
+            None => self.accounts.accounts.push(account),
+        }
+        self.unsaved = true;
+        Ok(())
+    }
+
+    /// Delete an account, fails while a purchase or transfer still uses it.
+    pub fn remove_account(&mut self, id: AccountId) -> Result<(), String> {
+        let name = self.accounts.name(id);
+        let purchases = self
+            .logs
+            .iter()
+            .flat_map(|log| log.purchases.iter())
+            .filter(|p| p.account == Some(id))
+            .count();
+        let transfers = self
+            .accounts
+            .transfers
+            .iter()
+            .filter(|t| t.from == id || t.to == id)
+            .count();
+        if purchases + transfers > 0 {
+            return Err(format!(
+                "{} still has {} purchases and {} transfers",
+                name, purchases, transfers
+            ));
+        }
+
+        let before = self.accounts.accounts.len();
+        self.accounts.accounts.retain(|a| a.id != id);
+        if self.accounts.accounts.len() == before {
+            return Err(format!("ERROR: no account with id {}", id));
+        }
+        self.unsaved = true;
+        Ok(())
+    }
+
+    /// Say which account a purchase was paid from, `None` leaves it unassigned.
+    pub fn assign_account(&mut self, log: LogId, purchase: PurchaseId, account: Option<AccountId>) -> Result<(), String> {
+        if let Some(id) = account {
+            self.accounts.get(id).ok_or(format!("ERROR: no account with id {}", id))?;
+        }
+        let p = self.purchase_mut(log, purchase)?;
+        check_unlocked(p)?;
+        p.account = account;
+        self.unsaved = true;
+        Ok(())
+    }
+
+    /// Move money between two accounts.
+    pub fn transfer(&mut self, transfer: Transfer) -> Result<(), String> {
+        if transfer.amount <= 0 {
+            return Err("a transfer has to move more than 0".to_string());
+        }
+        if transfer.from == transfer.to {
+            return Err("a transfer needs two different accounts".to_string());
+        }
+        for id in [transfer.from, transfer.to] {
+            self.accounts.get(id).ok_or(format!("ERROR: no account with id {}", id))?;
+        }
+        self.accounts.transfers.push(transfer);
+        self.unsaved = true;
+        Ok(())
+    }
+
+    /// Delete the transfer at `index`.
+    pub fn remove_transfer(&mut self, index: usize) -> Result<(), String> {
+        if index >= self.accounts.transfers.len() {
+            return Err("ERROR: no such transfer".to_string());
+        }
+        self.accounts.transfers.remove(index);
+        self.unsaved = true;
+        Ok(())
+    }
+
+    /// An account's opening balance followed by every purchase and transfer on it, oldest first,
+    /// each with the balance after it.
+    pub fn ledger(&self, id: AccountId) -> Vec<LedgerEntry> {
+        let Some(account) = self.accounts.get(id) else {
+            return Vec::new();
+        };
+
+        let purchases = self.logs.iter().flat_map(|log| {
+            log.purchases
+                .iter()
+                .filter(|p| p.account == Some(id))
+                .map(|p| (p.date, p.name.clone(), -p.cost, Some((log.id, p.id))))
+        });
+        let transfers = self.accounts.transfers.iter().filter_map(|t| {
+            let (description, amount) = match (t.from == id, t.to == id) {
+                (true, _) => (format!("to {}", self.accounts.name(t.to)), -t.amount),
+                (_, true) => (format!("from {}", self.accounts.name(t.from)), t.amount),
+                _ => return None,
+            };
+            let description = match t.note.is_empty() {
+                true => description,
+                false => format!("{} ({})", description, t.note),
+            };
+            Some((Some(t.date), description, amount, None))
+        });
+        let mut changes = purchases.chain(transfers).collect::<Vec<_>>();
+        // stable, so entries on the same day stay in the order they were added
+        changes.sort_by_key(|(date, ..)| *date);
+
+        let mut balance = account.opening_balance;
+        let mut ledger = vec![LedgerEntry {
+            date: account.opening_date,
+            description: "opening balance".to_string(),
+            amount: account.opening_balance,
+            balance,
+            purchase: None,
+        }];
+        for (date, description, amount, purchase) in changes {
+            balance += amount;
+            ledger.push(LedgerEntry {
+                date,
+                description,
+                amount,
+                balance,
+                purchase,
+            });
+        }
+        ledger
+    }
+
+    /// What is in an account now: the opening balance, less purchases, plus transfers in, less transfers out.
+    pub fn balance(&self, id: AccountId) -> i64 {
+        self.ledger(id).last().map(|entry| entry.balance).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::{today, PurchaseType};
+    use crate::testing::day;
+
+    // a food log and two accounts, checking starts with 1000 in it
+    fn budgr_with_accounts() -> (Budgr, LogId, AccountId, AccountId) {
+        let mut budgr = Budgr::new();
+        let food = budgr.new_log("food".to_string()).unwrap();
+        let checking = Account {
+            name: "checking".to_string(),
+            opening_balance: 1000,
+            opening_date: Some(day("2025-01-01")),
+            ..Default::default()
+        };
+        let card = Account {
+            name: "card".to_string(),
+            kind: AccountKind::Credit,
+            ..Default::default()
+        };
+        let (checking_id, card_id) = (checking.id, card.id);
+        budgr.save_account(checking).unwrap();
+        budgr.save_account(card).unwrap();
+        (budgr, food, checking_id, card_id)
+    }
+
+    fn paid_from(budgr: &mut Budgr, log: LogId, account: AccountId, name: &str, cost: i64, date: &str) -> PurchaseId {
+        let id = budgr.add_purchase(log, name.to_string(), cost, PurchaseType::Groceries, day(date)).unwrap();
+        budgr.assign_account(log, id, Some(account)).unwrap();
+        id
+    }
+
+    fn transfer(from: AccountId, to: AccountId, amount: i64, date: &str) -> Transfer {
+        Transfer {
+            from,
+            to,
+            amount,
+            date: day(date),
+            note: String::new(),
+        }
+    }
+
+    #[test]
+    fn purchases_come_out_of_the_opening_balance() {
+        let (mut budgr, food, checking, card) = budgr_with_accounts();
+        paid_from(&mut budgr, food, checking, "tesco", 30, "2025-02-01");
+        paid_from(&mut budgr, food, checking, "aldi", 20, "2025-02-02");
+        // not paid from any account
+        budgr.add_purchase(food, "market".to_string(), 5, PurchaseType::Groceries, today()).unwrap();
+
+        assert_eq!(budgr.balance(checking), 950);
+        assert_eq!(budgr.balance(card), 0);
+        let ledger = budgr.ledger(checking);
+        assert_eq!(ledger.len(), 3);
+        assert_eq!(
+            ledger[0],
+            LedgerEntry {
+                date: Some(day("2025-01-01")),
+                description: "opening balance".to_string(),
+                amount: 1000,
+                balance: 1000,
+                purchase: None,
+            }
+        );
+        assert_eq!((ledger[1].description.as_str(), ledger[1].amount, ledger[1].balance), ("tesco", -30, 970));
+        assert_eq!(ledger[1].purchase.map(|(log, _)| log), Some(food));
+    }
+
+    #[test]
+    fn transfers_move_money_without_being_spending() {
+        let (mut budgr, food, checking, card) = budgr_with_accounts();
+        paid_from(&mut budgr, food, card, "tesco", 200, "2025-02-01");
+        let (total, daily) = (budgr.get_total(food).unwrap(), budgr.daily_totals());
+
+        budgr.transfer(transfer(checking, card, 200, "2025-02-28")).unwrap();
+        budgr.transfer(Transfer {
+            note: "refund".to_string(),
+            ..transfer(card, checking, 50, "2025-03-02")
+        })
+        .unwrap();
+
+        assert_eq!(budgr.balance(checking), 850);
+        assert_eq!(budgr.balance(card), -50);
+        assert_eq!(budgr.get_total(food).unwrap(), total);
+        assert_eq!(budgr.daily_totals(), daily);
+        let descriptions = budgr.ledger(checking).into_iter().map(|e| e.description).collect::<Vec<String>>();
+        assert_eq!(descriptions, ["opening balance", "to card", "from card (refund)"]);
+    }
+
+    #[test]
+    fn ledger_is_in_date_order_with_a_running_balance() {
+        let (mut budgr, food, checking, card) = budgr_with_accounts();
+        paid_from(&mut budgr, food, checking, "late", 10, "2025-03-10");
+        paid_from(&mut budgr, food, checking, "early", 20, "2025-03-03");
+        budgr.transfer(transfer(card, checking, 100, "2025-03-05")).unwrap();
+        let undated = paid_from(&mut budgr, food, checking, "undated", 5, "2025-03-20");
+        budgr.logs[0].purchases.iter_mut().find(|p| p.id == undated).unwrap().date = None;
+
+        let ledger = budgr.ledger(checking);
+        let rows = ledger
+            .iter()
+            .map(|e| (e.description.as_str(), e.amount, e.balance))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            [
+                ("opening balance", 1000, 1000),
+                ("undated", -5, 995),
+                ("early", -20, 975),
+                ("from card", 100, 1075),
+                ("late", -10, 1065),
+            ]
+        );
+        assert_eq!(budgr.balance(checking), 1065);
+    }
+
+    #[test]
+    fn transfers_need_two_accounts_and_an_amount() {
+        let (mut budgr, _, checking, card) = budgr_with_accounts();
+        assert!(budgr.transfer(transfer(checking, card, 0, "2025-02-01")).is_err());
+        assert!(budgr.transfer(transfer(checking, checking, 10, "2025-02-01")).is_err());
+        assert!(budgr.transfer(transfer(checking, AccountId::new(), 10, "2025-02-01")).is_err());
+        assert!(budgr.accounts.transfers.is_empty());
+    }
+
+    #[test]
+    fn accounts_in_use_are_not_removed() {
+        let (mut budgr, food, checking, card) = budgr_with_accounts();
+        let tesco = paid_from(&mut budgr, food, checking, "tesco", 30, "2025-02-01");
+        budgr.transfer(transfer(checking, card, 10, "2025-02-01")).unwrap();
+
+        assert_eq!(
+            budgr.remove_account(checking),
+            Err("checking still has 1 purchases and 1 transfers".to_string())
+        );
+        budgr.assign_account(food, tesco, None).unwrap();
+        budgr.remove_transfer(0).unwrap();
+        budgr.remove_account(checking).unwrap();
+        assert!(budgr.accounts.get(checking).is_none());
+        assert!(budgr.accounts.get(card).is_some());
+    }
+}
@@ -0,0 +1,112 @@
+//! Receipts and other files attached to purchases.
+//!
+//! Attached files are copied into the attachments folder and named after the sha256 of their
+//! contents, so attaching the same file twice stores it once.
+
+use crate::log::{get_data_dir, Budgr, LogId, PurchaseId};
+use crate::logfile::logln;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::{self, create_dir_all};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Where attached files are kept.
+pub fn get_attachments_dir() -> PathBuf {
+    let mut path = get_data_dir();
+    path.push("attachments/");
+    path
+}
+
+/// A file attached to a purchase.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Attachment {
+    /// The file's name when it was attached, for showing to the user.
+    pub name: String,
+    /// Hex sha256 of the contents, plus the original extension, e.g. `ab12...ef.pdf`.
+    pub file: String,
+}
+
+impl Attachment {
+    /// Copy a file into the attachments folder, unless the same contents are already there.
+    pub fn store(path: &Path) -> io::Result<Attachment> {
+        let contents = fs::read(path)?;
+        let hash = format!("{:x}", Sha256::digest(&contents));
+        // the extension stays so the system opener knows what the file is
+        let file = match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) => format!("{}.{}", hash, ext.to_lowercase()),
+            None => hash,
+        };
+
+        let attachment = Attachment {
+            name: path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or(file.clone()),
+            file,
+        };
+        let stored = attachment.path();
+        if !stored.exists() {
+            create_dir_all(get_attachments_dir())?;
+            fs::write(&stored, contents)?;
+            logln!("stored attachment {}", stored.display());
+        }
+        Ok(attachment)
+    }
+
+    /// Where the stored copy is.
+    pub fn path(&self) -> PathBuf {
+        let mut path = get_attachments_dir();
+        path.push(&self.file);
+        path
+    }
+}
+
+impl Budgr {
+    /// Copy a file into the attachments folder and attach it to a purchase.
+    pub fn attach(&mut self, log: LogId, purchase: PurchaseId, path: &Path) -> Result<(), String> {
+        let attachment = Attachment::store(path)
+            .map_err(|err| format!("could not attach {}: {}", path.display(), err))?;
+        let p = self.purchase_mut(log, purchase)?;
+        if !p.attachments.contains(&attachment) {
+            p.attachments.push(attachment);
+            self.unsaved = true;
+        }
+        Ok(())
+    }
+
+    /// Delete stored files no purchase refers to anymore, returns how many were deleted.
+    ///
+    /// Purchases in the trash keep their attachments, so this only frees files once they are purged.
+    pub fn remove_orphaned_attachments(&self) -> io::Result<usize> {
+        let used = self
+            .logs
+            .iter()
+            .chain(self.trash.logs.iter().map(|t| &t.log))
+            .flat_map(|log| log.purchases.iter())
+            .chain(self.trash.purchases.iter().map(|t| &t.purchase))
+            .flat_map(|p| p.attachments.iter())
+            .map(|a| a.file.as_str())
+            .collect::<HashSet<&str>>();
+
+        let entries = match fs::read_dir(get_attachments_dir()) {
+            Ok(entries) => entries,
+            // nothing has been attached yet
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(err),
+        };
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            if !used.contains(name.to_string_lossy().as_ref()) {
+                fs::remove_file(entry.path())?;
+                removed += 1;
+            }
+        }
+        if removed > 0 {
+            logln!("removed {} orphaned attachments", removed);
+        }
+        Ok(removed)
+    }
+}
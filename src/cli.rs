@@ -0,0 +1,67 @@
+use budgr::log::Budgr;
+use budgr::report::Period;
+
+use color_eyre::eyre::{eyre, Result};
+
+const REPORT_USAGE: &str = "usage: budgr report [week|month|year] [--log NAME] [--json]";
+const REIMBURSEMENTS_USAGE: &str = "usage: budgr reimbursements [--json]";
+
+// budgr report [week|month|year] [--log NAME] [--json]
+// prints a period report to stdout instead of starting the tui
+pub fn report(args: &[String], budgr: &Budgr) -> Result<()> {
+    let mut period = Period::Month;
+    let mut log_name: Option<&String> = None;
+    let mut json = false;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--log" => log_name = Some(args.next().ok_or(eyre!(REPORT_USAGE))?),
+            "-h" | "--help" => {
+                println!("{}", REPORT_USAGE);
+                return Ok(());
+            }
+            other => period = other.parse().map_err(|err| eyre!("{}\n{}", err, REPORT_USAGE))?,
+        }
+    }
+
+    let report = match log_name {
+        Some(name) => budgr
+            .logs
+            .iter()
+            .find(|log| &log.name == name)
+            .ok_or(eyre!("no log named {}", name))?
+            .report(period),
+        None => budgr.report(period),
+    };
+
+    match json {
+        true => println!("{}", report.to_json()?),
+        false => print!("{}", report),
+    }
+    Ok(())
+}
+
+// budgr reimbursements [--json]
+// prints purchases tagged reimbursable that haven't been tagged reimbursed yet
+pub fn reimbursements(args: &[String], budgr: &Budgr) -> Result<()> {
+    let mut json = false;
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            "-h" | "--help" => {
+                println!("{}", REIMBURSEMENTS_USAGE);
+                return Ok(());
+            }
+            other => return Err(eyre!("unknown argument: {}\n{}", other, REIMBURSEMENTS_USAGE)),
+        }
+    }
+
+    let reimbursements = budgr.reimbursements();
+    match json {
+        true => println!("{}", reimbursements.to_json()?),
+        false => print!("{}", reimbursements),
+    }
+    Ok(())
+}
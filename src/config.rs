@@ -0,0 +1,58 @@
+//! User settings.
+
+use dirs::config_dir;
+use serde::{Deserialize, Serialize};
+use std::fs::{create_dir_all, File};
+use std::io::BufReader;
+use std::path::PathBuf;
+
+fn get_config_path() -> PathBuf {
+    [config_dir().unwrap(), "budgr/config.json".into()]
+        .iter()
+        .collect()
+}
+
+/// User settings, read from `~/.config/budgr/config.json`.
+///
+/// Any field missing from the file falls back to its default.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Config {
+    /// Trashed logs and purchases older than this are purged on startup, 0 keeps them forever.
+    pub trash_max_age_days: u64,
+    /// Name of a built in theme (dark, light, high-contrast) or one from `~/.config/budgr/themes/`.
+    pub theme: String,
+    /// Unsaved changes are written out this often while running, 0 only saves on exit.
+    pub autosave_seconds: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            trash_max_age_days: 30,
+            theme: "dark".to_string(),
+            autosave_seconds: 60,
+        }
+    }
+}
+
+impl Config {
+    /// Read the config file, a missing or broken file is not fatal and gives the defaults.
+    pub fn load() -> Self {
+        File::open(get_config_path())
+            .ok()
+            .and_then(|file| serde_json::from_reader(BufReader::new(file)).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the config file, creating the folder if needed.
+    pub fn save(&self) -> Result<(), std::io::Error> {
+        let path = get_config_path();
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir)?;
+        }
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,628 @@
+//! Loans and money lent, with amortization tables and payoff plans.
+//!
+//! A debt's payments are the purchases in its payments log, so paying a car loan is recorded
+//! like any other purchase and the outstanding balance goes down by itself. Interest is added
+//! on each scheduled payment date, on the balance owed that day.
+//!
+//! Money paid back on a lent debt isn't spending, so it is kept on the debt and added to the
+//! month's income instead of going in a log.
+
+use crate::log::{today, Budgr, LogId, PurchaseId, PurchaseType};
+use crate::report::Period;
+use chrono::{Duration, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// The most payments an amortization table or payoff plan runs for, 100 years of monthly payments.
+pub const MAX_PAYMENTS: usize = 1200;
+
+/// Which way the money went.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DebtKind {
+    /// Money owed, like a car loan.
+    #[default]
+    Borrowed,
+    /// Money someone else owes, like a loan to a teammate. Left out of payoff plans.
+    Lent,
+}
+
+impl DebtKind {
+    /// Every kind, in the order they are listed in the ui.
+    pub const ALL: [DebtKind; 2] = [DebtKind::Borrowed, DebtKind::Lent];
+}
+
+impl fmt::Display for DebtKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DebtKind::Borrowed => write!(f, "Borrowed"),
+            DebtKind::Lent => write!(f, "Lent"),
+        }
+    }
+}
+
+/// Case insensitive, so "lent" and "Lent" both parse.
+impl FromStr for DebtKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DebtKind::ALL
+            .into_iter()
+            .find(|k| k.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or(format!("unknown debt kind: {}", s))
+    }
+}
+
+/// How often a payment is due.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Schedule {
+    /// Every 7 days.
+    Weekly,
+    /// Every 14 days.
+    Fortnightly,
+    /// On the same day every month.
+    #[default]
+    Monthly,
+}
+
+impl Schedule {
+    /// Every schedule, in the order they are listed in the ui.
+    pub const ALL: [Schedule; 3] = [Schedule::Weekly, Schedule::Fortnightly, Schedule::Monthly];
+
+    /// How many payments fall in a year.
+    pub fn payments_per_year(&self) -> i64 {
+        match self {
+            Schedule::Weekly => 52,
+            Schedule::Fortnightly => 26,
+            Schedule::Monthly => 12,
+        }
+    }
+
+    /// The date of the `n`th payment after `start`.
+    pub fn nth_after(&self, start: NaiveDate, n: usize) -> NaiveDate {
+        match self {
+            Schedule::Weekly => start + Duration::weeks(n as i64),
+            Schedule::Fortnightly => start + Duration::weeks(2 * n as i64),
+            Schedule::Monthly => start.checked_add_months(Months::new(n as u32)).unwrap_or(NaiveDate::MAX),
+        }
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Schedule::Weekly => write!(f, "Weekly"),
+            Schedule::Fortnightly => write!(f, "Fortnightly"),
+            Schedule::Monthly => write!(f, "Monthly"),
+        }
+    }
+}
+
+/// Case insensitive, so "monthly" and "Monthly" both parse.
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Schedule::ALL
+            .into_iter()
+            .find(|k| k.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or(format!("unknown payment schedule: {}", s))
+    }
+}
+
+/// A loan taken out or money lent to someone.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Debt {
+    /// Shown in the debts view.
+    pub name: String,
+    /// Whether the money is owed or owed to us.
+    #[serde(default)]
+    pub kind: DebtKind,
+    /// How much was borrowed or lent.
+    pub principal: i64,
+    /// Yearly interest rate in percent, e.g. 6.9.
+    #[serde(default)]
+    pub rate: f64,
+    /// The amount due on each payment date.
+    pub payment: i64,
+    /// How often a payment is due.
+    #[serde(default)]
+    pub schedule: Schedule,
+    /// When the money was borrowed, the first payment is due one period later.
+    pub start: NaiveDate,
+    /// The log payments are recorded in, every purchase in it counts as a payment.
+    pub log: LogId,
+    /// Repayments received on a lent debt, see [`Budgr::record_debt_payment`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub repaid: Vec<(NaiveDate, i64)>,
+}
+
+impl Debt {
+    /// The interest added on each payment date, as a fraction of the balance.
+    pub fn period_rate(&self) -> f64 {
+        self.rate / 100.0 / self.schedule.payments_per_year() as f64
+    }
+
+    // the interest due on a balance for one period, rounded to whole units
+    fn interest(&self, balance: i64) -> i64 {
+        (balance as f64 * self.period_rate()).round() as i64
+    }
+
+    /// Every scheduled payment from the start until the debt is paid off, if the payments were
+    /// made as scheduled. Empty when a payment doesn't cover the interest.
+    pub fn amortization(&self) -> Vec<AmortizationRow> {
+        self.schedule_from(self.principal, 1)
+    }
+
+    // the scheduled payments from the `first`th one on, starting with `balance` owed
+    fn schedule_from(&self, mut balance: i64, first: usize) -> Vec<AmortizationRow> {
+        let mut rows = Vec::new();
+        if balance > 0 && self.payment <= self.interest(balance) {
+            return rows;
+        }
+        let mut n = first;
+        while balance > 0 && rows.len() < MAX_PAYMENTS {
+            let interest = self.interest(balance);
+            let payment = self.payment.min(balance + interest);
+            balance += interest - payment;
+            rows.push(AmortizationRow {
+                date: self.schedule.nth_after(self.start, n),
+                payment,
+                interest,
+                principal: payment - interest,
+                balance,
+            });
+            n += 1;
+        }
+        rows
+    }
+}
+
+/// One scheduled payment, see [`Debt::amortization`].
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct AmortizationRow {
+    /// When the payment is due.
+    pub date: NaiveDate,
+    /// What is paid, the last payment is smaller when it only has to clear the balance.
+    pub payment: i64,
+    /// The part of the payment that goes to interest.
+    pub interest: i64,
+    /// The part of the payment that pays down the balance.
+    pub principal: i64,
+    /// What is still owed after the payment.
+    pub balance: i64,
+}
+
+/// Every debt, stored as `debts.json` in the data folder.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Debts {
+    /// The debts in the order they were added.
+    #[serde(default)]
+    pub debts: Vec<Debt>,
+    /// Paid each month on top of the scheduled payments when following a payoff plan.
+    #[serde(default)]
+    pub extra_monthly: i64,
+}
+
+/// Which debt gets the money left over once every scheduled payment is made.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Strategy {
+    /// The smallest balance first, for the quickest wins.
+    Snowball,
+    /// The highest interest rate first, for the least interest.
+    Avalanche,
+}
+
+impl Strategy {
+    /// Both strategies, in the order they are listed in the ui.
+    pub const ALL: [Strategy; 2] = [Strategy::Snowball, Strategy::Avalanche];
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Strategy::Snowball => write!(f, "Snowball"),
+            Strategy::Avalanche => write!(f, "Avalanche"),
+        }
+    }
+}
+
+/// When every borrowed debt is paid off following a strategy, see [`Budgr::payoff_plan`].
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PayoffPlan {
+    /// The strategy followed.
+    pub strategy: Strategy,
+    /// Each debt's name and the month it is paid off in, in the order they are paid off.
+    pub paid_off: Vec<(String, NaiveDate)>,
+    /// When the last debt is paid off, today if nothing is owed, `None` if the payments never get there.
+    pub debt_free: Option<NaiveDate>,
+    /// The interest paid along the way.
+    pub total_interest: i64,
+}
+
+impl Budgr {
+    /// The payments made on a debt: the date and cost of every purchase in its payments log,
+    /// followed by the repayments received on a lent debt.
+    pub fn debt_payments(&self, debt: &Debt) -> Vec<(Option<NaiveDate>, i64)> {
+        let mut payments = self
+            .log(debt.log)
+            .map(|log| log.purchases.iter().map(|p| (p.date, p.cost)).collect::<Vec<_>>())
+            .unwrap_or_default();
+        payments.extend(debt.repaid.iter().map(|(date, amount)| (Some(*date), *amount)));
+        payments
+    }
+
+    /// What is still owed today: the principal plus interest up to today, less every payment.
+    ///
+    /// Payments without a date count from the start. 0 once the debt is paid off.
+    pub fn outstanding(&self, debt: &Debt) -> i64 {
+        let (balance, _) = self.balance_on(debt, today());
+        balance.max(0)
+    }
+
+    // the balance on `date`, and how many payment dates have passed by then
+    fn balance_on(&self, debt: &Debt, date: NaiveDate) -> (i64, usize) {
+        let mut payments = self
+            .debt_payments(debt)
+            .into_iter()
+            .map(|(d, amount)| (d.unwrap_or(debt.start), amount))
+            .filter(|(d, _)| *d <= date)
+            .collect::<Vec<_>>();
+        payments.sort_by_key(|(d, _)| *d);
+
+        let mut balance = debt.principal;
+        let mut n = 1;
+        // interest is due on a payment date before that day's payments go in
+        let mut accrue_until = |balance: &mut i64, until: NaiveDate| {
+            while debt.schedule.nth_after(debt.start, n) <= until && n <= MAX_PAYMENTS {
+                if *balance > 0 {
+                    *balance = balance.saturating_add(debt.interest(*balance));
+                }
+                n += 1;
+            }
+        };
+        for (d, amount) in payments {
+            accrue_until(&mut balance, d);
+            balance -= amount;
+        }
+        accrue_until(&mut balance, date);
+        (balance, n - 1)
+    }
+
+    /// The payments still to come if they are made as scheduled, starting from what is owed today.
+    ///
+    /// Empty once the debt is paid off, or when a payment doesn't cover the interest.
+    pub fn remaining_schedule(&self, debt: &Debt) -> Vec<AmortizationRow> {
+        let (balance, passed) = self.balance_on(debt, today());
+        debt.schedule_from(balance, passed + 1)
+    }
+
+    /// Record a payment on the debt at `index`, returns the purchase it was added to the payments log as.
+    ///
+    /// A repayment on a lent debt is income rather than spending, so it is kept on the debt and added
+    /// to the month's envelope income instead, and `None` is returned.
+    pub fn record_debt_payment(&mut self, index: usize, amount: i64, date: NaiveDate) -> Result<Option<PurchaseId>, String> {
+        let debt = self.debts.debts.get_mut(index).ok_or("ERROR: no such debt")?;
+        if amount <= 0 {
+            return Err("a payment has to be more than 0".to_string());
+        }
+        if debt.kind == DebtKind::Lent {
+            debt.repaid.push((date, amount));
+            let month = Period::Month.start_of(date);
+            self.envelopes.months.entry(month).or_default().income += amount;
+            self.unsaved = true;
+            return Ok(None);
+        }
+        let (log, name) = (debt.log, format!("{} payment", debt.name));
+        self.add_purchase(log, name, amount, PurchaseType::Bill, date).map(Some)
+    }
+
+    /// How every borrowed debt gets paid off month by month, paying each debt's scheduled payment
+    /// and putting [`Debts::extra_monthly`], and the payments of debts already paid off, towards
+    /// the one the strategy picks.
+    pub fn payoff_plan(&self, strategy: Strategy) -> PayoffPlan {
+        // each debt on a monthly footing: name, balance, monthly rate, yearly rate, monthly payment
+        let mut debts = self
+            .debts
+            .debts
+            .iter()
+            .filter(|d| d.kind == DebtKind::Borrowed)
+            .map(|d| {
+                let monthly_payment = d.payment * d.schedule.payments_per_year() / 12;
+                (d.name.clone(), self.outstanding(d), d.rate / 100.0 / 12.0, d.rate, monthly_payment)
+            })
+            .filter(|(_, balance, ..)| *balance > 0)
+            .collect::<Vec<_>>();
+        let budget = debts.iter().map(|(.., payment)| payment).sum::<i64>() + self.debts.extra_monthly.max(0);
+
+        let mut plan = PayoffPlan {
+            strategy,
+            paid_off: Vec::new(),
+            debt_free: None,
+            total_interest: 0,
+        };
+        let start = today();
+        if debts.is_empty() {
+            plan.debt_free = Some(start);
+            return plan;
+        }
+        // by index, two debts can share a name
+        let mut done = vec![false; debts.len()];
+        for month in 1..=MAX_PAYMENTS {
+            let date = start.checked_add_months(Months::new(month as u32)).unwrap_or(NaiveDate::MAX);
+            for (_, balance, rate, ..) in debts.iter_mut().filter(|(_, balance, ..)| *balance > 0) {
+                // a debt whose payment doesn't cover the interest grows for the whole simulation
+                let interest = (*balance as f64 * *rate).round() as i64;
+                *balance = balance.saturating_add(interest);
+                plan.total_interest = plan.total_interest.saturating_add(interest);
+            }
+
+            // scheduled payments first, then whatever is left goes down the strategy's order
+            let mut left = budget;
+            for (_, balance, _, _, payment) in debts.iter_mut() {
+                let paid = (*payment).min(*balance).max(0);
+                *balance -= paid;
+                left -= paid;
+            }
+            let mut order = (0..debts.len()).filter(|i| debts[*i].1 > 0).collect::<Vec<_>>();
+            match strategy {
+                Strategy::Snowball => order.sort_by_key(|i| debts[*i].1),
+                Strategy::Avalanche => order.sort_by(|a, b| debts[*b].3.total_cmp(&debts[*a].3)),
+            }
+            for i in order {
+                let paid = left.min(debts[i].1);
+                debts[i].1 -= paid;
+                left -= paid;
+            }
+
+            for (i, (name, balance, ..)) in debts.iter().enumerate() {
+                if *balance <= 0 && !done[i] {
+                    done[i] = true;
+                    plan.paid_off.push((name.clone(), date));
+                }
+            }
+            if done.iter().all(|d| *d) {
+                plan.debt_free = Some(date);
+                break;
+            }
+        }
+        plan
+    }
+
+    /// Replace the debt at `index`, or add it to the end with `None`.
+    ///
+    /// Repayments already received on the debt being replaced are kept.
+    pub fn save_debt(&mut self, index: Option<usize>, mut debt: Debt) -> Result<(), String> {
+        if debt.name.trim().is_empty() {
+            return Err("a debt needs a name".to_string());
+        }
+        if debt.principal <= 0 {
+            return Err("a debt needs a principal above 0".to_string());
+        }
+        if debt.payment <= 0 {
+            return Err("a debt needs a payment above 0".to_string());
+        }
+        if !debt.rate.is_finite() {
+            return Err("the interest rate has to be a number".to_string());
+        }
+        if debt.rate < 0.0 {
+            return Err("the interest rate can't be negative".to_string());
+        }
+        self.log(debt.log).ok_or(format!("ERROR: no log with id {}", debt.log))?;
+        match index {
+            Some(i) => {
+                let existing = self.debts.debts.get_mut(i).ok_or("ERROR: no such debt")?;
+                debt.repaid = std::mem::take(&mut existing.repaid);
+                *existing = debt;
+            }
+            None => self.debts.debts.push(debt),
+        }
+        self.unsaved = true;
+        Ok(())
+    }
+
+    /// Delete the debt at `index`, its payments stay in their log.
+    pub fn remove_debt(&mut self, index: usize) -> Result<(), String> {
+        if index >= self.debts.debts.len() {
+            return Err("ERROR: no such debt".to_string());
+        }
+        self.debts.debts.remove(index);
+        self.unsaved = true;
+        Ok(())
+    }
+
+    /// Set what is paid each month on top of the scheduled payments in payoff plans.
+    pub fn set_extra_monthly(&mut self, amount: i64) -> Result<(), String> {
+        if amount < 0 {
+            return Err("the extra payment can't be negative".to_string());
+        }
+        self.debts.extra_monthly = amount;
+        self.unsaved = true;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{day, months_from_today};
+
+    // a monthly debt starting today, with its payments going in a log of its own
+    fn debt(budgr: &mut Budgr, name: &str, kind: DebtKind, principal: i64, rate: f64, payment: i64) -> Debt {
+        let log = budgr.new_log(format!("{}-payments{}", name, budgr.logs.len())).unwrap();
+        Debt {
+            name: name.to_string(),
+            kind,
+            principal,
+            rate,
+            payment,
+            schedule: Schedule::Monthly,
+            start: today(),
+            log,
+            repaid: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn nothing_owed_is_debt_free_today() {
+        let mut budgr = Budgr::new();
+        assert_eq!(budgr.payoff_plan(Strategy::Snowball).debt_free, Some(today()));
+
+        let lent = debt(&mut budgr, "sam", DebtKind::Lent, 500, 0.0, 50);
+        budgr.save_debt(None, lent).unwrap();
+        for strategy in [Strategy::Snowball, Strategy::Avalanche] {
+            let plan = budgr.payoff_plan(strategy);
+            assert_eq!(plan.debt_free, Some(today()));
+            assert!(plan.paid_off.is_empty());
+            assert_eq!(plan.total_interest, 0);
+        }
+    }
+
+    #[test]
+    fn debts_sharing_a_name_are_both_paid_off() {
+        let mut budgr = Budgr::new();
+        for _ in 0..2 {
+            let card = debt(&mut budgr, "card", DebtKind::Borrowed, 1000, 0.0, 100);
+            budgr.save_debt(None, card).unwrap();
+        }
+        let plan = budgr.payoff_plan(Strategy::Snowball);
+        assert_eq!(
+            plan.paid_off,
+            vec![("card".to_string(), months_from_today(10)), ("card".to_string(), months_from_today(10))]
+        );
+        assert_eq!(plan.debt_free, Some(months_from_today(10)));
+    }
+
+    #[test]
+    fn amortization_pays_interest_first_and_ends_at_zero() {
+        let mut budgr = Budgr::new();
+        let mut loan = debt(&mut budgr, "loan", DebtKind::Borrowed, 1200, 12.0, 110);
+        loan.start = day("2025-01-15");
+        let rows = loan.amortization();
+
+        assert_eq!(
+            rows[0],
+            AmortizationRow {
+                date: day("2025-02-15"),
+                payment: 110,
+                interest: 12,
+                principal: 98,
+                balance: 1102,
+            }
+        );
+        assert_eq!(rows.len(), 12);
+        let last = rows.last().unwrap();
+        assert_eq!((last.date, last.balance), (day("2026-01-15"), 0));
+        assert!(last.payment < 110);
+        assert_eq!(rows.iter().map(|r| r.principal).sum::<i64>(), 1200);
+        assert!(rows.windows(2).all(|w| w[1].interest <= w[0].interest));
+
+        // 1000 at 2% a month never shrinks with payments of 20
+        let stuck = Debt {
+            principal: 1000,
+            rate: 24.0,
+            payment: 20,
+            ..loan
+        };
+        assert!(stuck.amortization().is_empty());
+    }
+
+    #[test]
+    fn balance_adds_interest_on_each_payment_date_before_the_payment() {
+        let mut budgr = Budgr::new();
+        let mut loan = debt(&mut budgr, "loan", DebtKind::Borrowed, 1000, 12.0, 100);
+        loan.start = day("2025-01-15");
+        budgr.save_debt(None, loan.clone()).unwrap();
+        for date in ["2025-02-15", "2025-03-15", "2025-04-15"] {
+            budgr.record_debt_payment(0, 100, day(date)).unwrap();
+        }
+
+        // 1000 + 10 - 100, 910 + 9 - 100, 819 + 8 - 100
+        assert_eq!(budgr.balance_on(&loan, day("2025-04-15")), (727, 3));
+        // the day before the third payment date neither its interest nor its payment is in
+        assert_eq!(budgr.balance_on(&loan, day("2025-04-14")), (819, 2));
+        // nothing was paid since, so interest keeps going on
+        assert_eq!(budgr.balance_on(&loan, day("2025-05-15")), (734, 4));
+    }
+
+    #[test]
+    fn outstanding_is_zero_once_paid_off() {
+        let mut budgr = Budgr::new();
+        let mut loan = debt(&mut budgr, "loan", DebtKind::Borrowed, 1000, 12.0, 100);
+        loan.start = day("2025-01-15");
+        budgr.save_debt(None, loan.clone()).unwrap();
+        assert!(budgr.outstanding(&loan) > 1000);
+
+        // a payment without a date counts from the start
+        budgr.record_debt_payment(0, 5000, today()).unwrap();
+        let log = budgr.log_index(loan.log).unwrap();
+        budgr.logs[log].purchases[0].date = None;
+        assert_eq!(budgr.outstanding(&loan), 0);
+        assert!(budgr.remaining_schedule(&loan).is_empty());
+    }
+
+    #[test]
+    fn snowball_clears_the_smallest_first_and_avalanche_the_priciest() {
+        let mut budgr = Budgr::new();
+        let store = debt(&mut budgr, "store", DebtKind::Borrowed, 300, 5.0, 10);
+        let credit = debt(&mut budgr, "credit", DebtKind::Borrowed, 1000, 25.0, 30);
+        budgr.save_debt(None, store).unwrap();
+        budgr.save_debt(None, credit).unwrap();
+        budgr.set_extra_monthly(200).unwrap();
+
+        let names = |plan: &PayoffPlan| plan.paid_off.iter().map(|(name, _)| name.clone()).collect::<Vec<String>>();
+        let snowball = budgr.payoff_plan(Strategy::Snowball);
+        let avalanche = budgr.payoff_plan(Strategy::Avalanche);
+        assert_eq!(names(&snowball), ["store", "credit"]);
+        assert_eq!(names(&avalanche), ["credit", "store"]);
+        assert!(snowball.debt_free.is_some() && avalanche.debt_free.is_some());
+        assert!(avalanche.total_interest < snowball.total_interest);
+    }
+
+    #[test]
+    fn a_rate_that_is_not_a_number_is_refused() {
+        let mut budgr = Budgr::new();
+        for rate in [f64::INFINITY, f64::NAN, -1.0] {
+            let card = debt(&mut budgr, "card", DebtKind::Borrowed, 1000, rate, 100);
+            assert!(budgr.save_debt(None, card).is_err());
+        }
+        assert!(budgr.debts.debts.is_empty());
+    }
+
+    #[test]
+    fn a_payment_that_never_covers_the_interest_does_not_overflow() {
+        let mut budgr = Budgr::new();
+        let mut loan = debt(&mut budgr, "loan", DebtKind::Borrowed, 1_000_000, 1000.0, 1);
+        loan.start = day("1990-01-01");
+        budgr.save_debt(None, loan.clone()).unwrap();
+
+        assert_eq!(budgr.outstanding(&loan), i64::MAX);
+        assert_eq!(budgr.payoff_plan(Strategy::Avalanche).debt_free, None);
+    }
+
+    #[test]
+    fn a_lent_repayment_is_income_not_spending() {
+        let mut budgr = Budgr::new();
+        let lent = debt(&mut budgr, "sam", DebtKind::Lent, 500, 0.0, 50);
+        budgr.save_debt(None, lent).unwrap();
+        let month = Period::Month.start_of(today());
+        let bills = |budgr: &Budgr| {
+            let budget = budgr.envelope_budget(month);
+            budget.envelopes.iter().find(|e| e.category == PurchaseType::Bill).unwrap().spent
+        };
+
+        assert_eq!(budgr.record_debt_payment(0, 200, today()), Ok(None));
+        assert_eq!(bills(&budgr), 0);
+        assert!(budgr.logs.iter().all(|log| log.purchases.is_empty()));
+        assert_eq!(budgr.envelope_budget(month).income, 200);
+        assert_eq!(budgr.outstanding(&budgr.debts.debts[0]), 300);
+
+        // editing the debt keeps what was paid back
+        let edited = Debt {
+            name: "sam and co".to_string(),
+            ..budgr.debts.debts[0].clone()
+        };
+        budgr.save_debt(Some(0), Debt { repaid: Vec::new(), ..edited }).unwrap();
+        assert_eq!(budgr.debts.debts[0].repaid, [(today(), 200)]);
+    }
+}
@@ -0,0 +1,252 @@
+//! Envelope budgeting: each month's income is assigned to categories, and whatever is left
+//! in a category, or overspent, rolls over into the next month.
+//!
+//! Overspending stays in its own envelope as a negative rollover and never comes out of the
+//! money left to assign, so it is covered by assigning more to that category. Taking it out
+//! of both would count the same money twice.
+//!
+//! Spending comes from the purchases in every log, by [`Purchase::category_amounts`], so
+//! split purchases count against each of their categories. Purchases without a date, or from
+//! before the first budgeted month, don't touch the envelopes.
+//!
+//! [`Purchase::category_amounts`]: crate::log::Purchase::category_amounts
+
+use crate::log::{Budgr, PurchaseType};
+use crate::report::Period;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Income and assignments for one month.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct EnvelopeMonth {
+    /// Money that came in during the month.
+    #[serde(default)]
+    pub income: i64,
+    /// How much was put into each category's envelope.
+    #[serde(default)]
+    pub assigned: BTreeMap<PurchaseType, i64>,
+}
+
+/// Every budgeted month, keyed by its first day, stored as `envelopes.json` in the data folder.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Envelopes {
+    /// The months that have income or assignments.
+    #[serde(default)]
+    pub months: BTreeMap<NaiveDate, EnvelopeMonth>,
+}
+
+impl Envelopes {
+    /// What was assigned to a category in the month starting on `month`.
+    pub fn assigned(&self, month: NaiveDate, category: PurchaseType) -> i64 {
+        self.months
+            .get(&month)
+            .and_then(|m| m.assigned.get(&category))
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+/// One category's envelope in a month, see [`Budgr::envelope_budget`].
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct Envelope {
+    /// The category the envelope is for.
+    pub category: PurchaseType,
+    /// Left over from earlier months, negative if they were overspent.
+    pub rolled_over: i64,
+    /// Put in this month.
+    pub assigned: i64,
+    /// Spent this month.
+    pub spent: i64,
+    /// What is left to spend: rolled over plus assigned less spent.
+    pub available: i64,
+}
+
+/// The envelopes for one month.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct EnvelopeBudget {
+    /// The first day of the month.
+    pub month: NaiveDate,
+    /// Income recorded for the month.
+    pub income: i64,
+    /// Income up to and including this month that hasn't been put into an envelope yet,
+    /// overspent envelopes don't lower it.
+    pub to_assign: i64,
+    /// One envelope per category, in the order of [`PurchaseType::ALL`].
+    pub envelopes: Vec<Envelope>,
+}
+
+impl Budgr {
+    /// Record the income for the month `month` falls in.
+    pub fn set_income(&mut self, month: NaiveDate, income: i64) {
+        let month = Period::Month.start_of(month);
+        self.envelopes.months.entry(month).or_default().income = income;
+        self.unsaved = true;
+    }
+
+    /// Set how much goes into a category's envelope in the month `month` falls in.
+    pub fn assign(&mut self, month: NaiveDate, category: PurchaseType, amount: i64) {
+        let month = Period::Month.start_of(month);
+        let assigned = &mut self.envelopes.months.entry(month).or_default().assigned;
+        match amount {
+            0 => assigned.remove(&category),
+            amount => assigned.insert(category, amount),
+        };
+        self.unsaved = true;
+    }
+
+    /// What was spent per category in each month, from the purchases in every log.
+    pub fn monthly_category_spending(&self) -> BTreeMap<(NaiveDate, PurchaseType), i64> {
+        let mut spent = BTreeMap::new();
+        for p in self.logs.iter().flat_map(|log| log.purchases.iter()) {
+            let Some(date) = p.date else {
+                continue;
+            };
+            let month = Period::Month.start_of(date);
+            for (category, amount) in p.category_amounts() {
+                *spent.entry((month, category)).or_insert(0) += amount;
+            }
+        }
+        spent
+    }
+
+    /// The envelopes for the month `month` falls in, with everything from earlier months rolled over.
+    pub fn envelope_budget(&self, month: NaiveDate) -> EnvelopeBudget {
+        let month = Period::Month.start_of(month);
+        let spending = self.monthly_category_spending();
+        let spent = |month: NaiveDate, category: PurchaseType| spending.get(&(month, category)).copied().unwrap_or(0);
+
+        // carry every envelope forward from the first budgeted month
+        let mut rolled_over = BTreeMap::<PurchaseType, i64>::new();
+        let mut current = self.envelopes.months.keys().next().copied().unwrap_or(month);
+        while current < month {
+            for category in PurchaseType::ALL {
+                *rolled_over.entry(category).or_insert(0) +=
+                    self.envelopes.assigned(current, category) - spent(current, category);
+            }
+            current = Period::Month.next_start(current);
+        }
+
+        let envelopes = PurchaseType::ALL
+            .into_iter()
+            .map(|category| {
+                let rolled_over = rolled_over.get(&category).copied().unwrap_or(0);
+                let assigned = self.envelopes.assigned(month, category);
+                let spent = spent(month, category);
+                Envelope {
+                    category,
+                    rolled_over,
+                    assigned,
+                    spent,
+                    available: rolled_over + assigned - spent,
+                }
+            })
+            .collect();
+
+        let (income, assigned) = self
+            .envelopes
+            .months
+            .range(..=month)
+            .fold((0, 0), |(income, assigned), (_, m)| {
+                (income + m.income, assigned + m.assigned.values().sum::<i64>())
+            });
+        EnvelopeBudget {
+            month,
+            income: self.envelopes.months.get(&month).map(|m| m.income).unwrap_or(0),
+            to_assign: income - assigned,
+            envelopes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::day;
+
+    // a budgr with one log holding (date, category, cost) purchases
+    fn spending(purchases: &[(&str, PurchaseType, i64)]) -> Budgr {
+        let mut budgr = Budgr::new();
+        let log = budgr.new_log("spending".to_string()).unwrap();
+        for (date, category, cost) in purchases {
+            budgr.add_purchase(log, "purchase".to_string(), *cost, *category, day(date)).unwrap();
+        }
+        budgr
+    }
+
+    fn envelope(budget: &EnvelopeBudget, category: PurchaseType) -> &Envelope {
+        budget.envelopes.iter().find(|e| e.category == category).unwrap()
+    }
+
+    #[test]
+    fn leftovers_and_overspending_roll_over() {
+        let mut budgr = spending(&[
+            ("2025-01-10", PurchaseType::Groceries, 60),
+            ("2025-01-12", PurchaseType::Leisure, 30),
+            ("2025-02-03", PurchaseType::Groceries, 50),
+        ]);
+        budgr.assign(day("2025-01-01"), PurchaseType::Groceries, 100);
+        budgr.assign(day("2025-01-01"), PurchaseType::Leisure, 10);
+        budgr.assign(day("2025-02-01"), PurchaseType::Groceries, 100);
+
+        let february = budgr.envelope_budget(day("2025-02-20"));
+        assert_eq!(february.month, day("2025-02-01"));
+        assert_eq!(
+            *envelope(&february, PurchaseType::Groceries),
+            Envelope {
+                category: PurchaseType::Groceries,
+                rolled_over: 40,
+                assigned: 100,
+                spent: 50,
+                available: 90,
+            }
+        );
+        let leisure = envelope(&february, PurchaseType::Leisure);
+        assert_eq!((leisure.rolled_over, leisure.available), (-20, -20));
+        // two months on nothing more was spent or assigned
+        let april = budgr.envelope_budget(day("2025-04-01"));
+        assert_eq!(envelope(&april, PurchaseType::Groceries).rolled_over, 90);
+    }
+
+    #[test]
+    fn spending_before_the_first_budgeted_month_is_ignored() {
+        let mut budgr = spending(&[("2024-12-24", PurchaseType::Other, 80)]);
+        budgr.assign(day("2025-01-01"), PurchaseType::Other, 20);
+
+        let january = budgr.envelope_budget(day("2025-01-01"));
+        let other = envelope(&january, PurchaseType::Other);
+        assert_eq!((other.rolled_over, other.spent, other.available), (0, 0, 20));
+    }
+
+    #[test]
+    fn to_assign_counts_income_up_to_the_month() {
+        let mut budgr = Budgr::new();
+        budgr.set_income(day("2025-01-15"), 500);
+        budgr.assign(day("2025-01-01"), PurchaseType::Bill, 300);
+        budgr.set_income(day("2025-02-15"), 200);
+        budgr.assign(day("2025-02-01"), PurchaseType::Bill, 100);
+
+        let january = budgr.envelope_budget(day("2025-01-01"));
+        assert_eq!((january.income, january.to_assign), (500, 200));
+        let february = budgr.envelope_budget(day("2025-02-01"));
+        assert_eq!((february.income, february.to_assign), (200, 300));
+        let march = budgr.envelope_budget(day("2025-03-01"));
+        assert_eq!((march.income, march.to_assign), (0, 300));
+    }
+
+    #[test]
+    fn overspending_does_not_lower_to_assign() {
+        let mut budgr = spending(&[("2025-01-20", PurchaseType::Leisure, 150)]);
+        budgr.set_income(day("2025-01-01"), 500);
+        budgr.assign(day("2025-01-01"), PurchaseType::Leisure, 100);
+
+        let february = budgr.envelope_budget(day("2025-02-01"));
+        assert_eq!(envelope(&february, PurchaseType::Leisure).available, -50);
+        assert_eq!(february.to_assign, 400);
+        // covering it comes out of what is left to assign
+        budgr.assign(day("2025-02-01"), PurchaseType::Leisure, 50);
+        let february = budgr.envelope_budget(day("2025-02-01"));
+        assert_eq!(envelope(&february, PurchaseType::Leisure).available, 0);
+        assert_eq!(february.to_assign, 350);
+    }
+}
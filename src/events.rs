@@ -0,0 +1,28 @@
+use crossterm::event::{self, Event};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+// where the ui gets its input from
+pub trait EventSource {
+    // the next event, or None if nothing happened within the timeout
+    fn next_event(&mut self, timeout: Duration) -> Option<Event>;
+}
+
+// the real terminal
+pub struct CrosstermEvents;
+
+impl EventSource for CrosstermEvents {
+    fn next_event(&mut self, timeout: Duration) -> Option<Event> {
+        if !event::poll(timeout).unwrap_or(false) {
+            return None;
+        }
+        event::read().ok()
+    }
+}
+
+// a scripted list of events, handed out one per call without waiting
+impl EventSource for VecDeque<Event> {
+    fn next_event(&mut self, _timeout: Duration) -> Option<Event> {
+        self.pop_front()
+    }
+}
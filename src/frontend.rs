@@ -1,354 +1,738 @@
-use crate::log::{Budgr, Log};
-use crate::ui_data::{InputData, UIState, UITransition, UserInput};
-
-use std::{io::Stdout, mem::swap};
-
-use crossterm::{
-    event::{self, Event, KeyCode},
-    //terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
-use ratatui::style::Stylize;
+use crate::events::EventSource;
+use crate::keymap::{KeyBinding, Keymap};
+use budgr::log::{today, Budgr, PurchaseType};
+use budgr::logfile;
+use budgr::report::Period;
+use crate::screens::{account_form, debt_form, debt_payment_form, envelope_form, goal_form, payee_form, purchase_form, reconcile_form, rule_form, transfer_form};
+use crate::status::{Message, MessageKind, Status};
+use crate::theme::{Theme, Themes};
+use crate::ui_data::{InputData, UIState, UITransition, UserInput, ViewSorts};
+
+use std::time::{Duration, Instant};
+
+use crossterm::event::{Event, KeyCode, MouseButton, MouseEventKind};
 use ratatui::{
-    backend::CrosstermBackend,
-    layout::{Constraint, Layout, Direction, Constraint::Ratio},
-    style::{palette::tailwind::SLATE, Color, Modifier, Style},
-    text::Text,
-    widgets::{Cell, ListItem, Row, Table, TableState, Paragraph},
-    Terminal,
+    backend::Backend,
+    layout::{Constraint, Constraint::Ratio, Layout, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Cell, Clear, Paragraph, Row, Table, TableState},
+    Frame, Terminal,
 };
 
-// style
-
-const HIGHLIGHT_STYLE: Style = Style::new().add_modifier(Modifier::REVERSED).fg(SLATE.c900).bg(SLATE.c100);
-const ITEM_STYLE: Style = Style::new().fg(SLATE.c100).bg(SLATE.c900);
-
-pub struct UI {
-    selection_index: usize,
+// generic over the backend and where input comes from, so it can be driven by a
+// TestBackend and a scripted list of events as well as a real terminal
+pub struct UI<B: Backend, E: EventSource> {
     user_input: UserInput,
     state: UIState,
     budgr: Budgr,
-    terminal: Terminal<CrosstermBackend<Stdout>>,
+    terminal: Terminal<B>,
+    events: E,
+    sorts: ViewSorts,
+    keymap: Keymap,
+    themes: Themes,
+    show_help: bool,
+    status: Status,
+    // None turns autosave off
+    autosave: Option<Duration>,
+    last_save: Instant,
     run: bool,
 }
 
-impl UIState {
-    fn render(
-        &mut self,
-        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
-        input: &UserInput,
-        budgr: &mut Budgr,
-    ) -> Option<UITransition> {
-        match self {
-            UIState::BudgrShow { state } => budgr_show(terminal, state, input, budgr),
-            UIState::LogShow { index, state } => log_show(terminal, index, state, input, budgr),
-            UIState::PurchaseInput { input_data, selection_index, log_index } => purchase_input(terminal, input_data, input, selection_index, *log_index, budgr),
-            _ => None,
-        }
-    }
-}
+// how long to wait for input before redrawing anyway
+const TICK_RATE: Duration = Duration::from_millis(250);
 
-impl UI {
-    pub fn new(budgr: Budgr, terminal: Terminal<CrosstermBackend<Stdout>>) -> Self {
+impl<B: Backend, E: EventSource> UI<B, E> {
+    pub fn new(budgr: Budgr, terminal: Terminal<B>, events: E, keymap: Keymap, themes: Themes, autosave: Option<Duration>) -> Self {
+        let mut status = Status::default();
+        for skipped in themes.skipped() {
+            status.error(skipped.clone());
+        }
         UI {
-            selection_index: 0,
             user_input: UserInput::None,
             state: UIState::BudgrShow {
                 state: TableState::new(),
             },
             budgr,
             terminal,
+            events,
+            sorts: ViewSorts::default(),
+            keymap,
+            themes,
+            show_help: false,
+            status,
+            autosave,
+            last_save: Instant::now(),
             run: true,
         }
     }
 
+    // the theme switched to while running, to be saved in the config
+    pub fn picked_theme(&self) -> Option<&str> {
+        self.themes.picked()
+    }
+
     pub fn run(&mut self) {
         while self.run {
-            self.transition();
-            self.process_input();
+            self.step();
         }
-        self.budgr.serialize();
+        self.save();
     }
 
-    fn process_input(&mut self) {
-        // process input
-
-        if let Event::Key(key) = event::read().unwrap() {
-            self.user_input = match key.code {
-                KeyCode::Char(char) => UserInput::Char(char),
-                KeyCode::Enter => UserInput::Submit,
-                KeyCode::Left => UserInput::Prev,
-                KeyCode::Right => UserInput::Next,
-                KeyCode::Down=> UserInput::NextSelect,
-                KeyCode::Up => UserInput::PrevSelect,
-                KeyCode::Esc => UserInput::Esc,
-                KeyCode::Backspace => UserInput::Backspace,
-                _ => UserInput::None,
-            }
-        }
+    // one pass of the loop: draw, wait for one event and act on it
+    // scripted tests call this once per event and look at the result in between
+    pub fn step(&mut self) {
+        self.draw();
+        self.process_input();
+        self.update();
+        self.tick();
     }
-    fn transition(&mut self) {
-
-        // draw then transition if needed
-        if let Some(transition) =
-            self.state
-                .render(&mut self.terminal, &mut self.user_input, &mut self.budgr)
-        {
-            // transition if needed
-            match (&self.state, transition) {
-                // exit the app
-                (UIState::BudgrShow { state: _ }, UITransition::ExitLayer) => {
-                    self.run = false;
-                }
-                // open a log
-                (UIState::BudgrShow { state: _ }, UITransition::OpenLog(i)) => {
-                    self.state = UIState::LogShow {
-                        index: i,
-                        state: TableState::new(),
-                    };
-                    self.transition_flush();
-                }
-                // create a new purchase
-                (UIState::LogShow { index: i, state: _ }, UITransition::NewPurchase) => {
-                    self.state = UIState::PurchaseInput {
-                        input_data: vec![InputData{input: String::new(), character_pos: 0}; 3],
-                        selection_index: 0,
-                        log_index: *i,
-                    }
-                }
-                // go back to seeing all logs from log show
-                (UIState::LogShow { index: _, state: _ }, UITransition::ExitLayer) => {
-                    self.state = UIState::BudgrShow {
-                        state: TableState::new(),
-                    };
-                    self.transition_flush();
-                }
-                (UIState::PurchaseInput { input_data: _, selection_index: _, log_index }, UITransition::ExitLayer) => {
-                    self.state = UIState::LogShow{index: *log_index, state: TableState::new()};
-                }
-                (_, _) => (),
+
+    #[cfg(test)]
+    pub fn is_running(&self) -> bool {
+        self.run
+    }
+
+    #[cfg(test)]
+    pub fn budgr(&self) -> &Budgr {
+        &self.budgr
+    }
+
+    // the backend holds what was drawn last, e.g. the buffer of a TestBackend
+    #[cfg(test)]
+    pub fn terminal(&self) -> &Terminal<B> {
+        &self.terminal
+    }
+
+    // write the logs if anything changed, also used to rescue data after a panic
+    pub fn save(&mut self) {
+        if !self.budgr.has_unsaved_changes() {
+            return;
+        }
+        match self.budgr.serialize() {
+            Ok(_) => self.status.info("saved"),
+            Err(err) => {
+                logfile::write(&format!("saving failed: {}", err));
+                self.status.error(format!("saving failed: {}", err));
             }
         }
+        self.last_save = Instant::now();
     }
 
-    fn transition_flush(&mut self) {
-        self.selection_index = 0;
+    // things that happen on a timer rather than on input
+    fn tick(&mut self) {
+        if self.autosave.is_some_and(|autosave| self.last_save.elapsed() >= autosave) {
+            self.save();
+        }
     }
 
-    // if standard_input_handle returns Some(foo) it means the user wishes to exit the layer
-    fn standard_input_handle(&mut self) -> Option<UITransition> {
+    fn process_input(&mut self) {
+        // wait up to a tick for input, with no input the screen is just redrawn
+        // so resizes and anything time based stay up to date
+        let Some(event) = self.events.next_event(TICK_RATE) else {
+            self.user_input = UserInput::None;
+            return;
+        };
+
+        self.user_input = match event {
+            Event::Key(key) => {
+                let key = KeyBinding::from(key);
+                let actions = self.state.actions().into_iter().map(|(action, _)| action).collect::<Vec<UserInput>>();
+
+                match key.code {
+                    // typing into a text box, plain characters are text not commands
+                    KeyCode::Char(c) if self.state.accepts_text() && key.modifiers.is_empty() => UserInput::Char(c),
+                    _ => self.keymap.lookup(key, &actions).unwrap_or(UserInput::None),
+                }
+            }
+            Event::Paste(text) if self.state.accepts_text() => UserInput::Paste(text),
+            Event::Mouse(mouse) => match mouse.kind {
+                MouseEventKind::Down(MouseButton::Left) => UserInput::Click(mouse.column, mouse.row),
+                MouseEventKind::ScrollDown => UserInput::NextSelect,
+                MouseEventKind::ScrollUp => UserInput::PrevSelect,
+                _ => UserInput::None,
+            },
+            // resizes just need a redraw, which happens every loop
+            _ => UserInput::None,
+        };
+
+        // the help overlay swallows input until it is closed, mouse movement doesn't close it
+        if self.show_help && self.user_input != UserInput::None {
+            self.show_help = false;
+            self.user_input = UserInput::None;
+            return;
+        }
+        // keys that work the same on every screen
         match self.user_input {
-            UserInput::Prev => self.selection_index -= 1,
-            UserInput::Next => self.selection_index += 1,
-            UserInput::Esc => return Some(UITransition::ExitLayer),
-            _ => {}
+            UserInput::Help => self.show_help = true,
+            UserInput::CycleTheme => self.themes.next(),
+            UserInput::Quit => self.run = false,
+            _ => return,
         }
-        return None;
+        self.user_input = UserInput::None;
     }
-}
 
-fn budgr_show(
-    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
-    state: &mut TableState,
-    input: &UserInput,
-    budgr: &Budgr,
-) -> Option<UITransition> {
-    // handle inputs
-    match input {
-        UserInput::Submit => return Some(UITransition::OpenLog(state.selected().unwrap())),
-        UserInput::Esc => return Some(UITransition::ExitLayer),
-        UserInput::NextSelect => state.select_next(),
-        UserInput::PrevSelect => state.select_previous(),
-        _ => {}
+    fn draw(&mut self) {
+        let UI { terminal, state, budgr, sorts, keymap, themes, show_help, status, .. } = self;
+        let theme = themes.current();
+        let _ = terminal.draw(|frame| {
+            let [area, status_area] = split_status_bar(frame.area());
+            state.view(frame, area, budgr, sorts, theme);
+            status_bar(frame, status_area, state, budgr, status, keymap, theme);
+            toasts(frame, area, status, theme);
+            if *show_help {
+                help_overlay(frame, state, keymap, theme);
+            }
+        });
     }
 
-    // make a bunch of widgets to draw
+    // hand the input to the current screen, then move to another screen if it asks to
+    fn update(&mut self) {
+        let size = self.terminal.size().unwrap_or_default();
+        let [area, _] = split_status_bar(Rect::new(0, 0, size.width, size.height));
+        let input = std::mem::replace(&mut self.user_input, UserInput::None);
+        let Some(transition) = self.state.update(&input, area, &mut self.budgr, &mut self.sorts, &mut self.status) else {
+            return;
+        };
+
+        match (&self.state, transition) {
+            // exit the app
+            (UIState::BudgrShow { state: _ }, UITransition::ExitLayer) => {
+                self.run = false;
+            }
+            // open a log
+            (UIState::BudgrShow { state: _ }, UITransition::OpenLog(log)) => {
+                self.state = UIState::LogShow {
+                    log,
+                    state: TableState::new(),
+                };
+            }
+            // look through deleted logs and purchases
+            (UIState::BudgrShow { state: _ }, UITransition::OpenTrash) => {
+                self.state = UIState::TrashShow {
+                    state: TableState::new(),
+                };
+            }
+            // search every log
+            (UIState::BudgrShow { state: _ }, UITransition::OpenSearch) => {
+                self.state = UIState::Search {
+                    input_data: vec![InputData::default(); 7],
+                    selection_index: 0,
+                    state: TableState::new(),
+                };
+            }
+            // jump to the purchase a search hit points at
+            (UIState::Search { .. }, UITransition::OpenPurchase(log, purchase)) => {
+                // the row the purchase is shown on depends on the sort
+                let row = self.budgr.log(log).and_then(|l| {
+                    let index = l.purchase_index(purchase)?;
+                    self.sorts.purchase_order(l).iter().position(|i| *i == index)
+                });
+                self.state = UIState::LogShow {
+                    log,
+                    state: TableState::new().with_selected(row),
+                };
+            }
+            (UIState::Search { .. }, UITransition::ExitLayer) => {
+                self.state = UIState::BudgrShow {
+                    state: TableState::new(),
+                };
+            }
+            // charts for every log, or for the open log
+            (UIState::BudgrShow { .. } | UIState::LogShow { .. }, UITransition::OpenStats(log)) => {
+                self.state = UIState::Stats { log };
+            }
+            // period reports for every log, or for the open log
+            (UIState::BudgrShow { .. } | UIState::LogShow { .. }, UITransition::OpenReport(log)) => {
+                self.state = UIState::Report {
+                    log,
+                    period: Period::Month,
+                    state: TableState::new(),
+                };
+            }
+            (UIState::Stats { log: Some(log) } | UIState::Report { log: Some(log), .. }, UITransition::ExitLayer) => {
+                self.state = UIState::LogShow {
+                    log: *log,
+                    state: TableState::new(),
+                };
+            }
+            // payees and the rules that fill in new purchases
+            (UIState::BudgrShow { .. }, UITransition::OpenRules) => {
+                self.state = UIState::Rules {
+                    state: TableState::new(),
+                };
+            }
+            (UIState::Rules { .. }, UITransition::OpenPayees) => {
+                self.state = UIState::Payees {
+                    state: TableState::new(),
+                };
+            }
+            (UIState::Rules { .. }, UITransition::EditRule(rule)) => {
+                self.state = UIState::RuleInput {
+                    input_data: rule_form(rule.and_then(|i| self.budgr.payees.rules.get(i))),
+                    selection_index: 0,
+                    rule,
+                };
+            }
+            (UIState::Payees { .. }, UITransition::EditPayee(payee)) => {
+                self.state = UIState::PayeeInput {
+                    input_data: payee_form(payee.and_then(|i| self.budgr.payees.payees.get(i))),
+                    selection_index: 0,
+                    payee,
+                };
+            }
+            // back to the list with the rule or payee that was edited selected, a new one is at the end
+            (UIState::RuleInput { rule, .. }, UITransition::ExitLayer) => {
+                let row = rule.or(self.budgr.payees.rules.len().checked_sub(1));
+                self.state = UIState::Rules {
+                    state: TableState::new().with_selected(row),
+                };
+            }
+            (UIState::PayeeInput { payee, .. }, UITransition::ExitLayer) => {
+                let row = payee.or(self.budgr.payees.payees.len().checked_sub(1));
+                self.state = UIState::Payees {
+                    state: TableState::new().with_selected(row),
+                };
+            }
+            (UIState::Payees { .. }, UITransition::ExitLayer) => {
+                self.state = UIState::Rules {
+                    state: TableState::new(),
+                };
+            }
+            // purchases still to be paid back
+            (UIState::BudgrShow { .. }, UITransition::OpenReimbursements) => {
+                self.state = UIState::Reimbursements {
+                    state: TableState::new(),
+                };
+            }
+            // accounts, their balances and transfers between them
+            (UIState::BudgrShow { .. }, UITransition::OpenAccounts) => {
+                let row = (!self.budgr.accounts.accounts.is_empty()).then_some(0);
+                self.state = UIState::Accounts {
+                    state: TableState::new().with_selected(row),
+                };
+            }
+            (UIState::Accounts { .. }, UITransition::EditAccount(account)) => {
+                self.state = UIState::AccountInput {
+                    input_data: account_form(account.and_then(|id| self.budgr.accounts.get(id))),
+                    selection_index: 0,
+                    account,
+                };
+            }
+            (UIState::Accounts { .. }, UITransition::NewTransfer(from)) => {
+                self.state = UIState::TransferInput {
+                    input_data: transfer_form(from.and_then(|id| self.budgr.accounts.get(id))),
+                    selection_index: 0,
+                };
+            }
+            // back to the accounts with the edited one selected, a new one is at the end
+            (UIState::AccountInput { account, .. }, UITransition::ExitLayer) => {
+                let accounts = &self.budgr.accounts.accounts;
+                let row = match account {
+                    Some(id) => accounts.iter().position(|a| a.id == *id),
+                    None => accounts.len().checked_sub(1),
+                };
+                self.state = UIState::Accounts {
+                    state: TableState::new().with_selected(row),
+                };
+            }
+            (UIState::TransferInput { input_data, .. }, UITransition::ExitLayer) => {
+                // the account the money came from stays selected
+                let accounts = &self.budgr.accounts;
+                let row = accounts
+                    .find(&input_data[0].input)
+                    .and_then(|from| accounts.accounts.iter().position(|a| a.id == from.id))
+                    .or((!accounts.accounts.is_empty()).then_some(0));
+                self.state = UIState::Accounts {
+                    state: TableState::new().with_selected(row),
+                };
+            }
+            // envelope budget, starting on this month with the first category selected
+            (UIState::BudgrShow { .. }, UITransition::OpenEnvelopes) => {
+                self.state = UIState::Envelopes {
+                    month: Period::Month.start_of(today()),
+                    state: TableState::new().with_selected(Some(0)),
+                };
+            }
+            (UIState::Envelopes { .. }, UITransition::EditEnvelope(month, category)) => {
+                self.state = UIState::EnvelopeInput {
+                    input_data: envelope_form(&self.budgr, month, category),
+                    selection_index: 0,
+                    month,
+                    category,
+                };
+            }
+            (UIState::EnvelopeInput { month, category, .. }, UITransition::ExitLayer) => {
+                let row = category.and_then(|c| PurchaseType::ALL.iter().position(|t| *t == c));
+                self.state = UIState::Envelopes {
+                    month: *month,
+                    state: TableState::new().with_selected(row.or(Some(0))),
+                };
+            }
+            // savings goals
+            (UIState::BudgrShow { .. }, UITransition::OpenGoals) => {
+                self.state = UIState::Goals {
+                    state: TableState::new(),
+                };
+            }
+            (UIState::Goals { .. }, UITransition::EditGoal(goal)) => {
+                self.state = UIState::GoalInput {
+                    input_data: goal_form(goal.and_then(|i| self.budgr.goals.goals.get(i)), &self.budgr),
+                    selection_index: 0,
+                    goal,
+                };
+            }
+            (UIState::GoalInput { goal, .. }, UITransition::ExitLayer) => {
+                let row = goal.or(self.budgr.goals.goals.len().checked_sub(1));
+                self.state = UIState::Goals {
+                    state: TableState::new().with_selected(row),
+                };
+            }
+            // debts, payments go back to the debt they were made on
+            (UIState::BudgrShow { .. }, UITransition::OpenDebts) => {
+                self.state = UIState::Debts {
+                    state: TableState::new().with_selected((!self.budgr.debts.debts.is_empty()).then_some(0)),
+                };
+            }
+            (UIState::Debts { .. }, UITransition::EditDebt(debt)) => {
+                self.state = UIState::DebtInput {
+                    input_data: debt_form(debt.and_then(|i| self.budgr.debts.debts.get(i)), &self.budgr),
+                    selection_index: 0,
+                    debt,
+                };
+            }
+            (UIState::Debts { .. }, UITransition::PayDebt(debt)) => {
+                self.state = UIState::DebtPaymentInput {
+                    input_data: debt_payment_form(&self.budgr, debt),
+                    selection_index: 0,
+                    debt,
+                };
+            }
+            (UIState::DebtInput { debt, .. } | UIState::DebtPaymentInput { debt: debt @ Some(_), .. }, UITransition::ExitLayer) => {
+                let row = debt.or(self.budgr.debts.debts.len().checked_sub(1));
+                self.state = UIState::Debts {
+                    state: TableState::new().with_selected(row),
+                };
+            }
+            (UIState::DebtPaymentInput { debt: None, .. }, UITransition::ExitLayer) => {
+                self.state = UIState::Debts {
+                    state: TableState::new().with_selected((!self.budgr.debts.debts.is_empty()).then_some(0)),
+                };
+            }
+            (UIState::Stats { log: None }
+            | UIState::Report { log: None, .. }
+            | UIState::Reimbursements { .. }
+            | UIState::Rules { .. }
+            | UIState::Accounts { .. }
+            | UIState::Envelopes { .. }
+            | UIState::Goals { .. }
+            | UIState::Debts { .. }, UITransition::ExitLayer) => {
+                self.state = UIState::BudgrShow {
+                    state: TableState::new(),
+                };
+            }
+            // create a new purchase
+            (UIState::LogShow { log, state: _ }, UITransition::NewPurchase) => {
+                self.state = UIState::PurchaseInput {
+                    input_data: purchase_form(),
+                    selection_index: 0,
+                    log: *log,
+                }
+            }
+            // attach a receipt to the selected purchase
+            (UIState::LogShow { .. }, UITransition::AttachFile(log, purchase)) => {
+                self.state = UIState::AttachInput {
+                    input_data: vec![InputData::default(); 2],
+                    selection_index: 0,
+                    log,
+                    purchase,
+                };
+            }
+            // back to the log with the purchase still selected
+            (UIState::AttachInput { log, purchase, .. }, UITransition::ExitLayer) => {
+                let row = self.budgr.log(*log).and_then(|l| {
+                    let index = l.purchase_index(*purchase)?;
+                    self.sorts.purchase_order(l).iter().position(|i| *i == index)
+                });
+                self.state = UIState::LogShow {
+                    log: *log,
+                    state: TableState::new().with_selected(row),
+                };
+            }
+            // check the log's purchases against a bank statement
+            (UIState::LogShow { log, .. }, UITransition::Reconcile(account)) => {
+                self.state = UIState::ReconcileInput {
+                    input_data: reconcile_form(account.and_then(|id| self.budgr.accounts.get(id))),
+                    selection_index: 0,
+                    log: *log,
+                };
+            }
+            (UIState::ReconcileInput { log, .. }, UITransition::ExitLayer) => {
+                self.state = UIState::LogShow {
+                    log: *log,
+                    state: TableState::new(),
+                };
+            }
+            // switch to another log picked from the side pane
+            (UIState::LogShow { .. }, UITransition::OpenLog(log)) => {
+                self.state = UIState::LogShow {
+                    log,
+                    state: TableState::new(),
+                };
+            }
+            // go back to seeing all logs from log show, with the log that was open still selected
+            (UIState::LogShow { log, state: _ }, UITransition::ExitLayer) => {
+                let index = self.budgr.log_index(*log);
+                let row = self.sorts.log_order(&self.budgr).iter().position(|i| Some(*i) == index);
+                self.state = UIState::BudgrShow {
+                    state: TableState::new().with_selected(row),
+                };
+            }
+            (UIState::PurchaseInput { input_data: _, selection_index: _, log }, UITransition::ExitLayer) => {
+                self.state = UIState::LogShow{log: *log, state: TableState::new()};
+            }
+            // back to the logs from the trash
+            (UIState::TrashShow { state: _ }, UITransition::ExitLayer) => {
+                self.state = UIState::BudgrShow {
+                    state: TableState::new(),
+                };
+            }
+            (_, _) => (),
+        }
+    }
+}
 
-    // table widget
-    let header = ["log name", "num purchases", "total expense"]
-        .into_iter()
-        .map(Cell::from)
-        .collect::<Row>()
-        .style(Style::new().fg(SLATE.c100).bg(SLATE.c950))
-        .height(2);
-
-    let rows = budgr.logs.iter().enumerate().map(|(i, log)| {
-        let colour = alternate_colour(&i);
-        let item: [&String; 3] = [
-            &log.name,
-            &log.purchases.len().to_string(),
-            &log.get_total().to_string(),
-        ];
-        item.into_iter()
-            .map(|content| Cell::from(Text::from(format!("{content}"))))
-            .collect::<Row>()
-            .style(Style::new().fg(SLATE.c400).bg(colour))
-            .height(4)
-    });
+// the screen above, the status bar on the last line
+fn split_status_bar(area: Rect) -> [Rect; 2] {
+    Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(area)
+}
 
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(64),
-            Constraint::Min(26),
-            Constraint::Min(25),
-        ],
-    )
-    .header(header)
-    .highlight_style(HIGHLIGHT_STYLE);
-
-    // draw them all in this closure
-    let _ = terminal.draw(|frame| {
-        frame.render_stateful_widget(table, frame.area(), state);
+// a popup listing the keys for everything the current screen can do
+fn help_overlay(frame: &mut Frame, state: &UIState, keymap: &Keymap, theme: &Theme) {
+    let rows = state.actions().into_iter().map(|(action, description)| {
+        let keys = keymap
+            .keys_for(&action)
+            .iter()
+            .map(|k| k.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        Row::new([Cell::from(keys), Cell::from(description)])
     });
-    None
+    let table = Table::new(rows, [Constraint::Length(20), Constraint::Min(20)])
+        .block(Block::bordered().title("help (any key to close)"))
+        .style(theme.item());
+
+    let [_, area, _] = Layout::vertical([Ratio(1, 6), Ratio(2, 3), Ratio(1, 6)]).areas(frame.area());
+    let [_, area, _] = Layout::horizontal([Ratio(1, 6), Ratio(2, 3), Ratio(1, 6)]).areas(area);
+    frame.render_widget(Clear, area);
+    frame.render_widget(table, area);
 }
 
-fn log_show(
-    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
-    index: &mut usize,
-    state: &mut TableState,
-    input: &UserInput,
-    budgr: &mut Budgr,
-) -> Option<UITransition> {
-    // input handle
-    match input {
-        //UserInput::Submit => return Some(UITransition::OpenLog(state.selected().unwrap())),
-        UserInput::Esc => return Some(UITransition::ExitLayer),
-        UserInput::NextSelect => state.select_next(),
-        UserInput::PrevSelect => state.select_previous(),
-        UserInput::Char('a') => return Some(UITransition::NewPurchase),
-        UserInput::Char('d') => {
-            budgr.remove_purchase(*index, state.selected()?);
-            return None
-        }
-        _ => {}
+// where you are, whether anything is unsaved, the last message and a few key hints
+fn status_bar(frame: &mut Frame, area: Rect, state: &UIState, budgr: &Budgr, status: &Status, keymap: &Keymap, theme: &Theme) {
+    let location = match state.log_id().and_then(|id| budgr.log(id)) {
+        Some(log) => log.name.as_str(),
+        None => "all logs",
+    };
+    let mut left = vec![Span::styled(format!(" {} ", location), theme.header())];
+    if budgr.has_unsaved_changes() {
+        left.push(Span::styled(" unsaved ", theme.on_accent()));
+    }
+    if let Some(message) = status.last() {
+        left.push(Span::styled(format!(" {}", message.text), message_style(message, theme)));
     }
 
-    // make widgets
-    let header = ["name", "purchase type", "cost"]
+    let hints = state
+        .hints()
         .into_iter()
-        .map(Cell::from)
-        .collect::<Row>()
-        .style(Style::new().fg(SLATE.c100).bg(SLATE.c950))
-        .height(2);
-
-    let rows = budgr.logs[*index]
-        .purchases
-        .iter()
-        .enumerate()
-        .map(|(i, p)| {
-            let item: [&String; 2] = [&p.name, &p.cost.to_string()];
-
-            item.into_iter()
-                .map(|content| Cell::from(Text::from(format!("{content}"))))
-                .collect::<Row>()
-                .style(ITEM_STYLE.bg(alternate_colour(&i)))
-                .height(4)
-        });
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(64),
-            Constraint::Min(26),
-            Constraint::Min(25),
-        ],
-    )
-    .header(header)
-    .highlight_style(HIGHLIGHT_STYLE);
-
-    // render widgets
-    terminal.draw(|frame| frame.render_stateful_widget(table, frame.area(), state));
-
-    None
+        .filter_map(|(action, description)| {
+            let key = keymap.keys_for(&action).into_iter().next()?;
+            Some(format!("{} {}", key, description))
+        })
+        .collect::<Vec<String>>()
+        .join("  ");
+    let hints_width = (hints.chars().count() as u16 + 1).min(area.width / 2);
+
+    let [left_area, hints_area] = Layout::horizontal([Constraint::Min(0), Constraint::Length(hints_width)]).areas(area);
+    frame.render_widget(Paragraph::new(Line::from(left)).style(theme.item()), left_area);
+    frame.render_widget(Paragraph::new(hints).style(theme.item().fg(theme.muted)), hints_area);
 }
 
-fn purchase_input( terminal: &mut Terminal<CrosstermBackend<Stdout>>,  dat: &mut Vec<InputData>, input: &UserInput, selection_index: &mut usize, log_index: usize, budgr: &mut Budgr) -> Option<UITransition> {
-    // input handle
-    match input {
-        UserInput::Next => dat[*selection_index].move_cursor_right(),
-        UserInput::Prev => dat[*selection_index].move_cursor_left(),
-        UserInput::NextSelect => {
-            if *selection_index < dat.len() {
-                *selection_index += 1
-            }
-        }
-        UserInput::PrevSelect => {
-            if *selection_index > 0 {
-                *selection_index -= 1
-            }
-        }
-        UserInput::Char(c) => dat[*selection_index].enter_char(*c),
-        UserInput::Esc => return Some(UITransition::ExitLayer),
-        UserInput::Submit => {
-            match selection_index {
-                // attempt to create a new purchase
-                2 => {
-                    let cost: i64;
-                    if let Ok(int) = dat[1].input.parse::<i64>() {
-                        cost = int;
-                    } else {
-                        // TODO: error handling (don't just spit the user out of the menu if they
-                        // inputted something incorrectly)
-
-                        return Some(UITransition::ExitLayer);
-                    };
-
-                    budgr.add_purchase(log_index, dat[0].input.clone(), cost);
-                }
-                _ => (),
-            };
+// messages pop up in the top right corner for a few seconds
+fn toasts(frame: &mut Frame, area: Rect, status: &Status, theme: &Theme) {
+    let mut y = area.y;
+    for message in status.toasts() {
+        let width = (message.text.chars().count() as u16 + 4).min(area.width / 2);
+        let toast = Rect::new(area.right().saturating_sub(width), y, width, 3).intersection(area);
+        if toast.height < 3 {
+            break;
         }
-        _ => (),
+        frame.render_widget(Clear, toast);
+        frame.render_widget(
+            Paragraph::new(message.text.as_str())
+                .block(Block::bordered())
+                .style(message_style(message, theme)),
+            toast,
+        );
+        y += 3;
     }
+}
 
+fn message_style(message: &Message, theme: &Theme) -> Style {
+    match message.kind {
+        MessageKind::Info => theme.item(),
+        MessageKind::Error => theme.error(),
+    }
+}
 
-    // make widgets
-    
-    let mut name_input = Paragraph::new(dat[0].input.as_str());
-    let mut cost_input = Paragraph::new(dat[1].input.as_str());
-    let mut submit_button = Paragraph::new("Submit");
-    
-    match selection_index {
-        0 => {
-            name_input = name_input.style(HIGHLIGHT_STYLE).add_modifier(Modifier::BOLD);
-        }
-        1 => {
-            cost_input = cost_input.style(HIGHLIGHT_STYLE).add_modifier(Modifier::BOLD);
-        }
-        2 => {
-            submit_button = submit_button.style(HIGHLIGHT_STYLE).add_modifier(Modifier::BOLD);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use budgr::goal::{Goal, GoalLink};
+    use budgr::log::{today, PurchaseType};
+    use crate::screens::tests::text;
+    use crossterm::event::{KeyEvent, KeyModifiers};
+    use ratatui::backend::TestBackend;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn help_overlay_snapshot_lists_the_screens_keys() {
+        let mut terminal = Terminal::new(TestBackend::new(72, 18)).unwrap();
+        let state = UIState::TrashShow {
+            state: TableState::new(),
+        };
+        terminal
+            .draw(|frame| help_overlay(frame, &state, &Keymap::default(), &Theme::monochrome()))
+            .unwrap();
+        assert_eq!(
+            text(terminal.backend().buffer()),
+            [
+                "",
+                "",
+                "",
+                "            ┌help (any key to close)───────────────────────┐",
+                "            │down, j              next item                │",
+                "            │up, k                previous item            │",
+                "            │r                    restore                  │",
+                "            │d                    delete forever           │",
+                "            │esc                  back                     │",
+                "            │?, f1                show / hide this help    │",
+                "            │T                    next colour theme        │",
+                "            │ctrl-c               save and quit            │",
+                "            │                                              │",
+                "            │                                              │",
+                "            └──────────────────────────────────────────────┘",
+                "",
+                "",
+                "",
+            ]
+        );
+    }
+
+    // a ui over a 80x30 TestBackend, fed scripted keys
+    fn scripted_ui(budgr: Budgr) -> UI<TestBackend, VecDeque<Event>> {
+        let terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+        UI::new(budgr, terminal, VecDeque::new(), Keymap::default(), Themes::plain(), None)
+    }
+
+    // one step per key like the real loop, then draw what it ended up on
+    fn press(ui: &mut UI<TestBackend, VecDeque<Event>>, keys: &[KeyCode]) {
+        for key in keys {
+            ui.events.push_back(Event::Key(KeyEvent::new(*key, KeyModifiers::NONE)));
+            ui.step();
         }
-        _ => (),
-    };
+        ui.draw();
+    }
 
-    // render
-    
-    let _ = terminal.draw(| f | {
-        let layout = Layout::vertical([Ratio(1, 3); 3]);
-        let [name_area, cost_area, submit_area] = layout.areas(f.area());
+    fn type_text(ui: &mut UI<TestBackend, VecDeque<Event>>, s: &str) {
+        press(ui, &s.chars().map(KeyCode::Char).collect::<Vec<KeyCode>>());
+    }
 
-        f.render_widget(name_input, name_area);
-        f.render_widget(cost_input, cost_area);
-        f.render_widget(submit_button, submit_area);
-    });
+    fn screen(ui: &UI<TestBackend, VecDeque<Event>>) -> String {
+        text(ui.terminal().backend().buffer()).join("\n")
+    }
 
-    None
-}
+    #[test]
+    fn adding_a_purchase_through_the_form_shows_it_in_the_log() {
+        let mut ui = scripted_ui(crate::make_test_budgr());
+        press(&mut ui, &[KeyCode::Char('j'), KeyCode::Enter, KeyCode::Char('a')]);
+        type_text(&mut ui, "bread");
+        press(&mut ui, &[KeyCode::Tab]);
+        type_text(&mut ui, "3");
+        press(&mut ui, &[KeyCode::Tab; 8]);
+        press(&mut ui, &[KeyCode::Enter, KeyCode::Esc]);
+
+        let log = &ui.budgr().logs[0];
+        assert_eq!(log.purchases.len(), 5);
+        let bread = log.purchases.last().unwrap();
+        assert_eq!((bread.name.as_str(), bread.cost, bread.category), ("bread", 3, PurchaseType::Other));
+        let screen = screen(&ui);
+        assert!(screen.contains(&format!("│bread                                  Other           {}   3", today())));
+        assert!(screen.contains("added purchase"));
+    }
 
-fn alternate_colour(i: &usize) -> Color {
-    match i % 2 {
-        0 => SLATE.c800,
-        _ => SLATE.c600,
+    #[test]
+    fn deleting_a_purchase_moves_it_to_the_trash() {
+        let mut ui = scripted_ui(crate::make_test_budgr());
+        press(&mut ui, &[KeyCode::Char('j'), KeyCode::Enter, KeyCode::Char('j'), KeyCode::Char('d')]);
+
+        let budgr = ui.budgr();
+        assert_eq!(budgr.logs[0].purchases.len(), 3);
+        assert_eq!(budgr.trash.purchases.len(), 1);
+        assert_eq!(budgr.trash.purchases[0].purchase.category, PurchaseType::Groceries);
+        let screen = screen(&ui);
+        assert!(screen.contains("moved purchase to trash"));
+        assert!(!screen.contains("Groceries"));
+    }
+
+    #[test]
+    fn editing_a_goal_saves_the_new_target() {
+        let mut budgr = crate::make_test_budgr();
+        let goal = Goal {
+            name: "holiday".to_string(),
+            target: 100,
+            target_date: None,
+            link: GoalLink::Log(budgr.logs[2].id),
+            since: None,
+        };
+        budgr.save_goal(None, goal).unwrap();
+        let mut ui = scripted_ui(budgr);
+        press(&mut ui, &[KeyCode::Char('G'), KeyCode::Char('j'), KeyCode::Enter, KeyCode::Tab]);
+        press(&mut ui, &[KeyCode::Backspace; 3]);
+        type_text(&mut ui, "250");
+        press(&mut ui, &[KeyCode::Tab; 4]);
+        press(&mut ui, &[KeyCode::Enter]);
+
+        let goals = &ui.budgr().goals.goals;
+        assert_eq!(goals.len(), 1);
+        assert_eq!((goals[0].name.as_str(), goals[0].target), ("holiday", 250));
+        assert!(matches!(ui.state, UIState::Goals { .. }));
+        let screen = screen(&ui);
+        assert!(screen.contains("holiday"));
+        assert!(screen.contains("saved goal"));
+    }
+
+    #[test]
+    fn a_search_hit_opens_its_log_on_the_purchase() {
+        let mut ui = scripted_ui(crate::make_test_budgr());
+        press(&mut ui, &[KeyCode::Char('/')]);
+        type_text(&mut ui, "purchase:9");
+        assert!(screen(&ui).contains("│test_log3        purchase:9                 School"));
+
+        press(&mut ui, &[KeyCode::Tab; 7]);
+        press(&mut ui, &[KeyCode::Char('j'), KeyCode::Enter]);
+        let test_log3 = ui.budgr().logs[3].id;
+        assert!(matches!(&ui.state, UIState::LogShow { log, state } if *log == test_log3 && state.selected() == Some(3)));
+        assert!(screen(&ui).starts_with("┌test_log3"));
     }
-}
 
-impl From<&Log> for ListItem<'_> {
-    fn from(log: &Log) -> Self {
-        ListItem::new(format!(
-            "{}: # Purchases: {} Total Value: {}",
-            log.name,
-            log.purchases.len(),
-            log.get_total()
-        ))
+    #[test]
+    fn escape_from_the_logs_stops_the_loop() {
+        let mut ui = scripted_ui(crate::make_test_budgr());
+        press(&mut ui, &[KeyCode::Char('j'), KeyCode::Enter, KeyCode::Esc]);
+        assert!(ui.is_running());
+        press(&mut ui, &[KeyCode::Esc]);
+        assert!(!ui.is_running());
     }
 }
@@ -0,0 +1,279 @@
+//! Savings goals, with progress measured by what has gone into a log or a category.
+
+use crate::log::{today, Budgr, LogId, Purchase, PurchaseType};
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+/// How many days of contributions the projected completion date is based on.
+pub const RECENT_DAYS: i64 = 90;
+
+/// What counts towards a goal.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GoalLink {
+    /// Every purchase in a log, e.g. a `holiday-fund` log that savings are recorded in.
+    Log(LogId),
+    /// Purchases in a category across every log.
+    Category(PurchaseType),
+}
+
+/// Something being saved up for.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Goal {
+    /// Shown in the goals view.
+    pub name: String,
+    /// How much is needed.
+    pub target: i64,
+    /// When it is needed by, if there is a deadline.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_date: Option<NaiveDate>,
+    /// What counts towards the goal.
+    pub link: GoalLink,
+    /// Only purchases on or after this date count, `None` counts all of them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<NaiveDate>,
+}
+
+/// Every goal, stored as `goals.json` in the data folder.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Goals {
+    /// The goals in the order they were added.
+    #[serde(default)]
+    pub goals: Vec<Goal>,
+}
+
+/// How a goal is coming along, see [`Budgr::goal_progress`].
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct GoalProgress {
+    /// What has gone towards the goal so far.
+    pub saved: i64,
+    /// What is still needed, 0 once the goal is reached.
+    pub remaining: i64,
+    /// Saved over target, capped at 1.
+    pub fraction: f64,
+    /// What has to go in each month to make the target date, `None` without one or once reached.
+    pub required_monthly: Option<i64>,
+    /// What went in per month over the last [`RECENT_DAYS`] days, or since the goal started if that is later.
+    pub recent_monthly: i64,
+    /// When the goal will be reached at the recent rate, `None` if nothing went in recently.
+    pub projected: Option<NaiveDate>,
+}
+
+impl GoalProgress {
+    /// True once the target has been saved.
+    pub fn is_reached(&self) -> bool {
+        self.remaining == 0
+    }
+
+    /// Whether the recent rate makes the target date, `None` without a target date.
+    pub fn on_track(&self, goal: &Goal) -> Option<bool> {
+        let target_date = goal.target_date?;
+        Some(self.is_reached() || self.projected.is_some_and(|p| p <= target_date))
+    }
+}
+
+// whole months from one date to another, counting a started month, at least 1
+fn months_until(from: NaiveDate, to: NaiveDate) -> i64 {
+    let months = (to.year() - from.year()) as i64 * 12 + to.month() as i64 - from.month() as i64;
+    match to.day() > from.day() {
+        true => months + 1,
+        false => months,
+    }
+    .max(1)
+}
+
+impl Budgr {
+    // the purchases that count towards a goal, with their dates and amounts
+    fn goal_contributions(&self, goal: &Goal) -> Vec<(Option<NaiveDate>, i64)> {
+        let counts = |p: &Purchase| match goal.since {
+            Some(since) => p.date.is_some_and(|date| date >= since),
+            None => true,
+        };
+        match goal.link {
+            GoalLink::Log(id) => self
+                .log(id)
+                .map(|log| log.purchases.iter().filter(|p| counts(p)).map(|p| (p.date, p.cost)).collect())
+                .unwrap_or_default(),
+            GoalLink::Category(category) => self
+                .logs
+                .iter()
+                .flat_map(|log| log.purchases.iter())
+                .filter(|p| counts(p))
+                .flat_map(|p| {
+                    p.category_amounts()
+                        .into_iter()
+                        .filter(|(c, _)| *c == category)
+                        .map(|(_, amount)| (p.date, amount))
+                })
+                .collect(),
+        }
+    }
+
+    /// How far along a goal is, and what it will take to finish it.
+    pub fn goal_progress(&self, goal: &Goal) -> GoalProgress {
+        let contributions = self.goal_contributions(goal);
+        let saved = contributions.iter().map(|(_, amount)| amount).sum::<i64>();
+        let remaining = (goal.target - saved).max(0);
+        let fraction = match goal.target {
+            target if target <= 0 => 1.0,
+            target => (saved as f64 / target as f64).clamp(0.0, 1.0),
+        };
+
+        // a goal started less than RECENT_DAYS ago is measured from its start
+        let today = today();
+        let recent_start = (today - Duration::days(RECENT_DAYS)).max(goal.since.unwrap_or(NaiveDate::MIN));
+        let recent = contributions
+            .iter()
+            .filter(|(date, _)| date.is_some_and(|d| d >= recent_start && d <= today))
+            .map(|(_, amount)| amount)
+            .sum::<i64>();
+        let recent_monthly = recent * 30 / (today - recent_start).num_days().max(1);
+
+        let required_monthly = match goal.target_date {
+            Some(date) if remaining > 0 => {
+                let months = months_until(today, date);
+                Some((remaining + months - 1) / months)
+            }
+            _ => None,
+        };
+        let projected = match (remaining, recent_monthly) {
+            (0, _) => Some(today),
+            (_, rate) if rate <= 0 => None,
+            (remaining, rate) => {
+                let months = (remaining + rate - 1) / rate;
+                today.checked_add_months(Months::new(months as u32))
+            }
+        };
+
+        GoalProgress {
+            saved,
+            remaining,
+            fraction,
+            required_monthly,
+            recent_monthly,
+            projected,
+        }
+    }
+
+    /// Replace the goal at `index`, or add it to the end with `None`.
+    pub fn save_goal(&mut self, index: Option<usize>, goal: Goal) -> Result<(), String> {
+        if goal.name.trim().is_empty() {
+            return Err("a goal needs a name".to_string());
+        }
+        if goal.target <= 0 {
+            return Err("a goal needs a target above 0".to_string());
+        }
+        if let GoalLink::Log(id) = goal.link {
+            self.log(id).ok_or(format!("ERROR: no log with id {}", id))?;
+        }
+        match index {
+            Some(i) => *self.goals.goals.get_mut(i).ok_or("ERROR: no such goal")? = goal,
+            None => self.goals.goals.push(goal),
+        }
+        self.unsaved = true;
+        Ok(())
+    }
+
+    /// Delete the goal at `index`.
+    pub fn remove_goal(&mut self, index: usize) -> Result<(), String> {
+        if index >= self.goals.goals.len() {
+            return Err("ERROR: no such goal".to_string());
+        }
+        self.goals.goals.remove(index);
+        self.unsaved = true;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{day, months_from_today};
+
+    // a budgr with a savings log holding (days ago, amount) payments in, and a goal on it
+    fn saving(payments: &[(i64, i64)], target: i64, target_date: Option<NaiveDate>) -> (Budgr, Goal) {
+        let mut budgr = Budgr::new();
+        let log = budgr.new_log("savings".to_string()).unwrap();
+        for (days_ago, amount) in payments {
+            let date = today() - Duration::days(*days_ago);
+            budgr.add_purchase(log, "in".to_string(), *amount, PurchaseType::Other, date).unwrap();
+        }
+        let goal = Goal {
+            name: "bike".to_string(),
+            target,
+            target_date,
+            link: GoalLink::Log(log),
+            since: None,
+        };
+        (budgr, goal)
+    }
+
+    #[test]
+    fn months_until_counts_a_started_month() {
+        assert_eq!(months_until(day("2025-01-15"), day("2025-03-15")), 2);
+        assert_eq!(months_until(day("2025-01-15"), day("2025-03-14")), 2);
+        assert_eq!(months_until(day("2025-01-15"), day("2025-03-16")), 3);
+        assert_eq!(months_until(day("2024-11-30"), day("2025-02-01")), 3);
+        // never less than one, so there is always a month to save in
+        assert_eq!(months_until(day("2025-01-15"), day("2025-01-15")), 1);
+        assert_eq!(months_until(day("2025-01-15"), day("2024-06-01")), 1);
+    }
+
+    #[test]
+    fn required_monthly_spreads_what_is_left_over_the_months() {
+        let (budgr, goal) = saving(&[(400, 200)], 1200, Some(months_from_today(5)));
+        let progress = budgr.goal_progress(&goal);
+        assert_eq!((progress.saved, progress.remaining), (200, 1000));
+        assert_eq!(progress.fraction, 200.0 / 1200.0);
+        // 1000 over 5 months rounds up
+        assert_eq!(progress.required_monthly, Some(200));
+
+        let (budgr, goal) = saving(&[], 1000, Some(months_from_today(6)));
+        assert_eq!(budgr.goal_progress(&goal).required_monthly, Some(167));
+        let (budgr, goal) = saving(&[], 1000, None);
+        assert_eq!(budgr.goal_progress(&goal).required_monthly, None);
+    }
+
+    #[test]
+    fn a_target_date_in_the_past_needs_everything_now() {
+        let (budgr, goal) = saving(&[(10, 300)], 1000, Some(today() - Duration::days(40)));
+        let progress = budgr.goal_progress(&goal);
+        assert_eq!(progress.required_monthly, Some(700));
+        assert_eq!(progress.on_track(&goal), Some(false));
+    }
+
+    #[test]
+    fn projection_follows_the_recent_rate() {
+        // 300 in over the last RECENT_DAYS is 100 a month, the older 200 doesn't count towards the rate
+        let (budgr, mut goal) = saving(&[(10, 300), (400, 200)], 1200, Some(months_from_today(6)));
+        let progress = budgr.goal_progress(&goal);
+        assert_eq!(progress.recent_monthly, 100);
+        assert_eq!(progress.projected, Some(months_from_today(7)));
+        assert_eq!(progress.on_track(&goal), Some(false));
+
+        // a goal started 30 days ago is measured over those 30 days
+        goal.since = Some(today() - Duration::days(30));
+        let progress = budgr.goal_progress(&goal);
+        assert_eq!((progress.saved, progress.recent_monthly), (300, 300));
+        assert_eq!(progress.projected, Some(months_from_today(3)));
+        assert_eq!(progress.on_track(&goal), Some(true));
+    }
+
+    #[test]
+    fn nothing_recent_means_no_projection() {
+        let (budgr, goal) = saving(&[(400, 200)], 1000, None);
+        let progress = budgr.goal_progress(&goal);
+        assert_eq!((progress.recent_monthly, progress.projected), (0, None));
+        assert_eq!(progress.on_track(&goal), None);
+    }
+
+    #[test]
+    fn a_met_goal_is_done_even_past_its_date() {
+        let (budgr, goal) = saving(&[(400, 700), (10, 600)], 1200, Some(today() - Duration::days(40)));
+        let progress = budgr.goal_progress(&goal);
+        assert!(progress.is_reached());
+        assert_eq!((progress.saved, progress.remaining, progress.fraction), (1300, 0, 1.0));
+        assert_eq!(progress.required_monthly, None);
+        assert_eq!(progress.projected, Some(today()));
+        assert_eq!(progress.on_track(&goal), Some(true));
+    }
+}
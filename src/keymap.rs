@@ -0,0 +1,291 @@
+use crate::ui_data::UserInput;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use dirs::config_dir;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+fn get_keymap_path() -> PathBuf {
+    [config_dir().unwrap(), "budgr/keymap.json".into()]
+        .iter()
+        .collect()
+}
+
+// a key plus the modifiers held with it, e.g. "ctrl-d" or "j"
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        // shift is already part of the character ('A' vs 'a', '?' vs '/') or key (backtab),
+        // so it is ignored for those
+        let modifiers = match code {
+            KeyCode::Char(_) | KeyCode::BackTab => modifiers - KeyModifiers::SHIFT,
+            _ => modifiers,
+        };
+        KeyBinding { code, modifiers }
+    }
+}
+
+impl From<KeyEvent> for KeyBinding {
+    fn from(key: KeyEvent) -> Self {
+        KeyBinding::new(key.code, key.modifiers)
+    }
+}
+
+fn parse_key_code(s: &str) -> Result<KeyCode, String> {
+    let mut chars = s.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok(KeyCode::Char(c));
+    }
+
+    let code = match s.to_lowercase().as_str() {
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "backspace" => KeyCode::Backspace,
+        "delete" => KeyCode::Delete,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "space" => KeyCode::Char(' '),
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "insert" => KeyCode::Insert,
+        f if f.starts_with('f') => f[1..]
+            .parse::<u8>()
+            .map(KeyCode::F)
+            .map_err(|_| format!("unknown key: {}", s))?,
+        _ => return Err(format!("unknown key: {}", s)),
+    };
+    Ok(code)
+}
+
+// "ctrl-alt-x", "shift-tab", "?", "-" (a lone dash is the dash key)
+impl FromStr for KeyBinding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        while let Some((modifier, key)) = rest.split_once('-') {
+            if key.is_empty() {
+                break;
+            }
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier in {}", s)),
+            };
+            rest = key;
+        }
+        Ok(KeyBinding::new(parse_key_code(rest)?, modifiers))
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt-")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "shift-")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "f{}", n),
+            KeyCode::BackTab => write!(f, "backtab"),
+            KeyCode::PageUp => write!(f, "pageup"),
+            KeyCode::PageDown => write!(f, "pagedown"),
+            code => write!(f, "{}", format!("{:?}", code).to_lowercase()),
+        }
+    }
+}
+
+const DEFAULT_BINDINGS: &[(UserInput, &[&str])] = &[
+    (UserInput::Next, &["right", "l"]),
+    (UserInput::Prev, &["left", "h"]),
+    (UserInput::NextSelect, &["down", "j"]),
+    (UserInput::PrevSelect, &["up", "k"]),
+    (UserInput::NextField, &["tab"]),
+    (UserInput::PrevField, &["backtab"]),
+    (UserInput::Submit, &["enter"]),
+    (UserInput::Esc, &["esc"]),
+    (UserInput::Backspace, &["backspace"]),
+    (UserInput::DeleteForward, &["delete"]),
+    (UserInput::DeleteWord, &["ctrl-w", "alt-backspace"]),
+    (UserInput::Home, &["home"]),
+    (UserInput::End, &["end", "ctrl-e"]),
+    (UserInput::WordLeft, &["ctrl-left", "alt-b"]),
+    (UserInput::WordRight, &["ctrl-right", "alt-f"]),
+    (UserInput::SelectAll, &["ctrl-a"]),
+    (UserInput::Add, &["a"]),
+    (UserInput::Delete, &["d"]),
+    (UserInput::Restore, &["r"]),
+    (UserInput::Trash, &["t"]),
+    (UserInput::Search, &["/"]),
+    (UserInput::Stats, &["g"]),
+    (UserInput::Report, &["p"]),
+    (UserInput::Reimbursements, &["R"]),
+    (UserInput::Rules, &["u"]),
+    (UserInput::Payees, &["p"]),
+    (UserInput::Attach, &["f"]),
+    (UserInput::OpenAttachments, &["o"]),
+    (UserInput::Accounts, &["A"]),
+    (UserInput::Transfer, &["x"]),
+    (UserInput::ToggleCleared, &["c"]),
+    (UserInput::Reconcile, &["C"]),
+    (UserInput::FinishReconcile, &["F"]),
+    (UserInput::Unlock, &["U"]),
+    (UserInput::Envelopes, &["e"]),
+    (UserInput::Income, &["i"]),
+    (UserInput::Goals, &["G"]),
+    (UserInput::Debts, &["D"]),
+    (UserInput::Pay, &["p"]),
+    (UserInput::ExtraPayment, &["i"]),
+    (UserInput::Newline, &["alt-enter"]),
+    (UserInput::CycleSort, &["s"]),
+    (UserInput::ReverseSort, &["r"]),
+    (UserInput::WeekPeriod, &["w"]),
+    (UserInput::MonthPeriod, &["m"]),
+    (UserInput::YearPeriod, &["y"]),
+    (UserInput::Help, &["?", "f1"]),
+    (UserInput::CycleTheme, &["T"]),
+    (UserInput::Quit, &["ctrl-c"]),
+];
+
+// which keys trigger which actions
+// a key can be bound to several actions, the current screen picks the one it understands
+pub struct Keymap {
+    bindings: Vec<(KeyBinding, UserInput)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = DEFAULT_BINDINGS
+            .iter()
+            .flat_map(|(action, keys)| {
+                keys.iter()
+                    .map(move |key| (key.parse::<KeyBinding>().unwrap(), action.clone()))
+            })
+            .collect();
+        Keymap { bindings }
+    }
+}
+
+impl Keymap {
+    // ~/.config/budgr/keymap.json maps action names to lists of keys, e.g.
+    // { "NextSelect": ["down", "j", "ctrl-n"], "Help": ["?", "f1"] }
+    // actions in the file replace the default keys for that action, the rest keep their defaults
+    pub fn load() -> Result<Self, String> {
+        match File::open(get_keymap_path()) {
+            Ok(file) => Keymap::from_reader(BufReader::new(file)),
+            Err(_) => Ok(Keymap::default()),
+        }
+    }
+
+    // the defaults with the user's bindings from a keymap file on top
+    fn from_reader(reader: impl Read) -> Result<Self, String> {
+        let mut keymap = Keymap::default();
+        let user: HashMap<UserInput, Vec<String>> = serde_json::from_reader(reader)
+            .map_err(|err| format!("invalid keymap file: {}", err))?;
+        for (action, keys) in user {
+            keymap.bindings.retain(|(_, a)| *a != action);
+            for key in keys {
+                keymap.bindings.push((key.parse()?, action.clone()));
+            }
+        }
+        Ok(keymap)
+    }
+
+    // the first action bound to the key that the current screen accepts
+    pub fn lookup(&self, key: KeyBinding, accepted: &[UserInput]) -> Option<UserInput> {
+        self.bindings
+            .iter()
+            .find(|(k, action)| *k == key && accepted.contains(action))
+            .map(|(_, action)| action.clone())
+    }
+
+    // every key bound to an action, for the help overlay
+    pub fn keys_for(&self, action: &UserInput) -> Vec<KeyBinding> {
+        self.bindings
+            .iter()
+            .filter(|(_, a)| a == action)
+            .map(|(k, _)| *k)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(s: &str) -> KeyBinding {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn keys_read_back_the_way_they_are_written() {
+        for s in ["ctrl-x", "h", "j", "k", "l", "alt-backspace", "ctrl-alt-f5", "space", "pageup", "-", "?", "shift-left"] {
+            assert_eq!(key(s).to_string(), s);
+        }
+        assert_eq!(key("ctrl-x"), KeyBinding::new(KeyCode::Char('x'), KeyModifiers::CONTROL));
+        assert_eq!(key("Ctrl-Enter").to_string(), "ctrl-enter");
+        // shift is part of the character already
+        assert_eq!(key("shift-a"), key("a"));
+        assert_eq!(KeyBinding::new(KeyCode::Char('J'), KeyModifiers::SHIFT).to_string(), "J");
+    }
+
+    #[test]
+    fn unknown_keys_and_modifiers_are_rejected() {
+        assert_eq!("hyper-x".parse::<KeyBinding>(), Err("unknown modifier in hyper-x".to_string()));
+        assert_eq!("ctrl-escape".parse::<KeyBinding>(), Err("unknown key: escape".to_string()));
+        assert_eq!("fx".parse::<KeyBinding>(), Err("unknown key: fx".to_string()));
+    }
+
+    #[test]
+    fn user_bindings_replace_the_defaults_of_their_action() {
+        let keymap = Keymap::from_reader(r#"{ "NextSelect": ["ctrl-n"], "Help": ["?", "ctrl-h"] }"#.as_bytes()).unwrap();
+        let accepted = [UserInput::NextSelect, UserInput::PrevSelect, UserInput::Help];
+
+        assert_eq!(keymap.lookup(key("ctrl-n"), &accepted), Some(UserInput::NextSelect));
+        assert_eq!(keymap.lookup(key("j"), &accepted), None);
+        assert_eq!(keymap.lookup(key("down"), &accepted), None);
+        assert_eq!(keymap.lookup(key("ctrl-h"), &accepted), Some(UserInput::Help));
+        assert_eq!(keymap.lookup(key("f1"), &accepted), None);
+        // actions left out of the file keep their keys
+        assert_eq!(keymap.lookup(key("k"), &accepted), Some(UserInput::PrevSelect));
+        assert_eq!(keymap.keys_for(&UserInput::NextSelect), [key("ctrl-n")]);
+    }
+
+    #[test]
+    fn a_key_goes_to_the_action_the_screen_accepts() {
+        let keymap = Keymap::default();
+        assert_eq!(keymap.lookup(key("r"), &[UserInput::ReverseSort, UserInput::Next]), Some(UserInput::ReverseSort));
+        assert_eq!(keymap.lookup(key("r"), &[UserInput::Restore]), Some(UserInput::Restore));
+        assert_eq!(keymap.lookup(key("r"), &[UserInput::Next]), None);
+    }
+
+    #[test]
+    fn unknown_actions_and_keys_in_the_file_are_rejected() {
+        let err = Keymap::from_reader(r#"{ "Fly": ["f"] }"#.as_bytes()).err().unwrap();
+        assert!(err.starts_with("invalid keymap file: "), "{}", err);
+        let err = Keymap::from_reader(r#"{ "Help": ["ctrl-qq"] }"#.as_bytes()).err().unwrap();
+        assert_eq!(err, "unknown key: qq");
+    }
+}
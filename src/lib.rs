@@ -0,0 +1,39 @@
+//! Budget logs: lists of purchases stored as json files, with search, reports
+//! and a trash for deleted entries.
+//!
+//! This crate is the data model and storage behind the `budgr` terminal app.
+//! Everything public here follows semver: breaking changes to these types or
+//! functions only happen with a new minor version while the crate is `0.x`.
+//!
+//! ```no_run
+//! use budgr::log::{read_budgr_from_directory, today, PurchaseType};
+//! use budgr::report::Period;
+//!
+//! let mut budgr = read_budgr_from_directory()?;
+//! let food = match budgr.logs.iter().find(|log| log.name == "food") {
+//!     Some(log) => log.id,
+//!     None => budgr.new_log("food".to_string())?,
+//! };
+//! budgr.add_purchase(food, "bread".to_string(), 3, PurchaseType::Groceries, today())?;
+//! budgr.serialize()?;
+//! print!("{}", budgr.report(Period::Month));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+#![warn(missing_docs)]
+
+pub mod account;
+pub mod attachment;
+pub mod config;
+pub mod debt;
+pub mod envelope;
+pub mod goal;
+pub mod log;
+pub mod logfile;
+pub mod payee;
+pub mod reconcile;
+pub mod report;
+pub mod search;
+pub mod trash;
+
+#[cfg(test)]
+mod testing;
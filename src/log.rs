@@ -1,24 +1,102 @@
+//! Logs, purchases and reading and writing them to disk.
+
+use crate::account::{AccountId, Accounts};
+use crate::attachment::Attachment;
+use crate::debt::Debts;
+use crate::envelope::Envelopes;
+use crate::goal::Goals;
+use crate::logfile::logln;
+use crate::payee::Payees;
+use crate::reconcile::{check_unlocked, Cleared};
+use crate::trash::Trash;
+use chrono::{Local, NaiveDate};
 use dirs::home_dir;
 use glob::glob;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
-use std::fs::{create_dir, remove_file, DirEntry, File};
+use std::fs::{create_dir_all, remove_file, File};
 use std::io::BufReader;
-use std::io::Write;
-use std::path::PathBuf;
-
-fn get_log_dir() -> PathBuf {
-    [home_dir().unwrap(), ".local/share/budgr/logs/".into()]
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Where budgr keeps its data, `~/.local/share/budgr/`.
+pub fn get_data_dir() -> PathBuf {
+    [home_dir().unwrap(), ".local/share/budgr/".into()]
         .iter()
         .collect()
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+fn get_log_dir() -> PathBuf {
+    let mut path = get_data_dir();
+    path.push("logs/");
+    path
+}
+
+fn get_payees_path() -> PathBuf {
+    let mut path = get_data_dir();
+    path.push("payees.json");
+    path
+}
+
+fn get_accounts_path() -> PathBuf {
+    let mut path = get_data_dir();
+    path.push("accounts.json");
+    path
+}
+
+fn get_envelopes_path() -> PathBuf {
+    let mut path = get_data_dir();
+    path.push("envelopes.json");
+    path
+}
+
+fn get_goals_path() -> PathBuf {
+    let mut path = get_data_dir();
+    path.push("goals.json");
+    path
+}
+
+fn get_debts_path() -> PathBuf {
+    let mut path = get_data_dir();
+    path.push("debts.json");
+    path
+}
+
+fn get_trash_path() -> PathBuf {
+    let mut path = get_data_dir();
+    path.push("trash/trash.json");
+    path
+}
+
+/// The category a purchase falls under.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum PurchaseType {
+    /// Food and household shopping.
     Groceries,
+    /// Going out, hobbies and entertainment.
     Leisure,
+    /// Rent, utilities and other regular bills.
     Bill,
+    /// Books, fees and supplies.
     School,
+    /// Anything else, and the category of purchases saved before categories existed.
+    #[default]
+    Other,
+}
+
+impl PurchaseType {
+    /// Every category, in the order they are listed in the ui.
+    pub const ALL: [PurchaseType; 5] = [
+        PurchaseType::Groceries,
+        PurchaseType::Leisure,
+        PurchaseType::Bill,
+        PurchaseType::School,
+        PurchaseType::Other,
+    ];
 }
 
 impl fmt::Display for PurchaseType {
@@ -28,26 +106,220 @@ impl fmt::Display for PurchaseType {
             PurchaseType::Leisure => write!(f, "Leisure"),
             PurchaseType::Bill => write!(f, "Bill"),
             PurchaseType::School => write!(f, "School"),
+            PurchaseType::Other => write!(f, "Other"),
         }
     }
 }
 
+/// Case insensitive, so "bill" and "Bill" both parse.
+impl FromStr for PurchaseType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PurchaseType::ALL
+            .into_iter()
+            .find(|t| t.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or(format!("unknown purchase type: {}", s))
+    }
+}
+
+/// A stable id for a [`Log`], it stays the same across renames, sorting and saves.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(transparent)]
+pub struct LogId(Uuid);
+
+/// A stable id for a [`Purchase`], unique across every log.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(transparent)]
+pub struct PurchaseId(Uuid);
+
+impl LogId {
+    /// A new random id.
+    pub fn new() -> Self {
+        LogId(Uuid::new_v4())
+    }
+}
+
+impl PurchaseId {
+    /// A new random id.
+    pub fn new() -> Self {
+        PurchaseId(Uuid::new_v4())
+    }
+}
+
+/// A new random id, this is also what logs saved before ids existed get when loaded.
+impl Default for LogId {
+    fn default() -> Self {
+        LogId::new()
+    }
+}
+
+/// A new random id, this is also what purchases saved before ids existed get when loaded.
+impl Default for PurchaseId {
+    fn default() -> Self {
+        PurchaseId::new()
+    }
+}
+
+impl fmt::Display for LogId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for PurchaseId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// One thing that was bought.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Purchase {
+    /// Identifies the purchase for [`Budgr`] methods.
+    #[serde(default)]
+    pub id: PurchaseId,
+    /// What was bought, or where.
     pub name: String,
+    /// Who it was bought from, a name from the [`Payees`] registry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payee: Option<String>,
+    /// The price in whole units of currency.
     pub cost: i64,
+    /// The category, [`PurchaseType::Other`] for purchases saved before categories existed.
+    #[serde(default)]
+    pub category: PurchaseType,
+    /// When the purchase was made, `None` for purchases saved before dates existed.
+    #[serde(default)]
+    pub date: Option<NaiveDate>,
+    /// How the cost is spread over several categories, empty when it all goes to [`Purchase::category`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub splits: Vec<Split>,
+    /// Free form labels like `trip-2026`, lowercase and without spaces, see [`parse_tags`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// A note about the purchase, can span several lines.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub note: String,
+    /// Receipts and other files kept with the purchase.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+    /// The account it was paid from, see [`Budgr::ledger`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<AccountId>,
+    /// Whether it has been checked against a bank statement, see [`Budgr::toggle_cleared`].
+    #[serde(default, skip_serializing_if = "Cleared::is_uncleared")]
+    pub cleared: Cleared,
+}
+
+/// One line of a split purchase.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Split {
+    /// The category this part of the purchase counts towards.
+    pub category: PurchaseType,
+    /// How much of the purchase's cost this part is.
+    pub amount: i64,
+}
+
+impl fmt::Display for Split {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.category, self.amount)
+    }
+}
+
+impl Purchase {
+    /// How much of the cost goes to each category, one entry per split or just the category and cost.
+    pub fn category_amounts(&self) -> Vec<(PurchaseType, i64)> {
+        match self.splits.is_empty() {
+            true => vec![(self.category, self.cost)],
+            false => self.splits.iter().map(|s| (s.category, s.amount)).collect(),
+        }
+    }
+
+    /// True if the purchase is tagged with `tag`.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// True if any of the cost goes to `category`.
+    pub fn has_category(&self, category: PurchaseType) -> bool {
+        self.category_amounts().iter().any(|(c, _)| *c == category)
+    }
+}
+
+/// Parse splits typed in by the user, like `groceries 20, other 5`. An empty string means no splits.
+pub fn parse_splits(s: &str) -> Result<Vec<Split>, String> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let (category, amount) = part
+                .rsplit_once(' ')
+                .ok_or(format!("split needs a category and an amount: {}", part))?;
+            let amount = amount
+                .parse::<i64>()
+                .map_err(|_| format!("split amount must be a whole number: {}", amount))?;
+            Ok(Split {
+                category: category.parse()?,
+                amount,
+            })
+        })
+        .collect()
+}
+
+/// Parse tags typed in by the user, separated by commas or spaces.
+///
+/// Tags are lowercased and repeats are dropped, so `Work, trip-2026 work` gives `work` and `trip-2026`.
+pub fn parse_tags(s: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in s.split([',', ' ']).map(str::trim).filter(|t| !t.is_empty()) {
+        let tag = tag.to_lowercase();
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Check that splits add up to `cost`, no splits at all is always fine.
+pub fn check_splits(cost: i64, splits: &[Split]) -> Result<(), String> {
+    let total: i64 = splits.iter().map(|s| s.amount).sum();
+    match splits.is_empty() || total == cost {
+        true => Ok(()),
+        false => Err(format!("splits add up to {} but the cost is {}", total, cost)),
+    }
+}
+
+/// The current date in the local timezone.
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
 }
 
-// A log is a list of purchases
+/// Parse a `YYYY-MM-DD` date typed in by the user, an empty string means today.
+pub fn parse_date(s: &str) -> Result<NaiveDate, String> {
+    if s.trim().is_empty() {
+        return Ok(today());
+    }
+    NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d")
+        .map_err(|_| format!("invalid date (expected YYYY-MM-DD): {}", s))
+}
+
+/// A named list of purchases, stored as `<name>.json` in the logs folder.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Log {
+    /// Identifies the log for [`Budgr`] methods.
+    #[serde(default)]
+    pub id: LogId,
+    /// The purchases in the order they were added.
     pub purchases: Vec<Purchase>,
+    /// The log's name, also its file name, so it can't contain spaces.
     pub name: String,
 }
 
 impl Default for Log {
     fn default() -> Log {
         Log {
+            id: LogId::new(),
             purchases: Vec::new(),
             name: "new-log".to_string(),
         }
@@ -59,20 +331,62 @@ struct SerializeLog {
     contents: String,
 }
 
+/// Every log plus the trash, and everything needed to change and save them.
+///
+/// Methods that change the data mark it unsaved until [`Budgr::serialize`] writes it out.
 pub struct Budgr {
+    /// The logs in the order they were loaded or created.
     pub logs: Vec<Log>,
+    /// Deleted logs and purchases.
+    pub trash: Trash,
+    /// Known payees and the rules for filling in new purchases.
+    pub payees: Payees,
+    /// Accounts purchases are paid from and transfers between them.
+    pub accounts: Accounts,
+    /// Monthly income and what was assigned to each category.
+    pub envelopes: Envelopes,
+    /// Savings goals.
+    pub goals: Goals,
+    /// Loans and money lent.
+    pub debts: Debts,
+    // set by anything that changes the data, cleared by serialize
+    pub(crate) unsaved: bool,
+}
+
+impl Default for Budgr {
+    fn default() -> Self {
+        Budgr::new()
+    }
 }
 
 // everything needed to interact with the data
 impl Budgr {
-    // change this to deserialize, not just make new variables
+    /// An empty budgr, use [`read_budgr_from_directory`] to load the saved one.
     pub fn new() -> Self {
         let logs: Vec<Log> = Vec::new(); // temporary (before frontend)
-        Self { logs }
+        Self {
+            logs,
+            trash: Trash::default(),
+            payees: Payees::default(),
+            accounts: Accounts::default(),
+            envelopes: Envelopes::default(),
+            goals: Goals::default(),
+            debts: Debts::default(),
+            unsaved: false,
+        }
     }
 
-    pub fn serialize(&self) -> Result<(), std::io::Error> {
-        // TODO: make directory if it does not exist
+    /// True if anything changed since the last [`Budgr::serialize`].
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.unsaved
+    }
+
+    /// Write every log, the trash, the payees, the accounts, the envelopes, the goals and the debts to the data folder.
+    ///
+    /// Attachments that nothing refers to anymore are deleted once everything is written.
+    pub fn serialize(&mut self) -> Result<(), std::io::Error> {
+        create_dir_all(get_log_dir())?;
+
         // serialize the logs into SerializeLog type to be put away into files
         let mut serialize_logs: Vec<SerializeLog> = Vec::new();
         for log in self.logs.iter() {
@@ -90,19 +404,64 @@ impl Budgr {
         for log in serialize_logs.iter() {
             let mut path: PathBuf = get_log_dir();
             path.push(log.name.clone());
-            let mut file = match File::create(path) {
-                Ok(file) => file,
-                Err(err) => panic!("create file failed: {}", err),
-            };
-            match file.write_all(log.contents.as_bytes()) {
-                Ok(_) => println!("successful write"),
-                Err(err) => panic!("write all failed: {}", err),
-            }
+            let mut file = File::create(path)?;
+            file.write_all(log.contents.as_bytes())?;
         }
+
+        self.serialize_trash()?;
+        let file = File::create(get_payees_path())?;
+        serde_json::to_writer(file, &self.payees)?;
+        let file = File::create(get_accounts_path())?;
+        serde_json::to_writer(file, &self.accounts)?;
+        let file = File::create(get_envelopes_path())?;
+        serde_json::to_writer(file, &self.envelopes)?;
+        let file = File::create(get_goals_path())?;
+        serde_json::to_writer(file, &self.goals)?;
+        let file = File::create(get_debts_path())?;
+        serde_json::to_writer(file, &self.debts)?;
+        logln!("saved {} logs", serialize_logs.len());
+        self.remove_orphaned_attachments()?;
+        self.unsaved = false;
         Ok(())
     }
 
-    pub fn new_log(&mut self, name: String) -> Result<(), String> {
+    fn serialize_trash(&self) -> Result<(), std::io::Error> {
+        let path = get_trash_path();
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir)?;
+        }
+        let file = File::create(path)?;
+        serde_json::to_writer(file, &self.trash)?;
+        Ok(())
+    }
+
+    /// The log with this id.
+    pub fn log(&self, id: LogId) -> Option<&Log> {
+        self.logs.iter().find(|log| log.id == id)
+    }
+
+    /// Where the log with this id is in [`Budgr::logs`].
+    pub fn log_index(&self, id: LogId) -> Option<usize> {
+        self.logs.iter().position(|log| log.id == id)
+    }
+
+    fn log_mut(&mut self, id: LogId) -> Result<&mut Log, String> {
+        self.logs
+            .iter_mut()
+            .find(|log| log.id == id)
+            .ok_or(format!("ERROR: no log with id {}", id))
+    }
+
+    pub(crate) fn purchase_mut(&mut self, log: LogId, purchase: PurchaseId) -> Result<&mut Purchase, String> {
+        let log = self.log_mut(log)?;
+        let index = log
+            .purchase_index(purchase)
+            .ok_or(format!("ERROR: no purchase with id {} in {}", purchase, log.name))?;
+        Ok(&mut log.purchases[index])
+    }
+
+    /// Add an empty log, fails if the name is taken or has spaces in it.
+    pub fn new_log(&mut self, name: String) -> Result<LogId, String> {
         // check if log exists already
         for log in self.logs.iter() {
             if log.name == name {
@@ -117,73 +476,222 @@ impl Budgr {
             }
         }
 
-        self.logs.push(Log {
+        let log = Log {
             name,
             ..Default::default()
-        });
-        Ok(())
+        };
+        let id = log.id;
+        self.logs.push(log);
+        self.unsaved = true;
+        Ok(id)
     }
 
-    pub fn delete_log(&mut self, log_index: usize) -> Result<(), String> {
-        if log_index > self.logs.len() || self.logs.len() == 0 {
-            return Err("ERROR: log index out of range".to_string());
+    /// Move a log into the trash.
+    ///
+    /// The trash is written straight away and the log's file is only removed once it is safely on disk.
+    /// Fails if any of its purchases is reconciled, as those can't be removed until they are unlocked.
+    pub fn delete_log(&mut self, id: LogId) -> Result<(), String> {
+        let index = self
+            .log_index(id)
+            .ok_or(format!("ERROR: no log with id {}", id))?;
+        for purchase in self.logs[index].purchases.iter() {
+            check_unlocked(purchase)?;
         }
 
-        let path = get_path_to_log(self.logs[log_index].name.as_str());
-        self.logs.remove(log_index);
-        println!("PATH TO REMOVE: {}", path.display());
-        remove_file(path).unwrap();
-
-        Ok(())
+        let log = self.logs.remove(index);
+        let path = get_path_to_log(log.name.as_str());
+        self.trash.add_log(log);
+        self.unsaved = true;
+
+        self.serialize_trash()
+            .map_err(|err| format!("ERROR: could not write trash: {}", err))?;
+        match remove_file(path) {
+            Ok(_) => Ok(()),
+            // the log was never saved, nothing to clean up
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(format!("ERROR: could not remove log file: {}", err)),
+        }
     }
 
+    /// Add a purchase to a log.
     pub fn add_purchase(
         &mut self,
-        log_index: usize,
+        log: LogId,
         name: String,
         cost: i64,
-    ) -> Result<(), String> {
-        let valid_index = match log_index {
-            _ if self.logs.len() < log_index => return Err("ERROR: Index too large".to_string()),
-            _ => log_index,
-        };
+        category: PurchaseType,
+        date: NaiveDate,
+    ) -> Result<PurchaseId, String> {
+        let id = self.log_mut(log)?.add_purchase(name, cost, category, date);
+        self.unsaved = true;
+        Ok(id)
+    }
 
-        self.logs[valid_index].add_purchase(name, cost);
+    /// Split a purchase over several categories, the amounts have to add up to its cost.
+    ///
+    /// The purchase's [`Purchase::category`] becomes the category of the largest split, an empty list removes the split.
+    pub fn split_purchase(&mut self, log: LogId, purchase: PurchaseId, splits: Vec<Split>) -> Result<(), String> {
+        let p = self.purchase_mut(log, purchase)?;
+        check_unlocked(p)?;
+        check_splits(p.cost, &splits)?;
 
+        if let Some(largest) = splits.iter().max_by_key(|s| s.amount) {
+            p.category = largest.category;
+        }
+        p.splits = splits;
+        self.unsaved = true;
         Ok(())
     }
 
-    pub fn remove_purchase(
-        &mut self,
-        log_index: usize,
-        purchase_index: usize,
-    ) -> Result<(), &'static str> {
-        if log_index > self.logs.len() {
-            return Err("index out of bounds");
-        } else if purchase_index > self.logs[log_index].purchases.len() {
-            return Err("index out of bounds");
-        }
+    /// Replace a purchase's tags and note.
+    pub fn annotate_purchase(&mut self, log: LogId, purchase: PurchaseId, tags: Vec<String>, note: String) -> Result<(), String> {
+        let p = self.purchase_mut(log, purchase)?;
+        check_unlocked(p)?;
+        p.tags = tags;
+        p.note = note;
+        self.unsaved = true;
+        Ok(())
+    }
+
+    /// Move a purchase into the trash, reconciled purchases are locked and stay put.
+    pub fn remove_purchase(&mut self, log: LogId, purchase: PurchaseId) -> Result<(), String> {
+        let log = self.log_mut(log)?;
+        let index = log
+            .purchase_index(purchase)
+            .ok_or(format!("ERROR: no purchase with id {} in {}", purchase, log.name))?;
+        check_unlocked(&log.purchases[index])?;
 
-        self.logs[log_index].purchases.remove(purchase_index);
+        let removed = log.purchases.remove(index);
+        let (log_id, log_name) = (log.id, log.name.clone());
+        self.trash.add_purchase(log_id, log_name, removed);
+        self.unsaved = true;
 
         Ok(())
     }
 
-    pub fn get_total(&self, log_index: usize) -> Result<i64, String> {
-        let valid_index = match log_index {
-            _ if self.logs.len() < log_index => return Err("ERROR: Index too large".to_string()),
-            _ => log_index,
-        };
+    /// Put a trashed log back, fails if a log with the same name has been made since.
+    pub fn restore_log(&mut self, id: LogId) -> Result<(), String> {
+        let index = self
+            .trash
+            .logs
+            .iter()
+            .position(|t| t.log.id == id)
+            .ok_or(format!("ERROR: no trashed log with id {}", id))?;
+        if self
+            .logs
+            .iter()
+            .any(|log| log.name == self.trash.logs[index].log.name)
+        {
+            return Err("ERROR: a log with that name already exists".to_string());
+        }
+
+        let trashed = self.trash.logs.remove(index);
+        self.logs.push(trashed.log);
+        self.unsaved = true;
+        Ok(())
+    }
 
-        Ok(self.logs[valid_index]
+    /// Put a trashed purchase back into the log it came from, recreating the log if it is gone.
+    pub fn restore_purchase(&mut self, id: PurchaseId) -> Result<(), String> {
+        let index = self
+            .trash
             .purchases
             .iter()
-            .map(|purchase| purchase.cost)
-            .sum())
+            .position(|t| t.purchase.id == id)
+            .ok_or(format!("ERROR: no trashed purchase with id {}", id))?;
+
+        let trashed = self.trash.purchases.remove(index);
+        // the log may have been renamed, or deleted and made again under the same name
+        let existing = self
+            .logs
+            .iter()
+            .find(|log| log.id == trashed.log_id)
+            .or_else(|| self.logs.iter().find(|log| log.name == trashed.log_name))
+            .map(|log| log.id);
+        let log = match existing {
+            Some(id) => id,
+            None => self.new_log(trashed.log_name)?,
+        };
+        self.log_mut(log)?.purchases.push(trashed.purchase);
+        self.unsaved = true;
+        Ok(())
+    }
+
+    /// Forget trash older than `max_age_days`, 0 keeps it forever.
+    pub fn purge_old_trash(&mut self, max_age_days: u64) {
+        if self.trash.purge_older_than(max_age_days) > 0 {
+            self.unsaved = true;
+        }
+    }
+
+    /// Permanently delete a trashed log.
+    pub fn purge_log(&mut self, id: LogId) -> Result<(), String> {
+        let before = self.trash.logs.len();
+        self.trash.logs.retain(|t| t.log.id != id);
+        if self.trash.logs.len() == before {
+            return Err(format!("ERROR: no trashed log with id {}", id));
+        }
+        self.unsaved = true;
+        Ok(())
+    }
+
+    /// Permanently delete a trashed purchase.
+    pub fn purge_purchase(&mut self, id: PurchaseId) -> Result<(), String> {
+        let before = self.trash.purchases.len();
+        self.trash.purchases.retain(|t| t.purchase.id != id);
+        if self.trash.purchases.len() == before {
+            return Err(format!("ERROR: no trashed purchase with id {}", id));
+        }
+        self.unsaved = true;
+        Ok(())
+    }
+
+    /// The total cost of a log.
+    pub fn get_total(&self, id: LogId) -> Result<i64, String> {
+        self.log(id)
+            .map(|log| log.get_total())
+            .ok_or(format!("ERROR: no log with id {}", id))
+    }
+
+    /// Every tag used by a purchase in any log, sorted.
+    pub fn tags(&self) -> BTreeSet<String> {
+        self.logs
+            .iter()
+            .flat_map(|log| log.purchases.iter())
+            .flat_map(|p| p.tags.iter().cloned())
+            .collect()
+    }
+
+    /// [`Log::tag_totals`] of every log added together.
+    pub fn tag_totals(&self) -> BTreeMap<String, i64> {
+        let mut totals = BTreeMap::new();
+        for (tag, total) in self.logs.iter().flat_map(|log| log.tag_totals()) {
+            *totals.entry(tag).or_insert(0) += total;
+        }
+        totals
     }
 
+    /// [`Log::category_totals`] of every log added together.
+    pub fn category_totals(&self) -> BTreeMap<PurchaseType, i64> {
+        let mut totals = BTreeMap::new();
+        for (category, total) in self.logs.iter().flat_map(|log| log.category_totals()) {
+            *totals.entry(category).or_insert(0) += total;
+        }
+        totals
+    }
+
+    /// [`Log::daily_totals`] of every log added together.
+    pub fn daily_totals(&self) -> BTreeMap<NaiveDate, i64> {
+        let mut totals = BTreeMap::new();
+        for (date, total) in self.logs.iter().flat_map(|log| log.daily_totals()) {
+            *totals.entry(date).or_insert(0) += total;
+        }
+        totals
+    }
+
+    /// Print every log to stdout.
     pub fn print_logs(&self) {
-        (0..self.logs.len()).for_each(|i| self.print_log(i));
+        self.logs.iter().for_each(Budgr::print_log);
     }
 
     fn print_purchase(purchase: &Purchase) {
@@ -193,60 +701,85 @@ impl Budgr {
         );
     }
 
-    pub fn print_log(&self, log_index: usize) {
-        println!(
-            "Log Print: {}, log index: {}",
-            self.logs[log_index].name, log_index
-        );
-        for purchase in self.logs[log_index].purchases.iter() {
-            Budgr::print_purchase(&purchase);
+    /// Print a log to stdout.
+    pub fn print_log(log: &Log) {
+        println!("Log Print: {}, log id: {}", log.name, log.id);
+        for purchase in log.purchases.iter() {
+            Budgr::print_purchase(purchase);
         }
         println!("\n");
     }
 
-    pub fn get_expenses(&self, log_index: usize) -> Result<i64, String> {
-        let valid_index = match log_index {
-            _ if self.logs.len() < log_index || self.logs.len() == 0 => {
-                return Err("ERROR: Index incorrect".to_string())
-            }
-            _ => log_index,
-        };
-
-        let total = self.logs[valid_index]
-            .purchases
-            .iter()
-            .map(|p| p.cost)
-            .sum();
-
-        Ok(total)
+    /// The total cost of a log, same as [`Budgr::get_total`].
+    pub fn get_expenses(&self, id: LogId) -> Result<i64, String> {
+        self.get_total(id)
     }
 }
 
+/// Load every log, the trash, the payees, the accounts, the envelopes, the goals and the debts from the data folder.
 pub fn read_budgr_from_directory() -> Result<Budgr, std::io::Error> {
     let mut budgr: Budgr = Budgr::new();
 
-    // there should be a path in .local for this program in the future, make a const for this?
     let mut glob_str = get_log_dir();
     glob_str.push("*.json");
-    // TODO: figure out a way to handle glob_str that isn't completely stupid
-    for path in glob(glob_str.into_os_string().into_string().unwrap().as_str()).unwrap() {
-        if let Ok(path) = path {
-            println!("LOADED FILE: {}", path.display());
-            let file = File::open(path)?;
-            let reader = BufReader::new(file);
-            budgr.logs.push(serde_json::from_reader(reader)?);
+    let paths = glob(&glob_str.to_string_lossy())
+        .map_err(|err| std::io::Error::new(ErrorKind::InvalidInput, err.to_string()))?;
+    for path in paths.flatten() {
+        logln!("loaded {}", path.display());
+        let file = File::open(&path).map_err(in_file(&path))?;
+        let value: serde_json::Value =
+            serde_json::from_reader(BufReader::new(file)).map_err(|err| in_file(&path)(err.into()))?;
+        // logs from before ids existed get new ones, save them so they stick
+        if missing_ids(&value) {
+            budgr.unsaved = true;
         }
+        budgr.logs.push(serde_json::from_value(value).map_err(|err| in_file(&path)(err.into()))?);
+    }
+
+    // no trash file just means nothing has been deleted yet, the same goes for the rest
+    if let Some(trash) = read_optional(get_trash_path())? {
+        budgr.trash = trash;
+    }
+    if let Some(payees) = read_optional(get_payees_path())? {
+        budgr.payees = payees;
+    }
+    if let Some(accounts) = read_optional(get_accounts_path())? {
+        budgr.accounts = accounts;
+    }
+    if let Some(envelopes) = read_optional(get_envelopes_path())? {
+        budgr.envelopes = envelopes;
+    }
+    if let Some(goals) = read_optional(get_goals_path())? {
+        budgr.goals = goals;
+    }
+    if let Some(debts) = read_optional(get_debts_path())? {
+        budgr.debts = debts;
     }
 
     Ok(budgr)
 }
 
-// check if a given index is valid
-fn check_index<T>(vec: Vec<T>, index: usize) -> Result<usize, String> {
-    match index {
-        _ if index > vec.len() => return Err("index too large".to_string()),
-        _ => return Ok(index),
-    }
+// a json file that may not exist yet, errors say which file was unreadable
+fn read_optional<T: DeserializeOwned>(path: PathBuf) -> Result<Option<T>, std::io::Error> {
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(in_file(&path)(err)),
+    };
+    serde_json::from_reader(BufReader::new(file))
+        .map(Some)
+        .map_err(|err| in_file(&path)(err.into()))
+}
+
+// put the file's path in front of an error about it
+fn in_file(path: &Path) -> impl Fn(std::io::Error) -> std::io::Error + '_ {
+    move |err| std::io::Error::new(err.kind(), format!("{}: {}", path.display(), err))
+}
+
+// whether a log or any of its purchases was saved without an id
+fn missing_ids(log: &serde_json::Value) -> bool {
+    let purchases = log["purchases"].as_array().map(|p| p.as_slice()).unwrap_or_default();
+    log.get("id").is_none() || purchases.iter().any(|p| p.get("id").is_none())
 }
 
 // return the absolute path to a log json file
@@ -258,10 +791,173 @@ fn get_path_to_log(log_name: &str) -> PathBuf {
 }
 
 impl Log {
+    /// The total cost of every purchase.
     pub fn get_total(&self) -> i64 {
         self.purchases.iter().map(|purchase| purchase.cost).sum()
     }
-    pub fn add_purchase(&mut self, name: String, cost: i64) {
-        self.purchases.push(Purchase { name, cost });
+
+    /// Spending per tag, a purchase with several tags counts towards each of them.
+    pub fn tag_totals(&self) -> BTreeMap<String, i64> {
+        let mut totals = BTreeMap::new();
+        for p in self.purchases.iter() {
+            for tag in p.tags.iter() {
+                *totals.entry(tag.clone()).or_insert(0) += p.cost;
+            }
+        }
+        totals
+    }
+
+    /// Spending per category, categories without purchases are left out.
+    pub fn category_totals(&self) -> BTreeMap<PurchaseType, i64> {
+        let mut totals = BTreeMap::new();
+        for (category, amount) in self.purchases.iter().flat_map(Purchase::category_amounts) {
+            *totals.entry(category).or_insert(0) += amount;
+        }
+        totals
+    }
+
+    /// Spending per day, purchases without a date are left out.
+    pub fn daily_totals(&self) -> BTreeMap<NaiveDate, i64> {
+        let mut totals = BTreeMap::new();
+        for p in self.purchases.iter() {
+            if let Some(date) = p.date {
+                *totals.entry(date).or_insert(0) += p.cost;
+            }
+        }
+        totals
+    }
+
+    /// The purchase with this id.
+    pub fn purchase(&self, id: PurchaseId) -> Option<&Purchase> {
+        self.purchases.iter().find(|p| p.id == id)
+    }
+
+    /// Where the purchase with this id is in [`Log::purchases`].
+    pub fn purchase_index(&self, id: PurchaseId) -> Option<usize> {
+        self.purchases.iter().position(|p| p.id == id)
+    }
+
+    /// Add a purchase to the end of the log.
+    pub fn add_purchase(&mut self, name: String, cost: i64, category: PurchaseType, date: NaiveDate) -> PurchaseId {
+        let id = PurchaseId::new();
+        self.purchases.push(Purchase {
+            id,
+            name,
+            payee: None,
+            cost,
+            category,
+            date: Some(date),
+            splits: Vec::new(),
+            tags: Vec::new(),
+            note: String::new(),
+            attachments: Vec::new(),
+            account: None,
+            cleared: Cleared::Uncleared,
+        });
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a budgr with a food log holding bread, and the purchase's id
+    fn budgr_with_bread() -> (Budgr, LogId, PurchaseId) {
+        let mut budgr = Budgr::new();
+        let log = budgr.new_log("food".to_string()).unwrap();
+        let bread = budgr.add_purchase(log, "bread".to_string(), 3, PurchaseType::Groceries, today()).unwrap();
+        (budgr, log, bread)
+    }
+
+    #[test]
+    fn removed_purchase_goes_back_where_it_came_from() {
+        let (mut budgr, log, bread) = budgr_with_bread();
+        budgr.remove_purchase(log, bread).unwrap();
+        assert!(budgr.log(log).unwrap().purchases.is_empty());
+        assert_eq!(budgr.trash.purchases[0].log_name, "food");
+
+        budgr.restore_purchase(bread).unwrap();
+        assert!(budgr.trash.is_empty());
+        assert_eq!(budgr.log(log).unwrap().purchases[0].id, bread);
+    }
+
+    #[test]
+    fn restoring_a_purchase_follows_a_renamed_log() {
+        let (mut budgr, log, bread) = budgr_with_bread();
+        budgr.remove_purchase(log, bread).unwrap();
+        budgr.logs[0].name = "groceries".to_string();
+
+        budgr.restore_purchase(bread).unwrap();
+        assert_eq!(budgr.logs.len(), 1);
+        assert_eq!(budgr.log(log).unwrap().purchases.len(), 1);
+    }
+
+    #[test]
+    fn restoring_a_purchase_remakes_its_log() {
+        let (mut budgr, log, bread) = budgr_with_bread();
+        budgr.remove_purchase(log, bread).unwrap();
+        let trashed = budgr.logs.remove(0);
+        budgr.trash.add_log(trashed);
+
+        budgr.restore_purchase(bread).unwrap();
+        assert_eq!(budgr.logs.len(), 1);
+        assert_eq!(budgr.logs[0].name, "food");
+        assert_eq!(budgr.logs[0].purchases[0].id, bread);
+    }
+
+    #[test]
+    fn trashed_log_comes_back_unless_its_name_is_taken() {
+        let (mut budgr, log, _) = budgr_with_bread();
+        let trashed = budgr.logs.remove(0);
+        budgr.trash.add_log(trashed);
+
+        budgr.new_log("food".to_string()).unwrap();
+        assert!(budgr.restore_log(log).is_err());
+        assert_eq!(budgr.trash.logs.len(), 1);
+
+        budgr.logs.clear();
+        budgr.restore_log(log).unwrap();
+        assert!(budgr.trash.is_empty());
+        assert_eq!(budgr.log(log).unwrap().purchases.len(), 1);
+    }
+
+    #[test]
+    fn purged_entries_are_gone_for_good() {
+        let (mut budgr, log, bread) = budgr_with_bread();
+        budgr.remove_purchase(log, bread).unwrap();
+        let trashed = budgr.logs.remove(0);
+        budgr.trash.add_log(trashed);
+
+        budgr.purge_purchase(bread).unwrap();
+        budgr.purge_log(log).unwrap();
+        assert!(budgr.trash.is_empty());
+        assert!(budgr.restore_purchase(bread).is_err());
+        assert!(budgr.restore_log(log).is_err());
+        assert!(budgr.purge_log(log).is_err());
+    }
+
+    #[test]
+    fn purging_old_trash_marks_the_budgr_unsaved_only_when_something_went() {
+        let (mut budgr, log, bread) = budgr_with_bread();
+        budgr.remove_purchase(log, bread).unwrap();
+        budgr.unsaved = false;
+
+        budgr.purge_old_trash(30);
+        assert!(!budgr.has_unsaved_changes());
+        budgr.trash.purchases[0].deleted_at = 0;
+        budgr.purge_old_trash(30);
+        assert!(budgr.trash.is_empty());
+        assert!(budgr.has_unsaved_changes());
+    }
+
+    #[test]
+    fn a_log_with_reconciled_purchases_is_not_deleted() {
+        let (mut budgr, log, bread) = budgr_with_bread();
+        budgr.purchase_mut(log, bread).unwrap().cleared = Cleared::Reconciled;
+
+        assert_eq!(budgr.delete_log(log), Err("bread is reconciled, unlock it to change it".to_string()));
+        assert!(budgr.log(log).is_some());
+        assert!(budgr.trash.is_empty());
     }
 }
@@ -0,0 +1,35 @@
+//! A log file for messages that can't go to stdout while the tui owns the terminal.
+
+use crate::log::get_data_dir;
+use chrono::Local;
+use std::fs::{create_dir_all, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// `budgr.log` in the data folder.
+pub fn get_log_file_path() -> PathBuf {
+    let mut path = get_data_dir();
+    path.push("budgr.log");
+    path
+}
+
+/// Append a timestamped line to the log file.
+///
+/// Failing to log is never worth crashing over, so errors are ignored.
+pub fn write(message: &str) {
+    let path = get_log_file_path();
+    if let Some(dir) = path.parent() {
+        let _ = create_dir_all(dir);
+    }
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(file, "{} {}", Local::now().format("%Y-%m-%d %H:%M:%S"), message);
+    }
+}
+
+// println! but into the log file
+macro_rules! logln {
+    ($($arg:tt)*) => {
+        $crate::logfile::write(&format!($($arg)*))
+    };
+}
+pub(crate) use logln;
@@ -1,35 +1,93 @@
+use crate::events::CrosstermEvents;
 use crate::frontend::UI;
+use crate::keymap::Keymap;
+use crate::theme::Themes;
+mod cli;
+mod events;
 mod frontend;
-mod log;
+mod keymap;
+mod screens;
+mod status;
+mod terminal;
+mod theme;
 mod ui_data;
 
-use log::{Budgr, PurchaseType};
+use budgr::config::Config;
+use budgr::logfile;
 
-use color_eyre::Result;
+use color_eyre::{eyre::eyre, Result};
+use crate::terminal::TerminalGuard;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
 
-use log::read_budgr_from_directory;
+use budgr::log::read_budgr_from_directory;
 
 fn main() -> Result<()> {
     //stdout().execute(EnterAlternateScreen)?;
     //let terminal = Terminal::new(CrosstermBackend::new(std::io::stdout())).unwrap();
 
-    let terminal = ratatui::init();
-    let budgr = read_budgr_from_directory().unwrap();
-    let mut ui = UI::new(budgr, terminal);
-    ui.run();
+    let mut config = Config::load();
+    // a corrupt or unreadable data folder is reported rather than panicking, nothing is overwritten
+    let mut budgr = read_budgr_from_directory().map_err(|err| {
+        logfile::write(&format!("loading failed: {}", err));
+        eyre!("could not load the budgr data: {}", err)
+    })?;
+    budgr.purge_old_trash(config.trash_max_age_days);
+
+    // subcommands print and exit without starting the tui
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    match args.first().map(|a| a.as_str()) {
+        Some("report") => return cli::report(&args[1..], &budgr),
+        Some("reimbursements") => return cli::reimbursements(&args[1..], &budgr),
+        _ => (),
+    }
+
+    let keymap = Keymap::load().map_err(|err| eyre!(err))?;
+    let themes = Themes::load(&config.theme);
+
+    // 0 turns autosave off
+    let autosave = match config.autosave_seconds {
+        0 => None,
+        seconds => Some(Duration::from_secs(seconds)),
+    };
+
+    let (guard, terminal) = TerminalGuard::init()?;
+    let mut ui = UI::new(budgr, terminal, CrosstermEvents, keymap, themes, autosave);
+    // the panic hook has already restored the terminal by the time this returns,
+    // unsaved changes are written before the panic carries on
+    if let Err(panic) = panic::catch_unwind(AssertUnwindSafe(|| ui.run())) {
+        ui.save();
+        eprintln!("budgr crashed, details are in {}", logfile::get_log_file_path().display());
+        panic::resume_unwind(panic);
+    }
+    drop(guard);
+
+    // remember a theme picked while running
+    if let Some(theme) = ui.picked_theme() {
+        config.theme = theme.to_string();
+        config.save()?;
+    }
 
     Ok(())
 }
 
-fn make_test_budgr() -> Budgr {
+// four logs of four purchases each, what the scripted ui tests start from
+#[cfg(test)]
+fn make_test_budgr() -> budgr::log::Budgr {
+    use budgr::log::{today, Budgr, PurchaseType};
+
     let mut budgr = Budgr::new();
     for i in 0..4 {
-        let _ = budgr.new_log(format!("test_log{}", i));
+        let Ok(log) = budgr.new_log(format!("test_log{}", i)) else {
+            continue;
+        };
         for j in 0..4 {
             let _ = budgr.add_purchase(
-                i,
+                log,
                 format!("purchase:{}", (i * j) as i64),
                 (i * j) as i64,
+                PurchaseType::ALL[j % PurchaseType::ALL.len()],
+                today(),
             );
         }
     }
@@ -0,0 +1,341 @@
+//! Payees, and rules that fill in a purchase's payee, category and tags from its name and cost.
+
+use crate::log::{Budgr, LogId, Purchase, PurchaseId, PurchaseType};
+use crate::search::NameMatch;
+use serde::{Deserialize, Serialize};
+
+/// Someone purchases are made from, like a shop.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Payee {
+    /// The name purchases are filed under.
+    pub name: String,
+    /// Other names the payee shows up as, e.g. `tesco stores` for `Tesco`.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+impl Payee {
+    /// True if the purchase name contains the payee's name or one of its aliases, ignoring case.
+    pub fn is_match(&self, purchase_name: &str) -> bool {
+        let purchase_name = purchase_name.to_lowercase();
+        std::iter::once(&self.name)
+            .chain(self.aliases.iter())
+            .any(|name| purchase_name.contains(&name.to_lowercase()))
+    }
+}
+
+/// Fills in a purchase whose name and cost match.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Rule {
+    /// Matched against the purchase name, `/regex/` or a substring, see [`NameMatch::parse`].
+    pub pattern: String,
+    /// Lowest cost the rule applies to.
+    #[serde(default)]
+    pub min_cost: Option<i64>,
+    /// Highest cost the rule applies to.
+    #[serde(default)]
+    pub max_cost: Option<i64>,
+    /// The payee to assign.
+    #[serde(default)]
+    pub payee: Option<String>,
+    /// The category to assign.
+    #[serde(default)]
+    pub category: Option<PurchaseType>,
+    /// Tags to add.
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl Rule {
+    /// Check the pattern is a valid regex or substring.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.pattern.trim().is_empty() {
+            return Err("a rule needs a pattern".to_string());
+        }
+        NameMatch::parse(self.pattern.trim()).map(|_| ())
+    }
+
+    /// True if the purchase's name and cost fit the rule, rules with a broken pattern never match.
+    pub fn matches(&self, purchase: &Purchase) -> bool {
+        let Ok(pattern) = NameMatch::parse(self.pattern.trim()) else {
+            return false;
+        };
+        pattern.is_match(&purchase.name)
+            && self.min_cost.is_none_or(|min| purchase.cost >= min)
+            && self.max_cost.is_none_or(|max| purchase.cost <= max)
+    }
+}
+
+/// The payee registry and the rules, stored as `payees.json` in the data folder.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Payees {
+    /// Every known payee.
+    #[serde(default)]
+    pub payees: Vec<Payee>,
+    /// Tried in order, the first one that matches is used.
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+}
+
+impl Payees {
+    /// The first payee whose name or an alias is in the purchase name.
+    pub fn find(&self, purchase_name: &str) -> Option<&Payee> {
+        self.payees.iter().find(|p| p.is_match(purchase_name))
+    }
+
+    /// Fill in what the first matching rule says, returns whether anything changed.
+    ///
+    /// Nothing set by hand is overwritten: the payee is only filled in when there is none,
+    /// the category only when it is still [`PurchaseType::Other`] and not split, and tags are added to.
+    /// Without a rule naming a payee, a payee whose name or alias matches is used.
+    pub fn apply(&self, purchase: &mut Purchase) -> bool {
+        let rule = self.rules.iter().find(|r| r.matches(purchase));
+        let mut changed = false;
+
+        if purchase.payee.is_none() {
+            purchase.payee = rule
+                .and_then(|r| r.payee.clone())
+                .or_else(|| self.find(&purchase.name).map(|p| p.name.clone()));
+            changed |= purchase.payee.is_some();
+        }
+        let Some(rule) = rule else {
+            return changed;
+        };
+        if let Some(category) = rule.category {
+            if purchase.category == PurchaseType::default() && purchase.splits.is_empty() && category != purchase.category {
+                purchase.category = category;
+                changed = true;
+            }
+        }
+        for tag in rule.tags.iter() {
+            if !purchase.has_tag(tag) {
+                purchase.tags.push(tag.clone());
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+impl Budgr {
+    /// Run the rules over a purchase, see [`Payees::apply`].
+    ///
+    /// Call this after adding a purchase, whether it was typed in or imported.
+    pub fn apply_rules(&mut self, log: LogId, purchase: PurchaseId) -> Result<(), String> {
+        // the rules are borrowed from self too, so they fill in a copy that is written back if it changed
+        let mut p = self.purchase_mut(log, purchase)?.clone();
+        if self.payees.apply(&mut p) {
+            *self.purchase_mut(log, purchase)? = p;
+            self.unsaved = true;
+        }
+        Ok(())
+    }
+
+    /// Replace the rule at `index`, or add it to the end with `None`.
+    ///
+    /// A payee the rule names that isn't in the registry yet is added to it.
+    pub fn save_rule(&mut self, index: Option<usize>, rule: Rule) -> Result<(), String> {
+        rule.validate()?;
+        if let Some(name) = &rule.payee {
+            if !self.payees.payees.iter().any(|p| &p.name == name) {
+                self.payees.payees.push(Payee {
+                    name: name.clone(),
+                    aliases: Vec::new(),
+                });
+            }
+        }
+        match index {
+            Some(i) => *self.payees.rules.get_mut(i).ok_or("ERROR: no such rule")? = rule,
+            None => self.payees.rules.push(rule),
+        }
+        self.unsaved = true;
+        Ok(())
+    }
+
+    /// Delete the rule at `index`.
+    pub fn remove_rule(&mut self, index: usize) -> Result<(), String> {
+        if index >= self.payees.rules.len() {
+            return Err("ERROR: no such rule".to_string());
+        }
+        self.payees.rules.remove(index);
+        self.unsaved = true;
+        Ok(())
+    }
+
+    /// Replace the payee at `index`, or add it with `None`. Payee names have to be unique.
+    pub fn save_payee(&mut self, index: Option<usize>, payee: Payee) -> Result<(), String> {
+        if payee.name.trim().is_empty() {
+            return Err("a payee needs a name".to_string());
+        }
+        let taken = self
+            .payees
+            .payees
+            .iter()
+            .enumerate()
+            .any(|(i, p)| p.name == payee.name && Some(i) != index);
+        if taken {
+            return Err(format!("there is already a payee called {}", payee.name));
+        }
+        match index {
+            Some(i) => *self.payees.payees.get_mut(i).ok_or("ERROR: no such payee")? = payee,
+            None => self.payees.payees.push(payee),
+        }
+        self.unsaved = true;
+        Ok(())
+    }
+
+    /// Delete the payee at `index`, purchases and rules naming it keep the name.
+    pub fn remove_payee(&mut self, index: usize) -> Result<(), String> {
+        if index >= self.payees.payees.len() {
+            return Err("ERROR: no such payee".to_string());
+        }
+        self.payees.payees.remove(index);
+        self.unsaved = true;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::{today, Log};
+
+    fn purchase(name: &str, cost: i64) -> Purchase {
+        let mut log = Log::default();
+        log.add_purchase(name.to_string(), cost, PurchaseType::Other, today());
+        log.purchases.remove(0)
+    }
+
+    fn rule(pattern: &str, payee: &str, category: PurchaseType) -> Rule {
+        Rule {
+            pattern: pattern.to_string(),
+            payee: Some(payee.to_string()),
+            category: Some(category),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rules_match_a_substring_or_a_regex() {
+        let substring = rule("Tesco", "Tesco", PurchaseType::Groceries);
+        assert!(substring.matches(&purchase("TESCO stores 2041", 10)));
+        assert!(!substring.matches(&purchase("tsco", 10)));
+
+        let regex = rule("/^uber (eats|trip)$/", "Uber", PurchaseType::Leisure);
+        assert!(regex.matches(&purchase("uber eats", 10)));
+        assert!(!regex.matches(&purchase("uber eats london", 10)));
+        // the slashes make it a regex, so a dot matches anything
+        assert!(rule("/a.c/", "x", PurchaseType::Other).matches(&purchase("abc", 1)));
+        assert!(!rule("a.c", "x", PurchaseType::Other).matches(&purchase("abc", 1)));
+    }
+
+    #[test]
+    fn cost_bounds_are_inclusive() {
+        let bounded = Rule {
+            min_cost: Some(10),
+            max_cost: Some(20),
+            ..rule("shop", "Shop", PurchaseType::Groceries)
+        };
+        let fits = |cost| bounded.matches(&purchase("shop", cost));
+        assert_eq!([fits(9), fits(10), fits(20), fits(21)], [false, true, true, false]);
+    }
+
+    #[test]
+    fn broken_or_empty_patterns_are_refused() {
+        assert!(rule("/(unclosed/", "x", PurchaseType::Other).validate().is_err());
+        assert!(rule("  ", "x", PurchaseType::Other).validate().is_err());
+        assert!(!rule("/(unclosed/", "x", PurchaseType::Other).matches(&purchase("(unclosed", 1)));
+
+        let mut budgr = Budgr::new();
+        assert!(budgr.save_rule(None, rule("/(/", "x", PurchaseType::Other)).is_err());
+        assert!(budgr.payees.rules.is_empty() && budgr.payees.payees.is_empty());
+    }
+
+    #[test]
+    fn rules_only_fill_in_what_was_left_empty() {
+        let payees = Payees {
+            payees: Vec::new(),
+            rules: vec![Rule {
+                tags: vec!["food".to_string()],
+                ..rule("tesco", "Tesco", PurchaseType::Groceries)
+            }],
+        };
+
+        let mut blank = purchase("tesco", 10);
+        assert!(payees.apply(&mut blank));
+        assert_eq!(blank.payee.as_deref(), Some("Tesco"));
+        assert_eq!(blank.category, PurchaseType::Groceries);
+        assert_eq!(blank.tags, ["food"]);
+        // running it again changes nothing
+        assert!(!payees.apply(&mut blank));
+
+        let mut by_hand = purchase("tesco", 10);
+        by_hand.payee = Some("Corner shop".to_string());
+        by_hand.category = PurchaseType::Bill;
+        by_hand.tags = vec!["mine".to_string()];
+        assert!(payees.apply(&mut by_hand));
+        assert_eq!(by_hand.payee.as_deref(), Some("Corner shop"));
+        assert_eq!(by_hand.category, PurchaseType::Bill);
+        assert_eq!(by_hand.tags, ["mine", "food"]);
+    }
+
+    #[test]
+    fn the_first_matching_rule_wins() {
+        let payees = Payees {
+            payees: vec![Payee {
+                name: "Amazon".to_string(),
+                aliases: vec!["amzn".to_string()],
+            }],
+            rules: vec![
+                Rule {
+                    max_cost: Some(5),
+                    ..rule("amzn", "Prime", PurchaseType::Leisure)
+                },
+                rule("amzn", "Amazon", PurchaseType::School),
+                rule("amzn mktp", "Marketplace", PurchaseType::Bill),
+            ],
+        };
+
+        let mut small = purchase("amzn mktp", 5);
+        payees.apply(&mut small);
+        assert_eq!((small.payee.as_deref(), small.category), (Some("Prime"), PurchaseType::Leisure));
+        let mut large = purchase("amzn mktp", 50);
+        payees.apply(&mut large);
+        assert_eq!((large.payee.as_deref(), large.category), (Some("Amazon"), PurchaseType::School));
+
+        // with no rule the payee registry still names it
+        let mut unruled = purchase("AMZN digital", 1);
+        let payees = Payees {
+            rules: Vec::new(),
+            ..payees
+        };
+        assert!(payees.apply(&mut unruled));
+        assert_eq!((unruled.payee.as_deref(), unruled.category), (Some("Amazon"), PurchaseType::Other));
+    }
+
+    #[test]
+    fn payee_names_are_unique() {
+        let payee = |name: &str| Payee {
+            name: name.to_string(),
+            aliases: Vec::new(),
+        };
+        let mut budgr = Budgr::new();
+        budgr.save_payee(None, payee("Tesco")).unwrap();
+        budgr.save_payee(None, payee("Aldi")).unwrap();
+
+        assert_eq!(
+            budgr.save_payee(None, payee("Tesco")),
+            Err("there is already a payee called Tesco".to_string())
+        );
+        assert!(budgr.save_payee(Some(1), payee("Tesco")).is_err());
+        assert!(budgr.save_payee(Some(1), payee(" ")).is_err());
+        // saving a payee over itself is fine
+        budgr.save_payee(Some(0), payee("Tesco")).unwrap();
+        assert_eq!(budgr.payees.payees, [payee("Tesco"), payee("Aldi")]);
+
+        // a rule naming a new payee adds it once
+        budgr.save_rule(None, rule("lidl", "Lidl", PurchaseType::Groceries)).unwrap();
+        budgr.save_rule(None, rule("lidl gmbh", "Lidl", PurchaseType::Groceries)).unwrap();
+        assert_eq!(budgr.payees.payees, [payee("Tesco"), payee("Aldi"), payee("Lidl")]);
+    }
+}
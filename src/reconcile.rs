@@ -0,0 +1,308 @@
+//! Checking purchases against a bank statement.
+//!
+//! Start with [`Budgr::start_reconciliation`], tick purchases that are on the statement with
+//! [`Budgr::toggle_cleared`] until [`Budgr::reconciliation_difference`] is 0, then
+//! [`Budgr::finish_reconciliation`] locks them. Transfers count as cleared once their date is
+//! on or before the statement date.
+
+use crate::account::AccountId;
+use crate::log::{Budgr, LogId, Purchase, PurchaseId};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Whether a purchase has been checked against a statement.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Cleared {
+    /// Not seen on a statement yet.
+    #[default]
+    Uncleared,
+    /// Ticked off against the statement being reconciled.
+    Cleared,
+    /// Part of a finished reconciliation, locked against edits until unlocked.
+    Reconciled,
+}
+
+impl Cleared {
+    /// True for purchases not seen on a statement yet.
+    pub fn is_uncleared(&self) -> bool {
+        *self == Cleared::Uncleared
+    }
+}
+
+impl fmt::Display for Cleared {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Cleared::Uncleared => write!(f, "uncleared"),
+            Cleared::Cleared => write!(f, "cleared"),
+            Cleared::Reconciled => write!(f, "reconciled"),
+        }
+    }
+}
+
+/// The closing date and balance printed on a bank statement.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Statement {
+    /// The last day the statement covers.
+    pub date: NaiveDate,
+    /// The account's balance at the end of that day.
+    pub balance: i64,
+}
+
+// reconciled purchases can't be changed until they are unlocked
+pub(crate) fn check_unlocked(purchase: &Purchase) -> Result<(), String> {
+    match purchase.cleared {
+        Cleared::Reconciled => Err(format!("{} is reconciled, unlock it to change it", purchase.name)),
+        _ => Ok(()),
+    }
+}
+
+impl Budgr {
+    /// Begin reconciling an account against a statement, replacing one already in progress.
+    pub fn start_reconciliation(&mut self, account: AccountId, statement: Statement) -> Result<(), String> {
+        let account = self
+            .accounts
+            .accounts
+            .iter_mut()
+            .find(|a| a.id == account)
+            .ok_or(format!("ERROR: no account with id {}", account))?;
+        account.statement = Some(statement);
+        self.unsaved = true;
+        Ok(())
+    }
+
+    /// Stop reconciling an account, purchases ticked so far stay cleared.
+    pub fn cancel_reconciliation(&mut self, account: AccountId) -> Result<(), String> {
+        let account = self
+            .accounts
+            .accounts
+            .iter_mut()
+            .find(|a| a.id == account)
+            .ok_or(format!("ERROR: no account with id {}", account))?;
+        account.statement = None;
+        self.unsaved = true;
+        Ok(())
+    }
+
+    /// Tick a purchase off as on the statement, or untick it, returns its new status.
+    ///
+    /// Reconciled purchases are locked and can't be unticked, see [`Budgr::unlock_purchase`].
+    pub fn toggle_cleared(&mut self, log: LogId, purchase: PurchaseId) -> Result<Cleared, String> {
+        let p = self.purchase_mut(log, purchase)?;
+        check_unlocked(p)?;
+        p.cleared = match p.cleared {
+            Cleared::Uncleared => Cleared::Cleared,
+            _ => Cleared::Uncleared,
+        };
+        let cleared = p.cleared;
+        self.unsaved = true;
+        Ok(cleared)
+    }
+
+    /// Take a reconciled purchase back to cleared so it can be changed again.
+    pub fn unlock_purchase(&mut self, log: LogId, purchase: PurchaseId) -> Result<(), String> {
+        let p = self.purchase_mut(log, purchase)?;
+        if p.cleared == Cleared::Reconciled {
+            p.cleared = Cleared::Cleared;
+            self.unsaved = true;
+        }
+        Ok(())
+    }
+
+    /// The account's balance counting only cleared and reconciled purchases, and transfers up to `date`.
+    pub fn cleared_balance(&self, account: AccountId, date: NaiveDate) -> i64 {
+        let Some(opening) = self.accounts.get(account).map(|a| a.opening_balance) else {
+            return 0;
+        };
+        let purchases = self
+            .logs
+            .iter()
+            .flat_map(|log| log.purchases.iter())
+            .filter(|p| p.account == Some(account) && !p.cleared.is_uncleared())
+            .map(|p| p.cost)
+            .sum::<i64>();
+        let transfers = self
+            .accounts
+            .transfers
+            .iter()
+            .filter(|t| t.date <= date)
+            .map(|t| match (t.from == account, t.to == account) {
+                (true, _) => -t.amount,
+                (_, true) => t.amount,
+                _ => 0,
+            })
+            .sum::<i64>();
+        opening - purchases + transfers
+    }
+
+    /// How far the cleared balance is from the statement being reconciled, `None` if there isn't one.
+    ///
+    /// Positive when the statement shows more money than what has been ticked off so far.
+    pub fn reconciliation_difference(&self, account: AccountId) -> Option<i64> {
+        let statement = self.accounts.get(account)?.statement?;
+        Some(statement.balance - self.cleared_balance(account, statement.date))
+    }
+
+    /// Lock every cleared purchase on the account once the cleared balance matches the statement,
+    /// returns how many were locked.
+    pub fn finish_reconciliation(&mut self, account: AccountId) -> Result<usize, String> {
+        let name = self.accounts.name(account);
+        match self.reconciliation_difference(account) {
+            None => return Err(format!("{} isn't being reconciled", name)),
+            Some(0) => (),
+            Some(difference) => return Err(format!("{} is still {} off the statement", name, difference)),
+        }
+
+        let mut locked = 0;
+        for p in self
+            .logs
+            .iter_mut()
+            .flat_map(|log| log.purchases.iter_mut())
+            .filter(|p| p.account == Some(account) && p.cleared == Cleared::Cleared)
+        {
+            p.cleared = Cleared::Reconciled;
+            locked += 1;
+        }
+        if let Some(account) = self.accounts.accounts.iter_mut().find(|a| a.id == account) {
+            account.reconciled = account.statement.take();
+        }
+        self.unsaved = true;
+        Ok(locked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::{Account, Transfer};
+    use crate::log::PurchaseType;
+    use crate::testing::day;
+
+    // checking opens with 500 and pays for tesco 40, cinema 12 and rent 300 from the food log
+    fn budgr() -> (Budgr, LogId, AccountId, Vec<PurchaseId>) {
+        let mut budgr = Budgr::new();
+        let food = budgr.new_log("food".to_string()).unwrap();
+        let checking = Account {
+            name: "checking".to_string(),
+            opening_balance: 500,
+            ..Default::default()
+        };
+        let account = checking.id;
+        budgr.save_account(checking).unwrap();
+        let mut ids = Vec::new();
+        for (name, cost, date) in [("tesco", 40, "2025-03-03"), ("cinema", 12, "2025-03-10"), ("rent", 300, "2025-03-28")] {
+            let id = budgr.add_purchase(food, name.to_string(), cost, PurchaseType::Other, day(date)).unwrap();
+            budgr.assign_account(food, id, Some(account)).unwrap();
+            ids.push(id);
+        }
+        (budgr, food, account, ids)
+    }
+
+    fn cleared(budgr: &Budgr) -> Vec<Cleared> {
+        budgr.logs[0].purchases.iter().map(|p| p.cleared).collect()
+    }
+
+    #[test]
+    fn cleared_balance_counts_ticked_purchases_and_transfers_up_to_the_statement() {
+        let (mut budgr, food, checking, ids) = budgr();
+        assert_eq!(budgr.cleared_balance(checking, day("2025-03-31")), 500);
+
+        budgr.toggle_cleared(food, ids[0]).unwrap();
+        budgr.toggle_cleared(food, ids[2]).unwrap();
+        assert_eq!(budgr.cleared_balance(checking, day("2025-03-31")), 160);
+
+        let savings = Account {
+            name: "savings".to_string(),
+            ..Default::default()
+        };
+        let savings_id = savings.id;
+        budgr.save_account(savings).unwrap();
+        budgr
+            .transfer(Transfer {
+                from: checking,
+                to: savings_id,
+                amount: 100,
+                date: day("2025-04-02"),
+                note: String::new(),
+            })
+            .unwrap();
+        assert_eq!(budgr.cleared_balance(checking, day("2025-03-31")), 160);
+        assert_eq!(budgr.cleared_balance(checking, day("2025-04-02")), 60);
+        assert_eq!(budgr.cleared_balance(savings_id, day("2025-04-02")), 100);
+
+        assert_eq!(budgr.reconciliation_difference(checking), None);
+        budgr
+            .start_reconciliation(
+                checking,
+                Statement {
+                    date: day("2025-03-31"),
+                    balance: 148,
+                },
+            )
+            .unwrap();
+        // the statement has the cinema on it too
+        assert_eq!(budgr.reconciliation_difference(checking), Some(-12));
+        budgr.toggle_cleared(food, ids[1]).unwrap();
+        assert_eq!(budgr.reconciliation_difference(checking), Some(0));
+    }
+
+    #[test]
+    fn finishing_locks_only_cleared_purchases() {
+        let (mut budgr, food, checking, ids) = budgr();
+        let statement = Statement {
+            date: day("2025-03-31"),
+            balance: 460,
+        };
+        assert_eq!(budgr.finish_reconciliation(checking), Err("checking isn't being reconciled".to_string()));
+        budgr.start_reconciliation(checking, statement).unwrap();
+        assert_eq!(
+            budgr.finish_reconciliation(checking),
+            Err("checking is still -40 off the statement".to_string())
+        );
+
+        budgr.toggle_cleared(food, ids[0]).unwrap();
+        // a cleared purchase on no account is left alone
+        let cash = budgr.add_purchase(food, "market".to_string(), 5, PurchaseType::Other, day("2025-03-05")).unwrap();
+        budgr.toggle_cleared(food, cash).unwrap();
+
+        assert_eq!(budgr.finish_reconciliation(checking), Ok(1));
+        assert_eq!(
+            cleared(&budgr),
+            [Cleared::Reconciled, Cleared::Uncleared, Cleared::Uncleared, Cleared::Cleared]
+        );
+        let account = budgr.accounts.get(checking).unwrap();
+        assert_eq!((account.statement, account.reconciled), (None, Some(statement)));
+
+        // locked purchases can't be edited, unticked or removed
+        assert!(budgr.toggle_cleared(food, ids[0]).is_err());
+        assert!(budgr.annotate_purchase(food, ids[0], Vec::new(), "note".to_string()).is_err());
+        assert!(budgr.remove_purchase(food, ids[0]).is_err());
+        assert_eq!(budgr.logs[0].purchases.len(), 4);
+    }
+
+    #[test]
+    fn unlocking_takes_a_purchase_back_to_cleared() {
+        let (mut budgr, food, checking, ids) = budgr();
+        budgr.toggle_cleared(food, ids[1]).unwrap();
+        budgr
+            .start_reconciliation(
+                checking,
+                Statement {
+                    date: day("2025-03-31"),
+                    balance: 488,
+                },
+            )
+            .unwrap();
+        budgr.finish_reconciliation(checking).unwrap();
+
+        budgr.unlock_purchase(food, ids[1]).unwrap();
+        assert_eq!(cleared(&budgr)[1], Cleared::Cleared);
+        budgr.annotate_purchase(food, ids[1], vec!["film".to_string()], String::new()).unwrap();
+        assert_eq!(budgr.toggle_cleared(food, ids[1]), Ok(Cleared::Uncleared));
+
+        // unlocking something that isn't locked changes nothing
+        budgr.unlock_purchase(food, ids[0]).unwrap();
+        assert_eq!(cleared(&budgr)[0], Cleared::Uncleared);
+        assert!(budgr.unlock_purchase(food, PurchaseId::new()).is_err());
+    }
+}
@@ -0,0 +1,436 @@
+//! Spending grouped into weeks, months or years, and money still owed back.
+
+use crate::log::{today, Budgr, Log, Purchase, PurchaseType};
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+/// How long each period of a report is.
+pub enum Period {
+    /// Monday to sunday.
+    Week,
+    /// A calendar month.
+    Month,
+    /// A calendar year.
+    Year,
+}
+
+impl Period {
+    /// First day of the period the date falls in, weeks start on monday.
+    pub fn start_of(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            Period::Month => date.with_day(1).unwrap(),
+            Period::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap(),
+        }
+    }
+
+    /// First day of the following period.
+    pub fn next_start(&self, start: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Week => start + Duration::weeks(1),
+            Period::Month => start + Months::new(1),
+            Period::Year => start + Months::new(12),
+        }
+    }
+
+    /// A short name for the period starting at `start`, e.g. 2026-W42, 2026-10, 2026.
+    pub fn label(&self, start: NaiveDate) -> String {
+        match self {
+            Period::Week => {
+                let week = start.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            Period::Month => start.format("%Y-%m").to_string(),
+            Period::Year => start.format("%Y").to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Period::Week => write!(f, "week"),
+            Period::Month => write!(f, "month"),
+            Period::Year => write!(f, "year"),
+        }
+    }
+}
+
+impl FromStr for Period {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "week" | "weekly" | "w" => Ok(Period::Week),
+            "month" | "monthly" | "m" => Ok(Period::Month),
+            "year" | "yearly" | "y" => Ok(Period::Year),
+            _ => Err(format!("unknown period: {}", s)),
+        }
+    }
+}
+
+/// Everything spent in one period.
+#[derive(Serialize, Clone, Debug)]
+pub struct PeriodSummary {
+    /// See [`Period::label`].
+    pub label: String,
+    /// First day of the period.
+    pub start: NaiveDate,
+    /// Last day of the period, inclusive.
+    pub end: NaiveDate,
+    /// Total spent.
+    pub total: i64,
+    /// Total spent per category.
+    pub by_category: BTreeMap<PurchaseType, i64>,
+    /// Spending per day, the period that is still running is averaged over the days so far.
+    pub average_daily: f64,
+    /// Total of the period before, `None` for the first period of the report.
+    pub previous_total: Option<i64>,
+    /// `total - previous_total`.
+    pub change: Option<i64>,
+}
+
+/// Spending over consecutive periods.
+#[derive(Serialize, Clone, Debug)]
+pub struct Report {
+    /// What the report covers, a log name or "all logs".
+    pub scope: String,
+    /// The length of each period.
+    pub period: Period,
+    /// Oldest first, periods without spending are included so comparisons line up.
+    pub summaries: Vec<PeriodSummary>,
+    /// Spending on purchases without a date, which can't be put in a period.
+    pub undated_total: i64,
+}
+
+impl Report {
+    /// Group purchases into consecutive periods from the first purchase to the last.
+    pub fn build<'a>(
+        scope: String,
+        period: Period,
+        purchases: impl Iterator<Item = &'a Purchase>,
+    ) -> Report {
+        let mut undated_total = 0;
+        let mut by_period: BTreeMap<NaiveDate, Vec<&Purchase>> = BTreeMap::new();
+        for p in purchases {
+            match p.date {
+                Some(date) => by_period.entry(period.start_of(date)).or_default().push(p),
+                None => undated_total += p.cost,
+            }
+        }
+
+        let mut summaries: Vec<PeriodSummary> = Vec::new();
+        if let (Some(first), Some(last)) = (
+            by_period.keys().next().copied(),
+            by_period.keys().last().copied(),
+        ) {
+            let mut start = first;
+            while start <= last {
+                let next = period.next_start(start);
+                let end = next - Duration::days(1);
+
+                let purchases = by_period.get(&start).map(|v| v.as_slice()).unwrap_or(&[]);
+                let mut by_category = BTreeMap::new();
+                for (category, amount) in purchases.iter().flat_map(|p| p.category_amounts()) {
+                    *by_category.entry(category).or_insert(0) += amount;
+                }
+                let total: i64 = by_category.values().sum();
+
+                let days_so_far = (end.min(today()) - start).num_days() + 1;
+                let average_daily = total as f64 / days_so_far.max(1) as f64;
+
+                let previous_total = summaries.last().map(|s| s.total);
+                summaries.push(PeriodSummary {
+                    label: period.label(start),
+                    start,
+                    end,
+                    total,
+                    by_category,
+                    average_daily,
+                    previous_total,
+                    change: previous_total.map(|prev| total - prev),
+                });
+                start = next;
+            }
+        }
+
+        Report {
+            scope,
+            period,
+            summaries,
+            undated_total,
+        }
+    }
+
+    /// The report as pretty printed json.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// Plain text version for the command line.
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} report for {}", self.period, self.scope)?;
+        for s in self.summaries.iter() {
+            let change = match s.change {
+                Some(change) => format!("{:+}", change),
+                None => "-".to_string(),
+            };
+            writeln!(
+                f,
+                "{:<10} total: {:>10}  vs previous: {:>10}  daily average: {:>10.2}",
+                s.label, s.total, change, s.average_daily
+            )?;
+            for (category, total) in s.by_category.iter() {
+                writeln!(f, "    {:<12} {:>10}", category.to_string(), total)?;
+            }
+        }
+        if self.undated_total != 0 {
+            writeln!(f, "undated purchases: {}", self.undated_total)?;
+        }
+        Ok(())
+    }
+}
+
+/// Tagging a reimbursable purchase with this marks it as paid back.
+pub const REIMBURSED_TAG: &str = "reimbursed";
+
+/// True for tags that mark a purchase as owed back, `reimbursable` or anything ending in `-reimbursable`.
+pub fn is_reimbursable_tag(tag: &str) -> bool {
+    tag == "reimbursable" || tag.ends_with("-reimbursable")
+}
+
+/// A purchase that is waiting to be paid back.
+#[derive(Serialize, Clone, Debug)]
+pub struct Reimbursement {
+    /// Name of the log the purchase is in.
+    pub log: String,
+    /// Name of the purchase.
+    pub name: String,
+    /// When it was bought.
+    pub date: Option<NaiveDate>,
+    /// How much is owed.
+    pub cost: i64,
+    /// The reimbursable tag, e.g. `work-reimbursable`.
+    pub tag: String,
+}
+
+/// Every purchase with a reimbursable tag that hasn't been tagged [`REIMBURSED_TAG`] yet.
+#[derive(Serialize, Clone, Debug)]
+pub struct Reimbursements {
+    /// Oldest first, undated purchases last.
+    pub outstanding: Vec<Reimbursement>,
+    /// How much is owed per reimbursable tag.
+    pub by_tag: BTreeMap<String, i64>,
+    /// How much is owed altogether.
+    pub total: i64,
+}
+
+impl Reimbursements {
+    /// Collect the outstanding reimbursements of some logs.
+    pub fn build<'a>(logs: impl Iterator<Item = &'a Log>) -> Reimbursements {
+        let mut outstanding = Vec::new();
+        for log in logs {
+            for p in log.purchases.iter().filter(|p| !p.has_tag(REIMBURSED_TAG)) {
+                // a purchase is only owed once even with several reimbursable tags
+                let Some(tag) = p.tags.iter().find(|t| is_reimbursable_tag(t)) else {
+                    continue;
+                };
+                outstanding.push(Reimbursement {
+                    log: log.name.clone(),
+                    name: p.name.clone(),
+                    date: p.date,
+                    cost: p.cost,
+                    tag: tag.clone(),
+                });
+            }
+        }
+        outstanding.sort_by_key(|r| (r.date.is_none(), r.date));
+
+        let mut by_tag = BTreeMap::new();
+        for r in outstanding.iter() {
+            *by_tag.entry(r.tag.clone()).or_insert(0) += r.cost;
+        }
+        Reimbursements {
+            total: outstanding.iter().map(|r| r.cost).sum(),
+            outstanding,
+            by_tag,
+        }
+    }
+
+    /// The reimbursements as pretty printed json.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+/// Plain text version for the command line.
+impl fmt::Display for Reimbursements {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "outstanding reimbursements: {}", self.total)?;
+        for r in self.outstanding.iter() {
+            let date = r.date.map(|d| d.to_string()).unwrap_or("-".to_string());
+            writeln!(
+                f,
+                "{:<10} {:<16} {:<24} {:>10}  {}",
+                date, r.log, r.name, r.cost, r.tag
+            )?;
+        }
+        for (tag, total) in self.by_tag.iter() {
+            writeln!(f, "    {:<24} {:>10}", tag, total)?;
+        }
+        Ok(())
+    }
+}
+
+impl Log {
+    /// A report over this log's purchases.
+    pub fn report(&self, period: Period) -> Report {
+        Report::build(self.name.clone(), period, self.purchases.iter())
+    }
+}
+
+impl Budgr {
+    /// A report over the purchases of every log.
+    pub fn report(&self, period: Period) -> Report {
+        Report::build(
+            "all logs".to_string(),
+            period,
+            self.logs.iter().flat_map(|log| log.purchases.iter()),
+        )
+    }
+
+    /// Purchases across every log that are still to be paid back.
+    pub fn reimbursements(&self) -> Reimbursements {
+        Reimbursements::build(self.logs.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::day;
+
+    fn log(purchases: &[(&str, i64, PurchaseType, &str)]) -> Log {
+        let mut log = Log {
+            name: "food".to_string(),
+            ..Default::default()
+        };
+        for (name, cost, category, date) in purchases {
+            log.add_purchase(name.to_string(), *cost, *category, day(date));
+        }
+        log
+    }
+
+    fn totals(report: &Report) -> Vec<(&str, i64)> {
+        report.summaries.iter().map(|s| (s.label.as_str(), s.total)).collect()
+    }
+
+    #[test]
+    fn periods_without_spending_are_filled_in() {
+        let log = log(&[
+            ("tesco", 40, PurchaseType::Groceries, "2025-01-15"),
+            ("cinema", 12, PurchaseType::Leisure, "2025-04-02"),
+            ("aldi", 8, PurchaseType::Groceries, "2025-01-31"),
+        ]);
+        let report = log.report(Period::Month);
+        assert_eq!(totals(&report), [("2025-01", 48), ("2025-02", 0), ("2025-03", 0), ("2025-04", 12)]);
+        assert_eq!(report.summaries[1].start, day("2025-02-01"));
+        assert_eq!(report.summaries[1].end, day("2025-02-28"));
+        assert!(report.summaries[1].by_category.is_empty());
+        assert_eq!(report.summaries[0].by_category[&PurchaseType::Groceries], 48);
+    }
+
+    #[test]
+    fn each_period_is_compared_with_the_one_before() {
+        let log = log(&[
+            ("tesco", 40, PurchaseType::Groceries, "2025-03-03"),
+            ("aldi", 25, PurchaseType::Groceries, "2025-03-12"),
+            ("market", 30, PurchaseType::Groceries, "2025-03-21"),
+        ]);
+        let report = log.report(Period::Week);
+        let changes = report
+            .summaries
+            .iter()
+            .map(|s| (s.previous_total, s.change))
+            .collect::<Vec<_>>();
+        assert_eq!(changes, [(None, None), (Some(40), Some(-15)), (Some(25), Some(5))]);
+    }
+
+    #[test]
+    fn daily_average_is_over_the_whole_period() {
+        // sunday and monday land in different weeks, january and february in different months
+        let log = log(&[
+            ("sunday", 70, PurchaseType::Groceries, "2025-03-02"),
+            ("monday", 14, PurchaseType::Groceries, "2025-03-03"),
+            ("january", 31, PurchaseType::Groceries, "2025-01-31"),
+            ("february", 56, PurchaseType::Groceries, "2025-02-01"),
+        ]);
+        let weeks = log.report(Period::Week);
+        let last_two = &weeks.summaries[weeks.summaries.len() - 2..];
+        assert_eq!((last_two[0].start, last_two[0].average_daily), (day("2025-02-24"), 10.0));
+        assert_eq!((last_two[1].start, last_two[1].average_daily), (day("2025-03-03"), 2.0));
+
+        let months = log.report(Period::Month);
+        let averages = months.summaries.iter().map(|s| s.average_daily).collect::<Vec<f64>>();
+        assert_eq!(averages, [1.0, 2.0, 84.0 / 31.0]);
+    }
+
+    #[test]
+    fn the_running_period_is_averaged_over_the_days_so_far() {
+        let mut log = log(&[]);
+        log.add_purchase("today".to_string(), 100, PurchaseType::Groceries, today());
+        let report = log.report(Period::Year);
+        let days_so_far = today().ordinal() as f64;
+        assert_eq!(report.summaries[0].average_daily, 100.0 / days_so_far);
+    }
+
+    #[test]
+    fn periods_roll_over_the_end_of_the_year() {
+        assert_eq!(Period::Week.next_start(day("2024-12-30")), day("2025-01-06"));
+        assert_eq!(Period::Month.next_start(day("2024-12-01")), day("2025-01-01"));
+        assert_eq!(Period::Year.next_start(day("2024-01-01")), day("2025-01-01"));
+        assert_eq!(Period::Week.start_of(day("2025-01-01")), day("2024-12-30"));
+        assert_eq!(Period::Week.label(day("2024-12-30")), "2025-W01");
+
+        let log = log(&[
+            ("december", 10, PurchaseType::Groceries, "2024-12-31"),
+            ("january", 20, PurchaseType::Groceries, "2025-01-01"),
+        ]);
+        assert_eq!(totals(&log.report(Period::Month)), [("2024-12", 10), ("2025-01", 20)]);
+        assert_eq!(totals(&log.report(Period::Week)), [("2025-W01", 30)]);
+    }
+
+    #[test]
+    fn undated_purchases_are_kept_out_of_the_periods() {
+        let mut log = log(&[("tesco", 40, PurchaseType::Groceries, "2025-03-03")]);
+        log.add_purchase("mystery".to_string(), 7, PurchaseType::Other, today());
+        log.purchases[1].date = None;
+        let report = log.report(Period::Month);
+        assert_eq!(totals(&report), [("2025-03", 40)]);
+        assert_eq!(report.undated_total, 7);
+    }
+
+    #[test]
+    fn reimbursed_purchases_are_no_longer_owed() {
+        let mut log = log(&[
+            ("train", 30, PurchaseType::Bill, "2025-03-10"),
+            ("hotel", 90, PurchaseType::Other, "2025-03-03"),
+            ("lunch", 12, PurchaseType::Groceries, "2025-03-04"),
+        ]);
+        log.purchases[0].tags = vec!["work-reimbursable".to_string()];
+        log.purchases[1].tags = vec!["work-reimbursable".to_string(), "reimbursable".to_string()];
+        log.purchases[2].tags = vec!["reimbursable".to_string(), REIMBURSED_TAG.to_string()];
+
+        let owed = Reimbursements::build(std::iter::once(&log));
+        let names = owed.outstanding.iter().map(|r| r.name.as_str()).collect::<Vec<&str>>();
+        assert_eq!(names, ["hotel", "train"]);
+        assert_eq!(owed.total, 120);
+        assert_eq!(owed.by_tag.into_iter().collect::<Vec<_>>(), [("work-reimbursable".to_string(), 120)]);
+    }
+}
@@ -0,0 +1,3246 @@
+use budgr::account::{Account, AccountId, AccountKind, Transfer};
+use budgr::debt::{Debt, DebtKind, Schedule, Strategy};
+use budgr::log::{check_splits, parse_date, parse_splits, parse_tags, today, Budgr, Log, LogId, Purchase, PurchaseId, PurchaseType, Split};
+use budgr::goal::{Goal, GoalLink, GoalProgress};
+use budgr::payee::{Payee, Rule};
+use budgr::reconcile::{Cleared, Statement};
+use budgr::report::{Period, REIMBURSED_TAG};
+use budgr::search::PurchaseQuery;
+use crate::status::Status;
+use crate::theme::Theme;
+use budgr::trash::format_age;
+use crate::ui_data::{InputData, UIState, UITransition, UserInput, ViewSorts};
+
+use chrono::{Months, NaiveDate};
+use std::path::PathBuf;
+use ratatui::style::Stylize;
+use ratatui::{
+    layout::{Constraint, Constraint::Ratio, Layout, Margin, Position, Rect},
+    style::Modifier,
+    symbols,
+    text::{Line, Span, Text},
+    widgets::{
+        Axis, Bar, BarChart, BarGroup, Block, Cell, Chart, Dataset, GraphType, Paragraph,
+        Row, Table, TableState,
+    },
+    Frame,
+};
+
+// every screen is split in two: update handles input and changes the budgr, view only draws.
+// update gets the area the screen is drawn in so clicks can be matched to what view draws there
+impl UIState {
+    pub fn update(
+        &mut self,
+        input: &UserInput,
+        area: Rect,
+        budgr: &mut Budgr,
+        sorts: &mut ViewSorts,
+        status: &mut Status,
+    ) -> Option<UITransition> {
+        match self {
+            UIState::BudgrShow { state } => budgr_update(state, input, area, budgr, sorts, status),
+            UIState::LogShow { log, state } => log_update(*log, state, input, area, budgr, sorts, status),
+            UIState::PurchaseInput { input_data, selection_index, log } => purchase_input_update(input_data, selection_index, *log, input, area, budgr, status),
+            UIState::TrashShow { state } => trash_update(state, input, area, budgr, status),
+            UIState::Search { input_data, selection_index, state } => search_update(input_data, selection_index, state, input, area, budgr),
+            UIState::Stats { .. } => stats_update(input),
+            UIState::Report { log, period, state } => report_update(*log, period, state, input, area, budgr),
+            UIState::Reimbursements { state } => reimbursements_update(state, input, area, budgr),
+            UIState::Rules { state } => rules_update(state, input, area, budgr, status),
+            UIState::Payees { state } => payees_update(state, input, area, budgr, status),
+            UIState::RuleInput { input_data, selection_index, rule } => rule_input_update(input_data, selection_index, *rule, input, area, budgr, status),
+            UIState::PayeeInput { input_data, selection_index, payee } => payee_input_update(input_data, selection_index, *payee, input, area, budgr, status),
+            UIState::AttachInput { input_data, selection_index, log, purchase } => attach_input_update(input_data, selection_index, *log, *purchase, input, area, budgr, status),
+            UIState::Accounts { state } => accounts_update(state, input, area, budgr, status),
+            UIState::AccountInput { input_data, selection_index, account } => account_input_update(input_data, selection_index, *account, input, area, budgr, status),
+            UIState::TransferInput { input_data, selection_index } => transfer_input_update(input_data, selection_index, input, area, budgr, status),
+            UIState::ReconcileInput { input_data, selection_index, .. } => reconcile_input_update(input_data, selection_index, input, area, budgr, status),
+            UIState::Envelopes { month, state } => envelopes_update(month, state, input, area),
+            UIState::EnvelopeInput { input_data, selection_index, month, category } => envelope_input_update(input_data, selection_index, *month, *category, input, area, budgr, status),
+            UIState::Goals { state } => goals_update(state, input, area, budgr, status),
+            UIState::Debts { state } => debts_update(state, input, area, budgr, status),
+            UIState::DebtInput { input_data, selection_index, debt } => debt_input_update(input_data, selection_index, *debt, input, area, budgr, status),
+            UIState::DebtPaymentInput { input_data, selection_index, debt } => debt_payment_input_update(input_data, selection_index, *debt, input, area, budgr, status),
+            UIState::GoalInput { input_data, selection_index, goal } => goal_input_update(input_data, selection_index, *goal, input, area, budgr, status),
+        }
+    }
+
+    pub fn view(&self, frame: &mut Frame, area: Rect, budgr: &Budgr, sorts: &ViewSorts, theme: &Theme) {
+        match self {
+            UIState::BudgrShow { state } => budgr_view(frame, area, state, budgr, sorts, theme),
+            UIState::LogShow { log, state } => log_view(frame, area, *log, state, budgr, sorts, theme),
+            UIState::PurchaseInput { input_data, selection_index, .. } => purchase_input_view(frame, area, input_data, *selection_index, budgr, theme),
+            UIState::TrashShow { state } => trash_view(frame, area, state, budgr, theme),
+            UIState::Search { input_data, selection_index, state } => search_view(frame, area, input_data, *selection_index, state, budgr, theme),
+            UIState::Stats { log } => stats_view(frame, area, *log, budgr, theme),
+            UIState::Report { log, period, state } => report_view(frame, area, *log, *period, state, budgr, theme),
+            UIState::Reimbursements { state } => reimbursements_view(frame, area, state, budgr, theme),
+            UIState::Rules { state } => rules_view(frame, area, state, budgr, theme),
+            UIState::Payees { state } => payees_view(frame, area, state, budgr, theme),
+            UIState::RuleInput { input_data, selection_index, .. } => rule_input_view(frame, area, input_data, *selection_index, theme),
+            UIState::PayeeInput { input_data, selection_index, .. } => payee_input_view(frame, area, input_data, *selection_index, theme),
+            UIState::AttachInput { input_data, selection_index, log, purchase } => attach_input_view(frame, area, input_data, *selection_index, *log, *purchase, budgr, theme),
+            UIState::Accounts { state } => accounts_view(frame, area, state, budgr, theme),
+            UIState::AccountInput { input_data, selection_index, .. } => account_input_view(frame, area, input_data, *selection_index, theme),
+            UIState::TransferInput { input_data, selection_index } => transfer_input_view(frame, area, input_data, *selection_index, budgr, theme),
+            UIState::ReconcileInput { input_data, selection_index, .. } => reconcile_input_view(frame, area, input_data, *selection_index, budgr, theme),
+            UIState::Envelopes { month, state } => envelopes_view(frame, area, *month, state, budgr, theme),
+            UIState::EnvelopeInput { input_data, selection_index, month, category } => envelope_input_view(frame, area, input_data, *selection_index, *month, *category, budgr, theme),
+            UIState::Goals { state } => goals_view(frame, area, state, budgr, theme),
+            UIState::Debts { state } => debts_view(frame, area, state, budgr, theme),
+            UIState::DebtInput { input_data, selection_index, .. } => debt_input_view(frame, area, input_data, *selection_index, budgr, theme),
+            UIState::DebtPaymentInput { input_data, selection_index, debt } => debt_payment_input_view(frame, area, input_data, *selection_index, *debt, budgr, theme),
+            UIState::GoalInput { input_data, selection_index, .. } => goal_input_view(frame, area, input_data, *selection_index, budgr, theme),
+        }
+    }
+}
+
+// - - - logs - - -
+
+fn budgr_update(
+    state: &mut TableState,
+    input: &UserInput,
+    area: Rect,
+    budgr: &mut Budgr,
+    sorts: &mut ViewSorts,
+    status: &mut Status,
+) -> Option<UITransition> {
+    // rows are shown in sorted order, map the selected row back to the log it shows
+    let order = sorts.log_order(budgr);
+    let selected = state.selected().and_then(|i| order.get(i)).map(|i| budgr.logs[*i].id);
+    let (area, _) = goals_strip(area, budgr);
+    let panes = split_panes(area);
+    let logs_area = match &panes {
+        Some(panes) => panes.logs,
+        None => area,
+    }
+    .inner(Margin::new(1, 1));
+
+    match input {
+        UserInput::Submit => return Some(UITransition::OpenLog(selected?)),
+        // clicking the selected log again opens it
+        UserInput::Click(x, y) if click_row(state, logs_area, 2, ROW_HEIGHT, order.len(), *x, *y) => {
+            return Some(UITransition::OpenLog(selected?));
+        }
+        // clicking the purchases of the selected log opens it too
+        UserInput::Click(x, y) if panes.as_ref().is_some_and(|p| p.purchases.contains(Position::new(*x, *y))) => {
+            return Some(UITransition::OpenLog(selected?));
+        }
+        UserInput::Esc => return Some(UITransition::ExitLayer),
+        UserInput::NextSelect => state.select_next(),
+        UserInput::PrevSelect => state.select_previous(),
+        UserInput::Trash => return Some(UITransition::OpenTrash),
+        UserInput::Search => return Some(UITransition::OpenSearch),
+        UserInput::Stats => return Some(UITransition::OpenStats(None)),
+        UserInput::Report => return Some(UITransition::OpenReport(None)),
+        UserInput::Reimbursements => return Some(UITransition::OpenReimbursements),
+        UserInput::Rules => return Some(UITransition::OpenRules),
+        UserInput::Accounts => return Some(UITransition::OpenAccounts),
+        UserInput::Envelopes => return Some(UITransition::OpenEnvelopes),
+        UserInput::Goals => return Some(UITransition::OpenGoals),
+        UserInput::Debts => return Some(UITransition::OpenDebts),
+        UserInput::Delete => {
+            let result = budgr.delete_log(selected?);
+            status.report(result, "moved log to trash");
+        }
+        UserInput::CycleSort => sorts.logs.cycle_key(),
+        UserInput::ReverseSort => sorts.logs.reverse(),
+        _ => {}
+    }
+
+    fit_table(state, budgr.logs.len(), logs_area, 2, ROW_HEIGHT);
+    None
+}
+
+fn budgr_view(frame: &mut Frame, area: Rect, state: &TableState, budgr: &Budgr, sorts: &ViewSorts, theme: &Theme) {
+    let order = sorts.log_order(budgr);
+    let (area, goals_area) = goals_strip(area, budgr);
+    if let Some(goals_area) = goals_area {
+        frame.render_widget(goals_summary(budgr, theme), goals_area);
+    }
+    let panes = split_panes(area);
+    let logs_area = match &panes {
+        Some(panes) => panes.logs,
+        None => area,
+    };
+
+    let table = logs_table(budgr, &order, sorts, panes.is_some(), theme)
+        .block(pane("logs".to_string(), true, theme))
+        .highlight_style(theme.highlight());
+    frame.render_stateful_widget(table, logs_area, &mut state.clone());
+
+    // the selected log is previewed next to the list
+    let Some(panes) = panes else {
+        return;
+    };
+    let Some(log) = state.selected().and_then(|i| order.get(i)).map(|i| &budgr.logs[*i]) else {
+        return;
+    };
+    let table = purchases_table(log, sorts, theme).block(pane(log.name.clone(), false, theme));
+    frame.render_widget(table, panes.purchases);
+    if let Some(detail) = panes.detail {
+        frame.render_widget(log_detail(log, theme), detail);
+    }
+}
+
+// - - - purchases of one log - - -
+
+fn log_update(
+    id: LogId,
+    state: &mut TableState,
+    input: &UserInput,
+    area: Rect,
+    budgr: &mut Budgr,
+    sorts: &mut ViewSorts,
+    status: &mut Status,
+) -> Option<UITransition> {
+    // the log can be gone if it was deleted from somewhere else
+    let Some(index) = budgr.log_index(id) else {
+        return Some(UITransition::ExitLayer);
+    };
+    // rows are shown in sorted order, map the selected row back to the purchase it shows
+    let log = &budgr.logs[index];
+    let order = sorts.purchase_order(log);
+    let selected = state.selected().and_then(|i| order.get(i)).map(|i| log.purchases[*i].id);
+    let log_order = sorts.log_order(budgr);
+    let panes = split_panes(area);
+    let purchases_area = match &panes {
+        Some(panes) => panes.purchases,
+        None => area,
+    }
+    .inner(Margin::new(1, 1));
+
+    match input {
+        UserInput::Click(x, y) if purchases_area.contains(Position::new(*x, *y)) => {
+            click_row(state, purchases_area, 2, ROW_HEIGHT, order.len(), *x, *y);
+        }
+        // clicking another log in the list switches to it
+        UserInput::Click(x, y) => {
+            let panes = panes.as_ref()?;
+            let mut logs_state = TableState::new().with_selected(log_order.iter().position(|i| *i == index));
+            click_row(&mut logs_state, panes.logs.inner(Margin::new(1, 1)), 2, ROW_HEIGHT, log_order.len(), *x, *y);
+            let clicked = *log_order.get(logs_state.selected()?)?;
+            if clicked != index {
+                return Some(UITransition::OpenLog(budgr.logs[clicked].id));
+            }
+        }
+        UserInput::Esc => return Some(UITransition::ExitLayer),
+        UserInput::NextSelect => state.select_next(),
+        UserInput::PrevSelect => state.select_previous(),
+        UserInput::Add => return Some(UITransition::NewPurchase),
+        UserInput::Attach => return Some(UITransition::AttachFile(id, selected?)),
+        UserInput::OpenAttachments => {
+            let purchase = budgr.log(id)?.purchase(selected?)?;
+            status.report(open_attachments(purchase), "opening attachments");
+        }
+        UserInput::ToggleCleared => match budgr.toggle_cleared(id, selected?) {
+            Ok(cleared) => status.info(format!("marked {}", cleared)),
+            Err(err) => status.error(err),
+        },
+        UserInput::Unlock => {
+            let result = budgr.unlock_purchase(id, selected?);
+            status.report(result, "unlocked purchase");
+        }
+        // start with the selected purchase's account, then one already being reconciled
+        UserInput::Reconcile => {
+            let accounts = &budgr.accounts.accounts;
+            let account = selected
+                .and_then(|p| budgr.log(id)?.purchase(p)?.account)
+                .or(accounts.iter().find(|a| a.statement.is_some()).map(|a| a.id))
+                .or(accounts.first().map(|a| a.id));
+            return Some(UITransition::Reconcile(account));
+        }
+        // finish the selected purchase's account, or the only one being reconciled
+        UserInput::FinishReconcile => {
+            let is_reconciling = |account: &AccountId| budgr.accounts.get(*account).is_some_and(|a| a.statement.is_some());
+            let mut reconciling = budgr.accounts.accounts.iter().map(|a| a.id).filter(is_reconciling);
+            let account = match selected.and_then(|p| budgr.log(id)?.purchase(p)?.account).filter(is_reconciling) {
+                Some(account) => account,
+                None => match (reconciling.next(), reconciling.next()) {
+                    (Some(account), None) => account,
+                    (None, _) => {
+                        status.error("nothing is being reconciled, C to start");
+                        return None;
+                    }
+                    (Some(_), Some(_)) => {
+                        status.error("more than one account is being reconciled, select a purchase from the one to finish");
+                        return None;
+                    }
+                },
+            };
+            let name = budgr.accounts.name(account);
+            match budgr.finish_reconciliation(account) {
+                Ok(locked) => status.info(format!("reconciled {}, {} purchases locked", name, locked)),
+                Err(err) => status.error(err),
+            }
+        }
+        UserInput::Stats => return Some(UITransition::OpenStats(Some(id))),
+        UserInput::Report => return Some(UITransition::OpenReport(Some(id))),
+        UserInput::Delete => {
+            let result = budgr.remove_purchase(id, selected?);
+            status.report(result, "moved purchase to trash");
+        }
+        UserInput::CycleSort => sorts.purchases.cycle_key(),
+        UserInput::ReverseSort => sorts.purchases.reverse(),
+        _ => {}
+    }
+
+    fit_table(state, budgr.logs[index].purchases.len(), purchases_area, 2, ROW_HEIGHT);
+    None
+}
+
+fn log_view(frame: &mut Frame, area: Rect, id: LogId, state: &TableState, budgr: &Budgr, sorts: &ViewSorts, theme: &Theme) {
+    let Some(index) = budgr.log_index(id) else {
+        return;
+    };
+    let log = &budgr.logs[index];
+    let order = sorts.purchase_order(log);
+    let panes = split_panes(area);
+    let purchases_area = match &panes {
+        Some(panes) => panes.purchases,
+        None => area,
+    };
+
+    let mut block = pane(log.name.clone(), true, theme);
+    if let Some(line) = reconcile_line(budgr, theme) {
+        block = block.title_bottom(line);
+    }
+    let table = purchases_table(log, sorts, theme)
+        .block(block)
+        .highlight_style(theme.highlight());
+    frame.render_stateful_widget(table, purchases_area, &mut state.clone());
+
+    // the other logs stay in view with the open one marked
+    let Some(panes) = panes else {
+        return;
+    };
+    let log_order = sorts.log_order(budgr);
+    let mut logs_state = TableState::new().with_selected(log_order.iter().position(|i| *i == index));
+    let table = logs_table(budgr, &log_order, sorts, true, theme)
+        .block(pane("logs".to_string(), false, theme))
+        .highlight_style(theme.accent().add_modifier(Modifier::BOLD));
+    frame.render_stateful_widget(table, panes.logs, &mut logs_state);
+
+    if let Some(detail) = panes.detail {
+        let purchase = state.selected().and_then(|i| order.get(i)).map(|i| &log.purchases[*i]);
+        let detail_widget = match purchase {
+            Some(purchase) => purchase_detail(log, purchase, budgr, theme),
+            None => log_detail(log, theme),
+        };
+        frame.render_widget(detail_widget, detail);
+    }
+}
+
+// - - - new purchase form - - -
+
+// the fields of the form in order, the submit button comes after them
+const PURCHASE_FIELDS: usize = 9;
+const SPLITS_FIELD: usize = 4;
+const TAGS_FIELD: usize = 5;
+const NOTE_FIELD: usize = 6;
+const ATTACHMENTS_FIELD: usize = 7;
+const ACCOUNT_FIELD: usize = 8;
+
+// an empty purchase form, the note is the only field that takes several lines
+pub fn purchase_form() -> Vec<InputData> {
+    let mut form = vec![InputData::default(); PURCHASE_FIELDS + 1];
+    form[NOTE_FIELD].multiline = true;
+    form
+}
+
+fn purchase_input_areas(area: Rect) -> Vec<Rect> {
+    let mut heights = vec![Constraint::Length(3); PURCHASE_FIELDS + 1];
+    heights[NOTE_FIELD] = Constraint::Length(5);
+    Layout::vertical(heights).split(area).to_vec()
+}
+
+// what the purchase form holds once it is filled in correctly
+struct PurchaseForm {
+    name: String,
+    cost: i64,
+    category: PurchaseType,
+    date: NaiveDate,
+    splits: Vec<Split>,
+    tags: Vec<String>,
+    note: String,
+    attachments: Vec<PathBuf>,
+    account: Option<AccountId>,
+}
+
+fn purchase_input_update(
+    dat: &mut [InputData],
+    selection_index: &mut usize,
+    log: LogId,
+    input: &UserInput,
+    area: Rect,
+    budgr: &mut Budgr,
+    status: &mut Status,
+) -> Option<UITransition> {
+    // moving right at the end of the tags takes the suggested tag
+    if *input == UserInput::Next && *selection_index == TAGS_FIELD {
+        if let Some(completion) = tag_completion(&dat[TAGS_FIELD], budgr) {
+            dat[TAGS_FIELD].insert_str(&completion);
+            return None;
+        }
+    }
+
+    match form_update(dat, selection_index, input, &purchase_input_areas(area))? {
+        FormAction::Submit => submit_purchase(&parse_purchase_input(dat, budgr), log, budgr, status),
+        FormAction::Exit => return Some(UITransition::ExitLayer),
+    }
+    None
+}
+
+fn purchase_input_view(frame: &mut Frame, area: Rect, dat: &[InputData], selection_index: usize, budgr: &Budgr, theme: &Theme) {
+    let category_title = format!(
+        "category ({})",
+        PurchaseType::ALL.map(|t| t.to_string()).join("/")
+    );
+    // the splits title keeps count of how much of the cost is still to be split
+    let splits_title = match (dat[1].input.trim().parse::<i64>(), parse_splits(&dat[SPLITS_FIELD].input)) {
+        (Ok(cost), Ok(splits)) if !splits.is_empty() => format!(
+            "splits (category amount, ...) {} left",
+            cost - splits.iter().map(|s| s.amount).sum::<i64>()
+        ),
+        _ => "splits (category amount, ..., empty for none)".to_string(),
+    };
+    let account_title = match budgr.accounts.accounts.is_empty() {
+        true => "account (add accounts with A on the logs screen)".to_string(),
+        false => format!(
+            "account ({}, empty for none)",
+            budgr.accounts.accounts.iter().map(|a| a.name.as_str()).collect::<Vec<&str>>().join("/")
+        ),
+    };
+    let titles = [
+        "name",
+        "cost",
+        category_title.as_str(),
+        "date (YYYY-MM-DD, empty for today)",
+        splits_title.as_str(),
+        "tags (right arrow takes the suggestion)",
+        "note (alt-enter for a new line)",
+        "attach files (paths, comma separated)",
+        account_title.as_str(),
+    ];
+    let mut fields = titles
+        .into_iter()
+        .enumerate()
+        .map(|(i, title)| input_field(title, &dat[i], selection_index == i, theme))
+        .collect::<Vec<Paragraph>>();
+    // the suggested tag is shown greyed out after what has been typed
+    if let Some(completion) = tag_completion(&dat[TAGS_FIELD], budgr).filter(|_| selection_index == TAGS_FIELD) {
+        let line = Line::from(vec![
+            Span::raw(dat[TAGS_FIELD].input.clone()),
+            Span::styled(completion, theme.item().add_modifier(Modifier::DIM)),
+        ]);
+        fields[TAGS_FIELD] = Paragraph::new(line)
+            .block(Block::bordered().title(titles[TAGS_FIELD]))
+            .style(theme.highlight())
+            .add_modifier(Modifier::BOLD);
+    }
+
+    // checked every frame so the submit button can say what is wrong
+    let check = parse_purchase_input(dat, budgr).map(|_| ());
+    form_view(frame, &purchase_input_areas(area), fields, dat, selection_index, check, theme);
+}
+
+// - - - attaching a file to a purchase - - -
+
+#[allow(clippy::too_many_arguments)]
+fn attach_input_update(
+    dat: &mut [InputData],
+    selection_index: &mut usize,
+    log: LogId,
+    purchase: PurchaseId,
+    input: &UserInput,
+    area: Rect,
+    budgr: &mut Budgr,
+    status: &mut Status,
+) -> Option<UITransition> {
+    match form_update(dat, selection_index, input, &form_areas(area, 1))? {
+        FormAction::Exit => Some(UITransition::ExitLayer),
+        FormAction::Submit => {
+            let result = parse_paths(&dat[0].input).and_then(|paths| {
+                paths.iter().try_for_each(|path| budgr.attach(log, purchase, path))
+            });
+            let attached = result.is_ok();
+            status.report(result, "attached");
+            attached.then_some(UITransition::ExitLayer)
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn attach_input_view(
+    frame: &mut Frame,
+    area: Rect,
+    dat: &[InputData],
+    selection_index: usize,
+    log: LogId,
+    purchase: PurchaseId,
+    budgr: &Budgr,
+    theme: &Theme,
+) {
+    let name = budgr
+        .log(log)
+        .and_then(|l| l.purchase(purchase))
+        .map(|p| p.name.clone())
+        .unwrap_or_default();
+    let title = format!("attach to {} (file paths, comma separated)", name);
+    let fields = vec![input_field(&title, &dat[0], selection_index == 0, theme)];
+    let check = parse_paths(&dat[0].input).and_then(|paths| match paths.is_empty() {
+        true => Err("type or paste a file path".to_string()),
+        false => Ok(()),
+    });
+    form_view(frame, &form_areas(area, 1), fields, dat, selection_index, check, theme);
+}
+
+// the rest of a known tag starting with the one being typed, only offered with the cursor at the end
+fn tag_completion(dat: &InputData, budgr: &Budgr) -> Option<String> {
+    if dat.character_pos != dat.input.chars().count() {
+        return None;
+    }
+    let partial = dat.input.rsplit([',', ' ']).next()?.to_lowercase();
+    if partial.is_empty() {
+        return None;
+    }
+    let typed = parse_tags(&dat.input);
+    budgr
+        .tags()
+        .into_iter()
+        .find(|tag| tag.starts_with(&partial) && tag.len() > partial.len() && !typed.contains(tag))
+        .map(|tag| tag[partial.len()..].to_string())
+}
+
+// - - - search - - -
+
+// the seven query fields in two rows, and the results below them
+fn search_areas(area: Rect) -> (Vec<Rect>, Rect) {
+    let [fields_area, results_area] =
+        Layout::vertical([Constraint::Length(6), Constraint::Min(0)]).areas(area);
+    let field_areas = Layout::vertical([Constraint::Length(3); 2])
+        .split(fields_area)
+        .iter()
+        .flat_map(|row| Layout::horizontal([Ratio(1, 4); 4]).split(*row).to_vec())
+        .take(7)
+        .collect::<Vec<Rect>>();
+    (field_areas, results_area)
+}
+
+fn search_query(dat: &[InputData]) -> Result<PurchaseQuery, String> {
+    PurchaseQuery::parse(
+        &dat[0].input,
+        &dat[1].input,
+        &dat[2].input,
+        &dat[3].input,
+        &dat[4].input,
+        &dat[5].input,
+        &dat[6].input,
+    )
+}
+
+fn search_update(
+    dat: &mut [InputData],
+    selection_index: &mut usize,
+    state: &mut TableState,
+    input: &UserInput,
+    area: Rect,
+    budgr: &Budgr,
+) -> Option<UITransition> {
+    // selection_index == dat.len() means the results table is selected
+    let results_index = dat.len();
+
+    let hits = match search_query(dat) {
+        Ok(query) => budgr.search(&query),
+        Err(_) => Vec::new(),
+    };
+    let (field_areas, results_area) = search_areas(area);
+    let results_area = results_area.inner(Margin::new(1, 1));
+
+    if let UserInput::Click(x, y) = input {
+        if let Some(i) = clicked_area(&field_areas, *x, *y) {
+            *selection_index = i;
+        } else if results_area.contains(Position::new(*x, *y)) {
+            *selection_index = results_index;
+            if click_row(state, results_area, 2, 2, hits.len(), *x, *y) {
+                let hit = hits.get(state.selected()?)?;
+                return Some(UITransition::OpenPurchase(hit.log, hit.purchase));
+            }
+        }
+    } else if *selection_index == results_index {
+        match input {
+            UserInput::Esc => return Some(UITransition::ExitLayer),
+            UserInput::NextSelect => state.select_next(),
+            UserInput::PrevField => *selection_index -= 1,
+            UserInput::PrevSelect => match state.selected() {
+                Some(0) | None => *selection_index -= 1,
+                Some(_) => state.select_previous(),
+            },
+            UserInput::Submit => {
+                let hit = hits.get(state.selected()?)?;
+                return Some(UITransition::OpenPurchase(hit.log, hit.purchase));
+            }
+            _ => (),
+        }
+    } else {
+        match input {
+            UserInput::NextSelect | UserInput::NextField | UserInput::Submit => {
+                *selection_index += 1;
+                if *selection_index == results_index && state.selected().is_none() {
+                    state.select_first();
+                }
+            }
+            UserInput::PrevSelect | UserInput::PrevField if *selection_index > 0 => *selection_index -= 1,
+            UserInput::Esc => return Some(UITransition::ExitLayer),
+            input => {
+                dat[*selection_index].handle(input);
+            }
+        }
+    }
+
+    // typing changes the results, so they are searched again for the fit
+    let hits = match search_query(dat) {
+        Ok(query) => budgr.search(&query).len(),
+        Err(_) => 0,
+    };
+    fit_table(state, hits, results_area, 2, 2);
+    None
+}
+
+fn search_view(
+    frame: &mut Frame,
+    area: Rect,
+    dat: &[InputData],
+    selection_index: usize,
+    state: &TableState,
+    budgr: &Budgr,
+    theme: &Theme,
+) {
+    let results_index = dat.len();
+    let query = search_query(dat);
+    let hits = match &query {
+        Ok(query) => budgr.search(query),
+        Err(_) => Vec::new(),
+    };
+
+    let titles = [
+        "name (/regex/ for a regex)",
+        "min cost",
+        "max cost",
+        "category",
+        "from (YYYY-MM-DD)",
+        "to (YYYY-MM-DD)",
+        "tag",
+    ];
+    let fields = titles
+        .into_iter()
+        .enumerate()
+        .map(|(i, title)| input_field(title, &dat[i], selection_index == i, theme))
+        .collect::<Vec<Paragraph>>();
+
+    let header = ["log", "name", "purchase type", "date", "cost"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(theme.header())
+        .height(2);
+
+    let rows = hits.iter().enumerate().filter_map(|(i, hit)| {
+        let log = budgr.log(hit.log)?;
+        let p = log.purchase(hit.purchase)?;
+        let row = [
+            log.name.clone(),
+            p.name.clone(),
+            category_label(p),
+            format_date(p.date),
+            p.cost.to_string(),
+        ]
+        .into_iter()
+        .map(|content| Cell::from(Text::from(content)))
+        .collect::<Row>()
+        .style(theme.row(i))
+        .height(2);
+        Some(row)
+    });
+
+    let results_title = match &query {
+        Ok(_) => format!("{} results", hits.len()),
+        Err(err) => err.clone(),
+    };
+    let mut table = Table::new(
+        rows,
+        [
+            Constraint::Min(16),
+            Constraint::Min(26),
+            Constraint::Length(14),
+            Constraint::Length(12),
+            Constraint::Length(12),
+        ],
+    )
+    .header(header)
+    .block(Block::bordered().title(results_title))
+    .style(theme.item());
+    if selection_index == results_index {
+        table = table.highlight_style(theme.highlight());
+    }
+
+    let (field_areas, results_area) = search_areas(area);
+    for (field, area) in fields.into_iter().zip(field_areas.iter()) {
+        frame.render_widget(field, *area);
+    }
+    frame.render_stateful_widget(table, results_area, &mut state.clone());
+    if selection_index < results_index {
+        frame.set_cursor_position(field_cursor(field_areas[selection_index], &dat[selection_index]));
+    }
+}
+
+// - - - trash - - -
+
+fn trash_update(
+    state: &mut TableState,
+    input: &UserInput,
+    area: Rect,
+    budgr: &mut Budgr,
+    status: &mut Status,
+) -> Option<UITransition> {
+    // trashed logs are listed first, then trashed purchases
+    let num_logs = budgr.trash.logs.len();
+
+    match input {
+        UserInput::Esc => return Some(UITransition::ExitLayer),
+        UserInput::NextSelect => state.select_next(),
+        UserInput::PrevSelect => state.select_previous(),
+        UserInput::Click(x, y) => {
+            click_row(state, area, 2, 2, budgr.trash.len(), *x, *y);
+        }
+        UserInput::Restore => {
+            let result = match state.selected()? {
+                i if i < num_logs => budgr.restore_log(budgr.trash.logs[i].log.id),
+                i => budgr.restore_purchase(budgr.trash.purchases.get(i - num_logs)?.purchase.id),
+            };
+            status.report(result, "restored");
+        }
+        UserInput::Delete => {
+            let result = match state.selected()? {
+                i if i < num_logs => budgr.purge_log(budgr.trash.logs[i].log.id),
+                i => budgr.purge_purchase(budgr.trash.purchases.get(i - num_logs)?.purchase.id),
+            };
+            status.report(result, "deleted forever");
+        }
+        _ => {}
+    }
+
+    fit_table(state, budgr.trash.len(), area, 2, 2);
+    None
+}
+
+fn trash_view(frame: &mut Frame, area: Rect, state: &TableState, budgr: &Budgr, theme: &Theme) {
+    if budgr.trash.is_empty() {
+        frame.render_widget(Paragraph::new("trash is empty").style(theme.item()), area);
+        return;
+    }
+
+    let header = ["deleted", "name", "from", "when"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(theme.header())
+        .height(2);
+
+    let log_items = budgr.trash.logs.iter().map(|t| {
+        [
+            "log".to_string(),
+            t.log.name.clone(),
+            format!("{} purchases", t.log.purchases.len()),
+            format_age(t.deleted_at),
+        ]
+    });
+    let purchase_items = budgr.trash.purchases.iter().map(|t| {
+        [
+            "purchase".to_string(),
+            t.purchase.name.clone(),
+            t.log_name.clone(),
+            format_age(t.deleted_at),
+        ]
+    });
+    let rows = log_items
+        .chain(purchase_items)
+        .enumerate()
+        .map(|(i, item)| {
+            item.into_iter()
+                .map(|content| Cell::from(Text::from(content)))
+                .collect::<Row>()
+                .style(theme.row(i))
+                .height(2)
+        });
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(10),
+            Constraint::Min(26),
+            Constraint::Min(26),
+            Constraint::Length(12),
+        ],
+    )
+    .header(header)
+    .highlight_style(theme.highlight());
+
+    frame.render_stateful_widget(table, area, &mut state.clone());
+}
+
+// - - - charts - - -
+
+fn stats_update(input: &UserInput) -> Option<UITransition> {
+    match input {
+        UserInput::Esc => Some(UITransition::ExitLayer),
+        _ => None,
+    }
+}
+
+fn stats_view(frame: &mut Frame, area: Rect, log: Option<LogId>, budgr: &Budgr, theme: &Theme) {
+    // a single log, or every log together
+    let (title, category_totals, tag_totals, daily_totals) = match log.and_then(|id| budgr.log(id)) {
+        Some(log) => (
+            log.name.clone(),
+            log.category_totals(),
+            log.tag_totals(),
+            log.daily_totals(),
+        ),
+        None => (
+            "all logs".to_string(),
+            budgr.category_totals(),
+            budgr.tag_totals(),
+            budgr.daily_totals(),
+        ),
+    };
+
+    // spending by category
+    let bars = category_totals
+        .iter()
+        .map(|(category, total)| {
+            Bar::default()
+                .label(category.to_string().into())
+                .value((*total).max(0) as u64)
+                .text_value(total.to_string())
+        })
+        .collect::<Vec<Bar>>();
+    let bar_chart = BarChart::default()
+        .block(Block::bordered().title(format!("{}: spending by category", title)))
+        .data(BarGroup::default().bars(&bars))
+        .bar_width(12)
+        .bar_gap(2)
+        .bar_style(theme.accent())
+        .value_style(theme.on_accent())
+        .style(theme.item());
+
+    // spending over time, x is days since the first purchase
+    let first_day = daily_totals.keys().next().copied().unwrap_or_else(today);
+    let last_day = daily_totals.keys().last().copied().unwrap_or_else(today);
+    let points = daily_totals
+        .iter()
+        .map(|(date, total)| ((*date - first_day).num_days() as f64, *total as f64))
+        .collect::<Vec<(f64, f64)>>();
+    let max_x = ((last_day - first_day).num_days() as f64).max(1.0);
+    let max_y = points.iter().map(|(_, y)| *y).fold(1.0, f64::max);
+    let min_y = points.iter().map(|(_, y)| *y).fold(0.0, f64::min);
+
+    let dataset = Dataset::default()
+        .name("daily spending")
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(theme.accent())
+        .data(&points);
+    let line_chart = Chart::new(vec![dataset])
+        .block(Block::bordered().title(format!("{}: spending over time", title)))
+        .x_axis(
+            Axis::default()
+                .bounds([0.0, max_x])
+                .labels([format_date(Some(first_day)), format_date(Some(last_day))]),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([min_y, max_y])
+                .labels([min_y.to_string(), max_y.to_string()]),
+        )
+        .style(theme.item());
+
+    let [bar_area, line_area] = Layout::vertical([Ratio(1, 2); 2]).areas(area);
+    frame.render_widget(line_chart, line_area);
+    if tag_totals.is_empty() {
+        frame.render_widget(bar_chart, bar_area);
+        return;
+    }
+
+    // spending by tag next to the categories, only when something is tagged
+    let tag_bars = tag_totals
+        .iter()
+        .map(|(tag, total)| {
+            Bar::default()
+                .label(format!("#{}", tag).into())
+                .value((*total).max(0) as u64)
+                .text_value(total.to_string())
+        })
+        .collect::<Vec<Bar>>();
+    let tag_chart = BarChart::default()
+        .block(Block::bordered().title(format!("{}: spending by tag", title)))
+        .data(BarGroup::default().bars(&tag_bars))
+        .bar_width(12)
+        .bar_gap(2)
+        .bar_style(theme.accent())
+        .value_style(theme.on_accent())
+        .style(theme.item());
+    let [category_area, tag_area] = Layout::horizontal([Ratio(1, 2); 2]).areas(bar_area);
+    frame.render_widget(bar_chart, category_area);
+    frame.render_widget(tag_chart, tag_area);
+}
+
+// - - - period reports - - -
+
+fn report_update(
+    log: Option<LogId>,
+    period: &mut Period,
+    state: &mut TableState,
+    input: &UserInput,
+    area: Rect,
+    budgr: &Budgr,
+) -> Option<UITransition> {
+    match input {
+        UserInput::Esc => return Some(UITransition::ExitLayer),
+        UserInput::NextSelect => state.select_next(),
+        UserInput::PrevSelect => state.select_previous(),
+        UserInput::WeekPeriod => *period = Period::Week,
+        UserInput::MonthPeriod => *period = Period::Month,
+        UserInput::YearPeriod => *period = Period::Year,
+        _ => {}
+    }
+
+    let report = match log.and_then(|id| budgr.log(id)) {
+        Some(log) => log.report(*period),
+        None => budgr.report(*period),
+    };
+    let inner = area.inner(Margin::new(1, 1));
+    if let UserInput::Click(x, y) = input {
+        click_row(state, inner, 2, 2, report.summaries.len(), *x, *y);
+    }
+    fit_table(state, report.summaries.len(), inner, 2, 2);
+    None
+}
+
+fn report_view(
+    frame: &mut Frame,
+    area: Rect,
+    log: Option<LogId>,
+    period: Period,
+    state: &TableState,
+    budgr: &Budgr,
+    theme: &Theme,
+) {
+    let report = match log.and_then(|id| budgr.log(id)) {
+        Some(log) => log.report(period),
+        None => budgr.report(period),
+    };
+
+    let mut titles = vec!["period", "total", "vs previous", "daily average"];
+    let categories = PurchaseType::ALL.map(|t| t.to_string());
+    titles.extend(categories.iter().map(|c| c.as_str()));
+
+    let header = titles
+        .iter()
+        .map(|t| Cell::from(*t))
+        .collect::<Row>()
+        .style(theme.header())
+        .height(2);
+
+    // newest period first
+    let rows = report.summaries.iter().rev().enumerate().map(|(i, s)| {
+        let mut item = vec![
+            s.label.clone(),
+            s.total.to_string(),
+            s.change.map(|c| format!("{:+}", c)).unwrap_or("-".to_string()),
+            format!("{:.2}", s.average_daily),
+        ];
+        item.extend(
+            PurchaseType::ALL.map(|t| s.by_category.get(&t).copied().unwrap_or(0).to_string()),
+        );
+        item.into_iter()
+            .map(|content| Cell::from(Text::from(content)))
+            .collect::<Row>()
+            .style(theme.row(i))
+            .height(2)
+    });
+
+    let mut widths = vec![Constraint::Length(12); 4];
+    widths.extend([Constraint::Min(10); PurchaseType::ALL.len()]);
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(Block::bordered().title(format!(
+            "{} report for {}",
+            report.period, report.scope
+        )))
+        .style(theme.item())
+        .highlight_style(theme.highlight());
+
+    frame.render_stateful_widget(table, area, &mut state.clone());
+}
+
+// - - - outstanding reimbursements - - -
+
+fn reimbursements_update(state: &mut TableState, input: &UserInput, area: Rect, budgr: &Budgr) -> Option<UITransition> {
+    match input {
+        UserInput::Esc => return Some(UITransition::ExitLayer),
+        UserInput::NextSelect => state.select_next(),
+        UserInput::PrevSelect => state.select_previous(),
+        _ => {}
+    }
+
+    let len = budgr.reimbursements().outstanding.len();
+    let inner = area.inner(Margin::new(1, 1));
+    if let UserInput::Click(x, y) = input {
+        click_row(state, inner, 2, 2, len, *x, *y);
+    }
+    fit_table(state, len, inner, 2, 2);
+    None
+}
+
+fn reimbursements_view(frame: &mut Frame, area: Rect, state: &TableState, budgr: &Budgr, theme: &Theme) {
+    let reimbursements = budgr.reimbursements();
+    if reimbursements.outstanding.is_empty() {
+        let text = "nothing to be paid back, tag purchases work-reimbursable (or anything ending in -reimbursable) to track them";
+        frame.render_widget(Paragraph::new(text).style(theme.item()), area);
+        return;
+    }
+
+    let header = ["date", "log", "name", "tag", "owed"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(theme.header())
+        .height(2);
+
+    let rows = reimbursements.outstanding.iter().enumerate().map(|(i, r)| {
+        [
+            format_date(r.date),
+            r.log.clone(),
+            r.name.clone(),
+            r.tag.clone(),
+            r.cost.to_string(),
+        ]
+        .into_iter()
+        .map(|content| Cell::from(Text::from(content)))
+        .collect::<Row>()
+        .style(theme.row(i))
+        .height(2)
+    });
+
+    // what is owed per tag goes along the bottom
+    let by_tag = reimbursements
+        .by_tag
+        .iter()
+        .map(|(tag, total)| format!("{} {}", tag, total))
+        .collect::<Vec<String>>()
+        .join("  ");
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(12),
+            Constraint::Min(16),
+            Constraint::Min(26),
+            Constraint::Min(20),
+            Constraint::Length(10),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::bordered()
+            .title(format!("outstanding reimbursements: {}", reimbursements.total))
+            .title_bottom(format!("tag {} once paid back  {}", REIMBURSED_TAG, by_tag)),
+    )
+    .style(theme.item())
+    .highlight_style(theme.highlight());
+
+    frame.render_stateful_widget(table, area, &mut state.clone());
+}
+
+// - - - payees and rules - - -
+
+const RULE_FIELDS: usize = 6;
+const PAYEE_FIELDS: usize = 2;
+
+// a rule form, filled in with the rule being edited
+pub fn rule_form(rule: Option<&Rule>) -> Vec<InputData> {
+    let Some(rule) = rule else {
+        return vec![InputData::default(); RULE_FIELDS + 1];
+    };
+    let number = |n: Option<i64>| n.map(|n| n.to_string()).unwrap_or_default();
+    vec![
+        InputData::with_text(rule.pattern.clone()),
+        InputData::with_text(number(rule.min_cost)),
+        InputData::with_text(number(rule.max_cost)),
+        InputData::with_text(rule.payee.clone().unwrap_or_default()),
+        InputData::with_text(rule.category.map(|c| c.to_string()).unwrap_or_default()),
+        InputData::with_text(rule.tags.join(", ")),
+        InputData::default(),
+    ]
+}
+
+// a payee form, filled in with the payee being edited
+pub fn payee_form(payee: Option<&Payee>) -> Vec<InputData> {
+    let Some(payee) = payee else {
+        return vec![InputData::default(); PAYEE_FIELDS + 1];
+    };
+    vec![
+        InputData::with_text(payee.name.clone()),
+        InputData::with_text(payee.aliases.join(", ")),
+        InputData::default(),
+    ]
+}
+
+fn rules_update(state: &mut TableState, input: &UserInput, area: Rect, budgr: &mut Budgr, status: &mut Status) -> Option<UITransition> {
+    let len = budgr.payees.rules.len();
+    let inner = area.inner(Margin::new(1, 1));
+    match input {
+        UserInput::Esc => return Some(UITransition::ExitLayer),
+        UserInput::NextSelect => state.select_next(),
+        UserInput::PrevSelect => state.select_previous(),
+        UserInput::Add => return Some(UITransition::EditRule(None)),
+        UserInput::Submit => return Some(UITransition::EditRule(Some(state.selected()?))),
+        UserInput::Payees => return Some(UITransition::OpenPayees),
+        // clicking the selected rule again edits it
+        UserInput::Click(x, y) if click_row(state, inner, 2, 2, len, *x, *y) => {
+            return Some(UITransition::EditRule(Some(state.selected()?)));
+        }
+        UserInput::Delete => {
+            let result = budgr.remove_rule(state.selected()?);
+            status.report(result, "deleted rule");
+        }
+        _ => {}
+    }
+    fit_table(state, budgr.payees.rules.len(), inner, 2, 2);
+    None
+}
+
+fn rules_view(frame: &mut Frame, area: Rect, state: &TableState, budgr: &Budgr, theme: &Theme) {
+    let header = ["name matches", "cost", "payee", "category", "tags"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(theme.header())
+        .height(2);
+
+    let rows = budgr.payees.rules.iter().enumerate().map(|(i, rule)| {
+        let cost = match (rule.min_cost, rule.max_cost) {
+            (None, None) => "any".to_string(),
+            (min, max) => format!(
+                "{}..{}",
+                min.map(|n| n.to_string()).unwrap_or_default(),
+                max.map(|n| n.to_string()).unwrap_or_default()
+            ),
+        };
+        [
+            rule.pattern.clone(),
+            cost,
+            rule.payee.clone().unwrap_or("-".to_string()),
+            rule.category.map(|c| c.to_string()).unwrap_or("-".to_string()),
+            tag_list(&rule.tags),
+        ]
+        .into_iter()
+        .map(|content| Cell::from(Text::from(content)))
+        .collect::<Row>()
+        .style(theme.row(i))
+        .height(2)
+    });
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(20),
+            Constraint::Length(12),
+            Constraint::Min(16),
+            Constraint::Length(12),
+            Constraint::Min(20),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::bordered()
+            .title("rules, the first one that matches a new purchase fills it in")
+            .title_bottom("a to add a rule, p for payees"),
+    )
+    .style(theme.item())
+    .highlight_style(theme.highlight());
+
+    frame.render_stateful_widget(table, area, &mut state.clone());
+}
+
+fn payees_update(state: &mut TableState, input: &UserInput, area: Rect, budgr: &mut Budgr, status: &mut Status) -> Option<UITransition> {
+    let len = budgr.payees.payees.len();
+    let inner = area.inner(Margin::new(1, 1));
+    match input {
+        UserInput::Esc => return Some(UITransition::ExitLayer),
+        UserInput::NextSelect => state.select_next(),
+        UserInput::PrevSelect => state.select_previous(),
+        UserInput::Add => return Some(UITransition::EditPayee(None)),
+        UserInput::Submit => return Some(UITransition::EditPayee(Some(state.selected()?))),
+        UserInput::Click(x, y) if click_row(state, inner, 2, 2, len, *x, *y) => {
+            return Some(UITransition::EditPayee(Some(state.selected()?)));
+        }
+        UserInput::Delete => {
+            let result = budgr.remove_payee(state.selected()?);
+            status.report(result, "deleted payee");
+        }
+        _ => {}
+    }
+    fit_table(state, budgr.payees.payees.len(), inner, 2, 2);
+    None
+}
+
+fn payees_view(frame: &mut Frame, area: Rect, state: &TableState, budgr: &Budgr, theme: &Theme) {
+    let header = ["payee", "also known as"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(theme.header())
+        .height(2);
+
+    let rows = budgr.payees.payees.iter().enumerate().map(|(i, payee)| {
+        [payee.name.clone(), payee.aliases.join(", ")]
+            .into_iter()
+            .map(|content| Cell::from(Text::from(content)))
+            .collect::<Row>()
+            .style(theme.row(i))
+            .height(2)
+    });
+
+    let table = Table::new(rows, [Constraint::Min(20), Constraint::Fill(2)])
+        .header(header)
+        .block(Block::bordered().title("payees, purchases with a name or alias in them are filed under the payee"))
+        .style(theme.item())
+        .highlight_style(theme.highlight());
+
+    frame.render_stateful_widget(table, area, &mut state.clone());
+}
+
+fn rule_input_update(
+    dat: &mut [InputData],
+    selection_index: &mut usize,
+    rule: Option<usize>,
+    input: &UserInput,
+    area: Rect,
+    budgr: &mut Budgr,
+    status: &mut Status,
+) -> Option<UITransition> {
+    match form_update(dat, selection_index, input, &form_areas(area, RULE_FIELDS))? {
+        FormAction::Exit => Some(UITransition::ExitLayer),
+        FormAction::Submit => {
+            let result = parse_rule_input(dat).and_then(|r| budgr.save_rule(rule, r));
+            let saved = result.is_ok();
+            status.report(result, "saved rule");
+            saved.then_some(UITransition::ExitLayer)
+        }
+    }
+}
+
+fn rule_input_view(frame: &mut Frame, area: Rect, dat: &[InputData], selection_index: usize, theme: &Theme) {
+    let category_title = format!(
+        "category to set ({}, empty to leave it)",
+        PurchaseType::ALL.map(|t| t.to_string()).join("/")
+    );
+    let titles = [
+        "name contains (or /regex/)",
+        "min cost",
+        "max cost",
+        "payee to set",
+        category_title.as_str(),
+        "tags to add",
+    ];
+    let fields = titles
+        .into_iter()
+        .enumerate()
+        .map(|(i, title)| input_field(title, &dat[i], selection_index == i, theme))
+        .collect::<Vec<Paragraph>>();
+    let check = parse_rule_input(dat).map(|_| ());
+    form_view(frame, &form_areas(area, RULE_FIELDS), fields, dat, selection_index, check, theme);
+}
+
+fn parse_rule_input(dat: &[InputData]) -> Result<Rule, String> {
+    let rule = Rule {
+        pattern: dat[0].input.trim().to_string(),
+        min_cost: optional_number(&dat[1].input, "min cost")?,
+        max_cost: optional_number(&dat[2].input, "max cost")?,
+        payee: Some(dat[3].input.trim().to_string()).filter(|p| !p.is_empty()),
+        category: match dat[4].input.trim() {
+            "" => None,
+            category => Some(category.parse::<PurchaseType>()?),
+        },
+        tags: parse_tags(&dat[5].input),
+    };
+    rule.validate()?;
+    Ok(rule)
+}
+
+fn payee_input_update(
+    dat: &mut [InputData],
+    selection_index: &mut usize,
+    payee: Option<usize>,
+    input: &UserInput,
+    area: Rect,
+    budgr: &mut Budgr,
+    status: &mut Status,
+) -> Option<UITransition> {
+    match form_update(dat, selection_index, input, &form_areas(area, PAYEE_FIELDS))? {
+        FormAction::Exit => Some(UITransition::ExitLayer),
+        FormAction::Submit => {
+            let result = budgr.save_payee(payee, parse_payee_input(dat));
+            let saved = result.is_ok();
+            status.report(result, "saved payee");
+            saved.then_some(UITransition::ExitLayer)
+        }
+    }
+}
+
+fn payee_input_view(frame: &mut Frame, area: Rect, dat: &[InputData], selection_index: usize, theme: &Theme) {
+    let titles = ["name", "aliases (comma separated)"];
+    let fields = titles
+        .into_iter()
+        .enumerate()
+        .map(|(i, title)| input_field(title, &dat[i], selection_index == i, theme))
+        .collect::<Vec<Paragraph>>();
+    let check = match dat[0].input.trim().is_empty() {
+        true => Err("a payee needs a name".to_string()),
+        false => Ok(()),
+    };
+    form_view(frame, &form_areas(area, PAYEE_FIELDS), fields, dat, selection_index, check, theme);
+}
+
+fn parse_payee_input(dat: &[InputData]) -> Payee {
+    Payee {
+        name: dat[0].input.trim().to_string(),
+        aliases: dat[1]
+            .input
+            .split(',')
+            .map(str::trim)
+            .filter(|alias| !alias.is_empty())
+            .map(str::to_string)
+            .collect(),
+    }
+}
+
+// - - - accounts - - -
+
+const ACCOUNT_FIELDS: usize = 4;
+const TRANSFER_FIELDS: usize = 5;
+
+// an account form, filled in with the account being edited
+pub fn account_form(account: Option<&Account>) -> Vec<InputData> {
+    let Some(account) = account else {
+        return vec![InputData::default(); ACCOUNT_FIELDS + 1];
+    };
+    vec![
+        InputData::with_text(account.name.clone()),
+        InputData::with_text(account.kind.to_string()),
+        InputData::with_text(account.opening_balance.to_string()),
+        InputData::with_text(account.opening_date.map(|d| d.to_string()).unwrap_or_default()),
+        InputData::default(),
+    ]
+}
+
+// a transfer form, with the money coming from the selected account
+pub fn transfer_form(from: Option<&Account>) -> Vec<InputData> {
+    let mut form = vec![InputData::default(); TRANSFER_FIELDS + 1];
+    if let Some(from) = from {
+        form[0] = InputData::with_text(from.name.clone());
+    }
+    form
+}
+
+// the list of accounts on top, the selected account's history under it
+fn accounts_areas(area: Rect) -> [Rect; 2] {
+    Layout::vertical([Ratio(1, 3), Ratio(2, 3)]).areas(area)
+}
+
+fn accounts_update(state: &mut TableState, input: &UserInput, area: Rect, budgr: &mut Budgr, status: &mut Status) -> Option<UITransition> {
+    let len = budgr.accounts.accounts.len();
+    let [list_area, _] = accounts_areas(area);
+    let inner = list_area.inner(Margin::new(1, 1));
+    let selected = |state: &TableState| {
+        state
+            .selected()
+            .and_then(|i| budgr.accounts.accounts.get(i))
+            .map(|a| a.id)
+    };
+    match input {
+        UserInput::Esc => return Some(UITransition::ExitLayer),
+        UserInput::NextSelect => state.select_next(),
+        UserInput::PrevSelect => state.select_previous(),
+        UserInput::Add => return Some(UITransition::EditAccount(None)),
+        UserInput::Submit => return Some(UITransition::EditAccount(Some(selected(state)?))),
+        UserInput::Transfer => return Some(UITransition::NewTransfer(selected(state))),
+        // clicking the selected account again edits it
+        UserInput::Click(x, y) if click_row(state, inner, 1, 1, len, *x, *y) => {
+            return Some(UITransition::EditAccount(Some(selected(state)?)));
+        }
+        UserInput::Delete => {
+            let result = budgr.remove_account(selected(state)?);
+            status.report(result, "deleted account");
+        }
+        _ => {}
+    }
+    fit_table(state, budgr.accounts.accounts.len(), inner, 1, 1);
+    None
+}
+
+fn accounts_view(frame: &mut Frame, area: Rect, state: &TableState, budgr: &Budgr, theme: &Theme) {
+    if budgr.accounts.accounts.is_empty() {
+        let text = "no accounts yet, press a to add one. purchases can then say which account paid for them";
+        frame.render_widget(Paragraph::new(text).style(theme.item()), area);
+        return;
+    }
+    let [list_area, ledger_area] = accounts_areas(area);
+
+    let header = ["account", "kind", "opening", "balance", "reconciled"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(theme.header());
+    let rows = budgr.accounts.accounts.iter().enumerate().map(|(i, account)| {
+        [
+            account.name.clone(),
+            account.kind.to_string(),
+            account.opening_balance.to_string(),
+            budgr.balance(account.id).to_string(),
+            match (account.statement, account.reconciled) {
+                (Some(_), _) => "in progress".to_string(),
+                (None, Some(statement)) => statement.date.to_string(),
+                (None, None) => "never".to_string(),
+            },
+        ]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(theme.row(i))
+    });
+    let total = budgr.accounts.accounts.iter().map(|a| budgr.balance(a.id)).sum::<i64>();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(20),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(12),
+        ],
+    )
+    .header(header)
+    .block(
+        pane(format!("accounts, {} altogether", total), true, theme)
+            .title_bottom("a to add an account, x to move money between accounts"),
+    )
+    .style(theme.item())
+    .highlight_style(theme.highlight());
+    frame.render_stateful_widget(table, list_area, &mut state.clone());
+
+    let Some(account) = state.selected().and_then(|i| budgr.accounts.accounts.get(i)) else {
+        return;
+    };
+    let ledger = budgr.ledger(account.id);
+    let header = ["date", "", "amount", "balance"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(theme.header());
+    let rows = ledger.iter().enumerate().map(|(i, entry)| {
+        [
+            format_date(entry.date),
+            entry.description.clone(),
+            entry.amount.to_string(),
+            entry.balance.to_string(),
+        ]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(theme.row(i))
+    });
+    // the newest entries are at the bottom, keep them in view
+    let visible = ledger_area.height.saturating_sub(3) as usize;
+    let mut ledger_state = TableState::new().with_offset(ledger.len().saturating_sub(visible));
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(12),
+            Constraint::Min(20),
+            Constraint::Length(10),
+            Constraint::Length(10),
+        ],
+    )
+    .header(header)
+    .block(pane(format!("{} history", account.name), false, theme))
+    .style(theme.item());
+    frame.render_stateful_widget(table, ledger_area, &mut ledger_state);
+}
+
+fn account_input_update(
+    dat: &mut [InputData],
+    selection_index: &mut usize,
+    account: Option<AccountId>,
+    input: &UserInput,
+    area: Rect,
+    budgr: &mut Budgr,
+    status: &mut Status,
+) -> Option<UITransition> {
+    match form_update(dat, selection_index, input, &form_areas(area, ACCOUNT_FIELDS))? {
+        FormAction::Exit => Some(UITransition::ExitLayer),
+        FormAction::Submit => {
+            let existing = account.and_then(|id| budgr.accounts.get(id)).cloned();
+            let result = parse_account_input(dat, existing).and_then(|a| budgr.save_account(a));
+            let saved = result.is_ok();
+            status.report(result, "saved account");
+            saved.then_some(UITransition::ExitLayer)
+        }
+    }
+}
+
+fn account_input_view(frame: &mut Frame, area: Rect, dat: &[InputData], selection_index: usize, theme: &Theme) {
+    let kind_title = format!(
+        "kind ({}, empty for Checking)",
+        AccountKind::ALL.map(|k| k.to_string()).join("/")
+    );
+    let titles = [
+        "name",
+        kind_title.as_str(),
+        "opening balance (negative for money owed)",
+        "opening date (YYYY-MM-DD, optional)",
+    ];
+    let fields = titles
+        .into_iter()
+        .enumerate()
+        .map(|(i, title)| input_field(title, &dat[i], selection_index == i, theme))
+        .collect::<Vec<Paragraph>>();
+    let check = parse_account_input(dat, None).map(|_| ());
+    form_view(frame, &form_areas(area, ACCOUNT_FIELDS), fields, dat, selection_index, check, theme);
+}
+
+// the account being edited keeps its id, so its purchases stay with it, and its reconciliation
+fn parse_account_input(dat: &[InputData], existing: Option<Account>) -> Result<Account, String> {
+    let name = dat[0].input.trim().to_string();
+    if name.is_empty() {
+        return Err("an account needs a name".to_string());
+    }
+    Ok(Account {
+        name,
+        kind: match dat[1].input.trim() {
+            "" => AccountKind::default(),
+            kind => kind.parse::<AccountKind>()?,
+        },
+        opening_balance: optional_number(&dat[2].input, "opening balance")?.unwrap_or(0),
+        opening_date: match dat[3].input.trim() {
+            "" => None,
+            date => Some(parse_date(date)?),
+        },
+        ..existing.unwrap_or_default()
+    })
+}
+
+fn transfer_input_update(
+    dat: &mut [InputData],
+    selection_index: &mut usize,
+    input: &UserInput,
+    area: Rect,
+    budgr: &mut Budgr,
+    status: &mut Status,
+) -> Option<UITransition> {
+    match form_update(dat, selection_index, input, &form_areas(area, TRANSFER_FIELDS))? {
+        FormAction::Exit => Some(UITransition::ExitLayer),
+        FormAction::Submit => {
+            let result = parse_transfer_input(dat, budgr).and_then(|t| budgr.transfer(t));
+            let saved = result.is_ok();
+            status.report(result, "transferred");
+            saved.then_some(UITransition::ExitLayer)
+        }
+    }
+}
+
+fn transfer_input_view(frame: &mut Frame, area: Rect, dat: &[InputData], selection_index: usize, budgr: &Budgr, theme: &Theme) {
+    let names = budgr.accounts.accounts.iter().map(|a| a.name.as_str()).collect::<Vec<&str>>().join("/");
+    let from_title = format!("from ({})", names);
+    let to_title = format!("to ({})", names);
+    let titles = [
+        from_title.as_str(),
+        to_title.as_str(),
+        "amount",
+        "date (YYYY-MM-DD, empty for today)",
+        "note",
+    ];
+    let fields = titles
+        .into_iter()
+        .enumerate()
+        .map(|(i, title)| input_field(title, &dat[i], selection_index == i, theme))
+        .collect::<Vec<Paragraph>>();
+    let check = parse_transfer_input(dat, budgr).map(|_| ());
+    form_view(frame, &form_areas(area, TRANSFER_FIELDS), fields, dat, selection_index, check, theme);
+}
+
+fn parse_transfer_input(dat: &[InputData], budgr: &Budgr) -> Result<Transfer, String> {
+    let from = parse_account(&dat[0].input, budgr)?.ok_or("which account is the money coming from?")?;
+    let to = parse_account(&dat[1].input, budgr)?.ok_or("which account is the money going to?")?;
+    if from == to {
+        return Err("pick two different accounts".to_string());
+    }
+    let amount = dat[2]
+        .input
+        .trim()
+        .parse::<i64>()
+        .map_err(|_| "amount must be a whole number".to_string())?;
+    if amount <= 0 {
+        return Err("amount must be more than 0".to_string());
+    }
+    Ok(Transfer {
+        from,
+        to,
+        amount,
+        date: parse_date(&dat[3].input)?,
+        note: dat[4].input.trim().to_string(),
+    })
+}
+
+// - - - reconciling against a statement - - -
+
+const RECONCILE_FIELDS: usize = 3;
+
+// a reconcile form for an account, filled in with the statement already being reconciled against
+pub fn reconcile_form(account: Option<&Account>) -> Vec<InputData> {
+    let mut form = vec![InputData::default(); RECONCILE_FIELDS + 1];
+    if let Some(account) = account {
+        form[0] = InputData::with_text(account.name.clone());
+        if let Some(statement) = account.statement {
+            form[1] = InputData::with_text(statement.date.to_string());
+            form[2] = InputData::with_text(statement.balance.to_string());
+        }
+    }
+    form
+}
+
+// how far each account being reconciled is from its statement, shown under the purchases
+fn reconcile_line<'a>(budgr: &Budgr, theme: &Theme) -> Option<Line<'a>> {
+    let accounts = budgr
+        .accounts
+        .accounts
+        .iter()
+        .filter_map(|account| {
+            let statement = account.statement?;
+            let difference = budgr.reconciliation_difference(account.id)?;
+            Some(match difference {
+                0 => format!("{} matches the {} statement, F to finish", account.name, statement.date),
+                difference => format!(
+                    "reconciling {} to {}: statement {}, cleared {}, {} off",
+                    account.name,
+                    statement.date,
+                    statement.balance,
+                    statement.balance - difference,
+                    difference
+                ),
+            })
+        })
+        .collect::<Vec<String>>();
+    (!accounts.is_empty()).then(|| Line::styled(accounts.join("  |  "), theme.accent()))
+}
+
+fn reconcile_input_update(
+    dat: &mut [InputData],
+    selection_index: &mut usize,
+    input: &UserInput,
+    area: Rect,
+    budgr: &mut Budgr,
+    status: &mut Status,
+) -> Option<UITransition> {
+    match form_update(dat, selection_index, input, &form_areas(area, RECONCILE_FIELDS))? {
+        FormAction::Exit => Some(UITransition::ExitLayer),
+        FormAction::Submit => {
+            let result = parse_reconcile_input(dat, budgr);
+            let saved = result.is_ok();
+            match result {
+                Ok((account, Some(statement))) => {
+                    let result = budgr.start_reconciliation(account, statement);
+                    status.report(result, "reconciling, tick off purchases on the statement with c");
+                }
+                Ok((account, None)) => {
+                    let result = budgr.cancel_reconciliation(account);
+                    status.report(result, "stopped reconciling");
+                }
+                Err(err) => status.error(err),
+            }
+            saved.then_some(UITransition::ExitLayer)
+        }
+    }
+}
+
+fn reconcile_input_view(frame: &mut Frame, area: Rect, dat: &[InputData], selection_index: usize, budgr: &Budgr, theme: &Theme) {
+    let names = budgr.accounts.accounts.iter().map(|a| a.name.as_str()).collect::<Vec<&str>>().join("/");
+    let account_title = format!("account ({})", names);
+    let titles = [
+        account_title.as_str(),
+        "statement end date (YYYY-MM-DD, empty for today)",
+        "statement balance (empty to stop reconciling)",
+    ];
+    let fields = titles
+        .into_iter()
+        .enumerate()
+        .map(|(i, title)| input_field(title, &dat[i], selection_index == i, theme))
+        .collect::<Vec<Paragraph>>();
+    let check = parse_reconcile_input(dat, budgr).map(|_| ());
+    form_view(frame, &form_areas(area, RECONCILE_FIELDS), fields, dat, selection_index, check, theme);
+}
+
+// the account and its statement, no balance means stop reconciling it
+fn parse_reconcile_input(dat: &[InputData], budgr: &Budgr) -> Result<(AccountId, Option<Statement>), String> {
+    let account = parse_account(&dat[0].input, budgr)?.ok_or("which account is the statement for?")?;
+    let statement = match optional_number(&dat[2].input, "statement balance")? {
+        Some(balance) => Some(Statement {
+            date: parse_date(&dat[1].input)?,
+            balance,
+        }),
+        None => None,
+    };
+    Ok((account, statement))
+}
+
+// - - - envelope budget - - -
+
+// the amount being changed, filled in with what it is now
+pub fn envelope_form(budgr: &Budgr, month: NaiveDate, category: Option<PurchaseType>) -> Vec<InputData> {
+    let amount = match category {
+        Some(category) => budgr.envelopes.assigned(month, category),
+        None => budgr.envelopes.months.get(&month).map(|m| m.income).unwrap_or(0),
+    };
+    vec![InputData::with_text(amount.to_string()), InputData::default()]
+}
+
+fn envelopes_update(month: &mut NaiveDate, state: &mut TableState, input: &UserInput, area: Rect) -> Option<UITransition> {
+    let inner = area.inner(Margin::new(1, 1));
+    let selected = state.selected().and_then(|i| PurchaseType::ALL.get(i)).copied();
+    match input {
+        UserInput::Esc => return Some(UITransition::ExitLayer),
+        UserInput::NextSelect => state.select_next(),
+        UserInput::PrevSelect => state.select_previous(),
+        UserInput::Next => *month = Period::Month.next_start(*month),
+        UserInput::Prev => *month = *month - Months::new(1),
+        UserInput::Submit => return Some(UITransition::EditEnvelope(*month, Some(selected?))),
+        UserInput::Income => return Some(UITransition::EditEnvelope(*month, None)),
+        // clicking the selected category again assigns to it
+        UserInput::Click(x, y) if click_row(state, inner, 2, 2, PurchaseType::ALL.len(), *x, *y) => {
+            let selected = state.selected().and_then(|i| PurchaseType::ALL.get(i)).copied();
+            return Some(UITransition::EditEnvelope(*month, Some(selected?)));
+        }
+        _ => {}
+    }
+    fit_table(state, PurchaseType::ALL.len(), inner, 2, 2);
+    None
+}
+
+fn envelopes_view(frame: &mut Frame, area: Rect, month: NaiveDate, state: &TableState, budgr: &Budgr, theme: &Theme) {
+    let budget = budgr.envelope_budget(month);
+    let header = ["category", "rolled over", "assigned", "spent", "available"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(theme.header())
+        .height(2);
+
+    let rows = budget.envelopes.iter().enumerate().map(|(i, envelope)| {
+        // overspent envelopes stand out
+        let available = match envelope.available < 0 {
+            true => Cell::from(envelope.available.to_string()).style(theme.error()),
+            false => Cell::from(envelope.available.to_string()),
+        };
+        Row::new([
+            Cell::from(envelope.category.to_string()),
+            Cell::from(envelope.rolled_over.to_string()),
+            Cell::from(envelope.assigned.to_string()),
+            Cell::from(envelope.spent.to_string()),
+            available,
+        ])
+        .style(theme.row(i))
+        .height(2)
+    });
+
+    let to_assign = Span::styled(
+        format!("{} to assign", budget.to_assign),
+        match budget.to_assign < 0 {
+            true => theme.error(),
+            false => theme.accent(),
+        },
+    );
+    let title = Line::from(vec![
+        Span::raw(format!("envelopes for {}, income {}, ", Period::Month.label(budget.month), budget.income)),
+        to_assign,
+    ]);
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(16),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(12),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::bordered()
+            .title(title)
+            .title_bottom("left/right to change month, enter to assign, i to set the income"),
+    )
+    .style(theme.item())
+    .highlight_style(theme.highlight());
+
+    frame.render_stateful_widget(table, area, &mut state.clone());
+}
+
+#[allow(clippy::too_many_arguments)]
+fn envelope_input_update(
+    dat: &mut [InputData],
+    selection_index: &mut usize,
+    month: NaiveDate,
+    category: Option<PurchaseType>,
+    input: &UserInput,
+    area: Rect,
+    budgr: &mut Budgr,
+    status: &mut Status,
+) -> Option<UITransition> {
+    match form_update(dat, selection_index, input, &form_areas(area, 1))? {
+        FormAction::Exit => Some(UITransition::ExitLayer),
+        FormAction::Submit => {
+            let amount = match optional_number(&dat[0].input, "amount") {
+                Ok(amount) => amount.unwrap_or(0),
+                Err(err) => {
+                    status.error(err);
+                    return None;
+                }
+            };
+            match category {
+                Some(category) => budgr.assign(month, category, amount),
+                None => budgr.set_income(month, amount),
+            }
+            status.info("saved");
+            Some(UITransition::ExitLayer)
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn envelope_input_view(
+    frame: &mut Frame,
+    area: Rect,
+    dat: &[InputData],
+    selection_index: usize,
+    month: NaiveDate,
+    category: Option<PurchaseType>,
+    budgr: &Budgr,
+    theme: &Theme,
+) {
+    let month_label = Period::Month.label(month);
+    // how much is left to assign if this amount is saved
+    let to_assign = budgr.envelope_budget(month).to_assign;
+    let title = match category {
+        Some(category) => format!(
+            "assign to {} in {} ({} to assign before this)",
+            category,
+            month_label,
+            to_assign + budgr.envelopes.assigned(month, category)
+        ),
+        None => format!("income for {}", month_label),
+    };
+    let fields = vec![input_field(&title, &dat[0], selection_index == 0, theme)];
+    let check = optional_number(&dat[0].input, "amount").map(|_| ());
+    form_view(frame, &form_areas(area, 1), fields, dat, selection_index, check, theme);
+}
+
+// - - - savings goals - - -
+
+const GOAL_FIELDS: usize = 5;
+// the most goals shown under the logs, the goals screen has the rest
+const GOALS_STRIP_MAX: usize = 4;
+const PROGRESS_BAR_WIDTH: usize = 20;
+
+// a goal form, filled in with the goal being edited
+pub fn goal_form(goal: Option<&Goal>, budgr: &Budgr) -> Vec<InputData> {
+    let Some(goal) = goal else {
+        return vec![InputData::default(); GOAL_FIELDS + 1];
+    };
+    let date = |d: Option<NaiveDate>| d.map(|d| d.to_string()).unwrap_or_default();
+    vec![
+        InputData::with_text(goal.name.clone()),
+        InputData::with_text(goal.target.to_string()),
+        InputData::with_text(date(goal.target_date)),
+        InputData::with_text(goal_link_name(goal.link, budgr)),
+        InputData::with_text(date(goal.since)),
+        InputData::default(),
+    ]
+}
+
+// the log's name or the category
+fn goal_link_name(link: GoalLink, budgr: &Budgr) -> String {
+    match link {
+        GoalLink::Log(id) => budgr.log(id).map(|log| log.name.clone()).unwrap_or("deleted log".to_string()),
+        GoalLink::Category(category) => category.to_string(),
+    }
+}
+
+// a bar of full and empty blocks
+fn progress_bar(fraction: f64, width: usize) -> String {
+    let full = (fraction * width as f64).round() as usize;
+    format!("{}{}", "█".repeat(full), "░".repeat(width - full.min(width)))
+}
+
+// what it takes to make the target date, or when the goal will be reached at the recent rate
+fn goal_outlook(goal: &Goal, progress: &GoalProgress) -> String {
+    if progress.is_reached() {
+        return "reached".to_string();
+    }
+    let projected = match (progress.projected, progress.on_track(goal)) {
+        (Some(date), Some(false)) => format!("late, done by {}", date),
+        (Some(date), _) => format!("done by {}", date),
+        (None, _) => "nothing saved lately".to_string(),
+    };
+    match progress.required_monthly {
+        Some(monthly) => format!("needs {}/month, {}", monthly, projected),
+        None => projected,
+    }
+}
+
+// the logs screen keeps a few lines at the bottom for goals when there are any and room for them
+fn goals_strip(area: Rect, budgr: &Budgr) -> (Rect, Option<Rect>) {
+    if budgr.goals.goals.is_empty() || area.height < DETAIL_MIN_HEIGHT {
+        return (area, None);
+    }
+    let height = budgr.goals.goals.len().min(GOALS_STRIP_MAX) as u16 + 2;
+    let [rest, goals] = Layout::vertical([Constraint::Min(0), Constraint::Length(height)]).areas(area);
+    (rest, Some(goals))
+}
+
+// one line per goal for the logs screen
+fn goals_summary<'a>(budgr: &Budgr, theme: &Theme) -> Paragraph<'a> {
+    let lines = budgr
+        .goals
+        .goals
+        .iter()
+        .take(GOALS_STRIP_MAX)
+        .map(|goal| {
+            let progress = budgr.goal_progress(goal);
+            Line::from(vec![
+                Span::raw(format!("{:<20} ", goal.name)),
+                Span::styled(progress_bar(progress.fraction, PROGRESS_BAR_WIDTH), theme.accent()),
+                Span::raw(format!(
+                    " {:>3.0}%  {}/{}  {}",
+                    progress.fraction * 100.0,
+                    progress.saved,
+                    goal.target,
+                    goal_outlook(goal, &progress)
+                )),
+            ])
+        })
+        .collect::<Vec<Line>>();
+    let title = match budgr.goals.goals.len() {
+        n if n > GOALS_STRIP_MAX => format!("goals ({} more on the goals screen)", n - GOALS_STRIP_MAX),
+        _ => "goals".to_string(),
+    };
+    Paragraph::new(lines).block(pane(title, false, theme))
+}
+
+fn goals_update(state: &mut TableState, input: &UserInput, area: Rect, budgr: &mut Budgr, status: &mut Status) -> Option<UITransition> {
+    let len = budgr.goals.goals.len();
+    let inner = area.inner(Margin::new(1, 1));
+    match input {
+        UserInput::Esc => return Some(UITransition::ExitLayer),
+        UserInput::NextSelect => state.select_next(),
+        UserInput::PrevSelect => state.select_previous(),
+        UserInput::Add => return Some(UITransition::EditGoal(None)),
+        UserInput::Submit => return Some(UITransition::EditGoal(Some(state.selected()?))),
+        // clicking the selected goal again edits it
+        UserInput::Click(x, y) if click_row(state, inner, 2, 2, len, *x, *y) => {
+            return Some(UITransition::EditGoal(Some(state.selected()?)));
+        }
+        UserInput::Delete => {
+            let result = budgr.remove_goal(state.selected()?);
+            status.report(result, "deleted goal");
+        }
+        _ => {}
+    }
+    fit_table(state, budgr.goals.goals.len(), inner, 2, 2);
+    None
+}
+
+fn goals_view(frame: &mut Frame, area: Rect, state: &TableState, budgr: &Budgr, theme: &Theme) {
+    if budgr.goals.goals.is_empty() {
+        let text = "no goals yet, press a to add one. a goal counts what goes into a log, like a holiday-fund log, or into a category";
+        frame.render_widget(Paragraph::new(text).style(theme.item()), area);
+        return;
+    }
+
+    let header = ["goal", "progress", "saved", "by", "outlook"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(theme.header())
+        .height(2);
+
+    let rows = budgr.goals.goals.iter().enumerate().map(|(i, goal)| {
+        let progress = budgr.goal_progress(goal);
+        // what the goal counts goes under its name, the recent rate under the outlook
+        let outlook = Text::from(vec![
+            Line::from(goal_outlook(goal, &progress)),
+            Line::from(format!("{}/month lately", progress.recent_monthly)),
+        ]);
+        let outlook = match progress.on_track(goal) {
+            Some(false) => outlook.style(theme.error()),
+            _ => outlook,
+        };
+        Row::new([
+            Cell::from(format!("{}\n{}", goal.name, goal_link_name(goal.link, budgr))),
+            Cell::from(Line::from(vec![
+                Span::styled(progress_bar(progress.fraction, PROGRESS_BAR_WIDTH), theme.accent()),
+                Span::raw(format!(" {:.0}%", progress.fraction * 100.0)),
+            ])),
+            Cell::from(format!("{}\nof {}", progress.saved, goal.target)),
+            Cell::from(format_date(goal.target_date)),
+            Cell::from(outlook),
+        ])
+        .style(theme.row(i))
+        .height(2)
+    });
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(20),
+            Constraint::Length(PROGRESS_BAR_WIDTH as u16 + 5),
+            Constraint::Length(10),
+            Constraint::Length(12),
+            Constraint::Min(30),
+        ],
+    )
+    .header(header)
+    .block(Block::bordered().title("savings goals").title_bottom("a to add a goal, enter to edit"))
+    .style(theme.item())
+    .highlight_style(theme.highlight());
+
+    frame.render_stateful_widget(table, area, &mut state.clone());
+}
+
+fn goal_input_update(
+    dat: &mut [InputData],
+    selection_index: &mut usize,
+    goal: Option<usize>,
+    input: &UserInput,
+    area: Rect,
+    budgr: &mut Budgr,
+    status: &mut Status,
+) -> Option<UITransition> {
+    match form_update(dat, selection_index, input, &form_areas(area, GOAL_FIELDS))? {
+        FormAction::Exit => Some(UITransition::ExitLayer),
+        FormAction::Submit => {
+            let result = parse_goal_input(dat, budgr).and_then(|g| budgr.save_goal(goal, g));
+            let saved = result.is_ok();
+            status.report(result, "saved goal");
+            saved.then_some(UITransition::ExitLayer)
+        }
+    }
+}
+
+fn goal_input_view(frame: &mut Frame, area: Rect, dat: &[InputData], selection_index: usize, budgr: &Budgr, theme: &Theme) {
+    let titles = [
+        "name",
+        "target amount",
+        "target date (YYYY-MM-DD, optional)",
+        "counts what goes into (a log name or a category)",
+        "counting from (YYYY-MM-DD, empty for everything)",
+    ];
+    let fields = titles
+        .into_iter()
+        .enumerate()
+        .map(|(i, title)| input_field(title, &dat[i], selection_index == i, theme))
+        .collect::<Vec<Paragraph>>();
+    let check = parse_goal_input(dat, budgr).map(|_| ());
+    form_view(frame, &form_areas(area, GOAL_FIELDS), fields, dat, selection_index, check, theme);
+}
+
+// a log with the typed name wins over a category with the same name
+fn parse_goal_input(dat: &[InputData], budgr: &Budgr) -> Result<Goal, String> {
+    let optional_date = |s: &str| match s.trim() {
+        "" => Ok(None),
+        date => parse_date(date).map(Some),
+    };
+    let link = match dat[3].input.trim() {
+        "" => return Err("say which log or category the goal counts".to_string()),
+        name => match budgr.logs.iter().find(|log| log.name == name) {
+            Some(log) => GoalLink::Log(log.id),
+            None => GoalLink::Category(
+                name.parse::<PurchaseType>()
+                    .map_err(|_| format!("no log or category called {}", name))?,
+            ),
+        },
+    };
+    let name = dat[0].input.trim().to_string();
+    if name.is_empty() {
+        return Err("a goal needs a name".to_string());
+    }
+    Ok(Goal {
+        name,
+        target: dat[1]
+            .input
+            .trim()
+            .parse::<i64>()
+            .map_err(|_| "target must be a whole number".to_string())?,
+        target_date: optional_date(&dat[2].input)?,
+        link,
+        since: optional_date(&dat[4].input)?,
+    })
+}
+
+// - - - debts - - -
+
+const DEBT_FIELDS: usize = 8;
+
+// a debt form, filled in with the debt being edited
+pub fn debt_form(debt: Option<&Debt>, budgr: &Budgr) -> Vec<InputData> {
+    let Some(debt) = debt else {
+        return vec![InputData::default(); DEBT_FIELDS + 1];
+    };
+    vec![
+        InputData::with_text(debt.name.clone()),
+        InputData::with_text(debt.kind.to_string()),
+        InputData::with_text(debt.principal.to_string()),
+        InputData::with_text(debt.rate.to_string()),
+        InputData::with_text(debt.payment.to_string()),
+        InputData::with_text(debt.schedule.to_string()),
+        InputData::with_text(debt.start.to_string()),
+        InputData::with_text(budgr.log(debt.log).map(|log| log.name.clone()).unwrap_or_default()),
+        InputData::default(),
+    ]
+}
+
+// a payment starts at the scheduled amount, the extra each month at what it is now
+pub fn debt_payment_form(budgr: &Budgr, debt: Option<usize>) -> Vec<InputData> {
+    match debt.and_then(|i| budgr.debts.debts.get(i)) {
+        Some(debt) => vec![
+            InputData::with_text(debt.payment.to_string()),
+            InputData::default(),
+            InputData::default(),
+        ],
+        None => vec![
+            InputData::with_text(budgr.debts.extra_monthly.to_string()),
+            InputData::default(),
+        ],
+    }
+}
+
+// the debts on top, the selected one's amortization table in the middle, payoff plans below
+fn debts_areas(area: Rect) -> [Rect; 3] {
+    let plans = Strategy::ALL.len() as u16 + 2;
+    Layout::vertical([Ratio(1, 3), Constraint::Min(0), Constraint::Length(plans)]).areas(area)
+}
+
+// when the last scheduled payment is due, or why there isn't one
+fn paid_off_by(debt: &Debt, budgr: &Budgr) -> String {
+    match (budgr.outstanding(debt), budgr.remaining_schedule(debt).last()) {
+        (0, _) => "paid off".to_string(),
+        (_, Some(row)) => row.date.to_string(),
+        (_, None) => "never".to_string(),
+    }
+}
+
+fn debts_update(state: &mut TableState, input: &UserInput, area: Rect, budgr: &mut Budgr, status: &mut Status) -> Option<UITransition> {
+    let len = budgr.debts.debts.len();
+    let [list_area, ..] = debts_areas(area);
+    let inner = list_area.inner(Margin::new(1, 1));
+    match input {
+        UserInput::Esc => return Some(UITransition::ExitLayer),
+        UserInput::NextSelect => state.select_next(),
+        UserInput::PrevSelect => state.select_previous(),
+        UserInput::Add => return Some(UITransition::EditDebt(None)),
+        UserInput::Submit => return Some(UITransition::EditDebt(Some(state.selected()?))),
+        UserInput::Pay => return Some(UITransition::PayDebt(Some(state.selected()?))),
+        UserInput::ExtraPayment => return Some(UITransition::PayDebt(None)),
+        // clicking the selected debt again edits it
+        UserInput::Click(x, y) if click_row(state, inner, 1, 1, len, *x, *y) => {
+            return Some(UITransition::EditDebt(Some(state.selected()?)));
+        }
+        UserInput::Delete => {
+            let result = budgr.remove_debt(state.selected()?);
+            status.report(result, "deleted debt, its payments are still in their log");
+        }
+        _ => {}
+    }
+    fit_table(state, budgr.debts.debts.len(), inner, 1, 1);
+    None
+}
+
+fn debts_view(frame: &mut Frame, area: Rect, state: &TableState, budgr: &Budgr, theme: &Theme) {
+    if budgr.debts.debts.is_empty() {
+        let text = "no debts yet, press a to add one. payments are the purchases in the debt's payments log";
+        frame.render_widget(Paragraph::new(text).style(theme.item()), area);
+        return;
+    }
+    let [list_area, schedule_area, plans_area] = debts_areas(area);
+
+    let header = ["debt", "kind", "principal", "rate", "payment", "outstanding", "paid off by"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(theme.header());
+    let rows = budgr.debts.debts.iter().enumerate().map(|(i, debt)| {
+        [
+            debt.name.clone(),
+            debt.kind.to_string(),
+            debt.principal.to_string(),
+            format!("{}%", debt.rate),
+            format!("{} {}", debt.payment, debt.schedule),
+            budgr.outstanding(debt).to_string(),
+            paid_off_by(debt, budgr),
+        ]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(theme.row(i))
+    });
+    let owed = budgr
+        .debts
+        .debts
+        .iter()
+        .filter(|d| d.kind == DebtKind::Borrowed)
+        .map(|d| budgr.outstanding(d))
+        .sum::<i64>();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(20),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Length(18),
+            Constraint::Length(12),
+            Constraint::Length(12),
+        ],
+    )
+    .header(header)
+    .block(
+        pane(format!("debts, {} owed altogether", owed), true, theme)
+            .title_bottom("a to add a debt, p to record a payment, i to set the extra paid each month"),
+    )
+    .style(theme.item())
+    .highlight_style(theme.highlight());
+    frame.render_stateful_widget(table, list_area, &mut state.clone());
+
+    let plans = Strategy::ALL
+        .into_iter()
+        .map(|strategy| {
+            let plan = budgr.payoff_plan(strategy);
+            let order = plan
+                .paid_off
+                .iter()
+                .map(|(name, date)| format!("{} {}", name, Period::Month.label(*date)))
+                .collect::<Vec<String>>()
+                .join(", ");
+            let debt_free = match plan.debt_free {
+                Some(date) => Span::raw(format!("debt free {}, ", Period::Month.label(date))),
+                None => Span::styled("never debt free at these payments, ", theme.error()),
+            };
+            Line::from(vec![
+                Span::styled(format!("{:<10}", strategy.to_string()), theme.accent()),
+                debt_free,
+                Span::raw(format!("{} interest: {}", plan.total_interest, order)),
+            ])
+        })
+        .collect::<Vec<Line>>();
+    let plans_title = format!("payoff plans with {} extra each month", budgr.debts.extra_monthly);
+    frame.render_widget(Paragraph::new(plans).block(pane(plans_title, false, theme)), plans_area);
+
+    let Some(debt) = state.selected().and_then(|i| budgr.debts.debts.get(i)) else {
+        return;
+    };
+    schedule_view(frame, schedule_area, debt, budgr, theme);
+}
+
+// the scheduled payments, with the ones already due muted and the next one in view
+fn schedule_view(frame: &mut Frame, area: Rect, debt: &Debt, budgr: &Budgr, theme: &Theme) {
+    let schedule = debt.amortization();
+    let title = format!(
+        "{} amortization, {} paid, {} outstanding",
+        debt.name,
+        budgr.debt_payments(debt).iter().map(|(_, amount)| amount).sum::<i64>(),
+        budgr.outstanding(debt)
+    );
+    if schedule.is_empty() {
+        let text = "the payment doesn't cover the interest, so this never gets paid off";
+        frame.render_widget(Paragraph::new(text).style(theme.error()).block(pane(title, false, theme)), area);
+        return;
+    }
+
+    let header = ["due", "payment", "interest", "principal", "balance"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(theme.header());
+    let today = today();
+    let rows = schedule.iter().enumerate().map(|(i, row)| {
+        [
+            row.date.to_string(),
+            row.payment.to_string(),
+            row.interest.to_string(),
+            row.principal.to_string(),
+            row.balance.to_string(),
+        ]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(match row.date <= today {
+            true => theme.muted_row(i),
+            false => theme.row(i),
+        })
+    });
+    let next = schedule.iter().position(|row| row.date > today).unwrap_or(schedule.len());
+    let mut schedule_state = TableState::new().with_offset(next.saturating_sub(1));
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(12),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(12),
+        ],
+    )
+    .header(header)
+    .block(pane(title, false, theme))
+    .style(theme.item());
+    frame.render_stateful_widget(table, area, &mut schedule_state);
+}
+
+fn debt_input_update(
+    dat: &mut [InputData],
+    selection_index: &mut usize,
+    debt: Option<usize>,
+    input: &UserInput,
+    area: Rect,
+    budgr: &mut Budgr,
+    status: &mut Status,
+) -> Option<UITransition> {
+    match form_update(dat, selection_index, input, &form_areas(area, DEBT_FIELDS))? {
+        FormAction::Exit => Some(UITransition::ExitLayer),
+        FormAction::Submit => {
+            let result = parse_debt_input(dat, budgr).and_then(|d| budgr.save_debt(debt, d));
+            let saved = result.is_ok();
+            status.report(result, "saved debt");
+            saved.then_some(UITransition::ExitLayer)
+        }
+    }
+}
+
+fn debt_input_view(frame: &mut Frame, area: Rect, dat: &[InputData], selection_index: usize, budgr: &Budgr, theme: &Theme) {
+    let kind_title = format!(
+        "kind ({}, empty for Borrowed)",
+        DebtKind::ALL.map(|k| k.to_string()).join("/")
+    );
+    let schedule_title = format!(
+        "payment schedule ({}, empty for Monthly)",
+        Schedule::ALL.map(|s| s.to_string()).join("/")
+    );
+    let titles = [
+        "name",
+        kind_title.as_str(),
+        "principal",
+        "interest rate (% a year)",
+        "payment",
+        schedule_title.as_str(),
+        "borrowed on (YYYY-MM-DD, empty for today)",
+        "payments log (every purchase in it is a payment)",
+    ];
+    let fields = titles
+        .into_iter()
+        .enumerate()
+        .map(|(i, title)| input_field(title, &dat[i], selection_index == i, theme))
+        .collect::<Vec<Paragraph>>();
+    let check = parse_debt_input(dat, budgr).map(|_| ());
+    form_view(frame, &form_areas(area, DEBT_FIELDS), fields, dat, selection_index, check, theme);
+}
+
+fn parse_debt_input(dat: &[InputData], budgr: &Budgr) -> Result<Debt, String> {
+    let name = dat[0].input.trim().to_string();
+    if name.is_empty() {
+        return Err("a debt needs a name".to_string());
+    }
+    let number = |s: &str, what: &str| optional_number(s, what)?.ok_or(format!("{} is needed", what));
+    let log = match dat[7].input.trim() {
+        "" => return Err("say which log the payments go in".to_string()),
+        log => budgr
+            .logs
+            .iter()
+            .find(|l| l.name == log)
+            .ok_or(format!("no log called {}, make it first", log))?,
+    };
+    Ok(Debt {
+        name,
+        kind: match dat[1].input.trim() {
+            "" => DebtKind::default(),
+            kind => kind.parse()?,
+        },
+        principal: number(&dat[2].input, "principal")?,
+        rate: match dat[3].input.trim().trim_end_matches('%') {
+            "" => 0.0,
+            // "inf" and "NaN" parse as floats too
+            rate => match rate.trim().parse::<f64>() {
+                Ok(rate) if rate.is_finite() && rate >= 0.0 => rate,
+                Ok(rate) if rate < 0.0 => return Err("interest rate can't be negative".to_string()),
+                _ => return Err("interest rate must be a number, like 6.9".to_string()),
+            },
+        },
+        payment: number(&dat[4].input, "payment")?,
+        schedule: match dat[5].input.trim() {
+            "" => Schedule::default(),
+            schedule => schedule.parse()?,
+        },
+        start: parse_date(&dat[6].input)?,
+        log: log.id,
+        repaid: Vec::new(),
+    })
+}
+
+fn debt_payment_input_update(
+    dat: &mut [InputData],
+    selection_index: &mut usize,
+    debt: Option<usize>,
+    input: &UserInput,
+    area: Rect,
+    budgr: &mut Budgr,
+    status: &mut Status,
+) -> Option<UITransition> {
+    let fields = dat.len() - 1;
+    match form_update(dat, selection_index, input, &form_areas(area, fields))? {
+        FormAction::Exit => Some(UITransition::ExitLayer),
+        FormAction::Submit => {
+            let result = match debt {
+                Some(i) => parse_payment(dat)
+                    .and_then(|(amount, date)| budgr.record_debt_payment(i, amount, date))
+                    .map(|_| ()),
+                None => optional_number(&dat[0].input, "extra payment")
+                    .and_then(|amount| budgr.set_extra_monthly(amount.unwrap_or(0))),
+            };
+            let saved = result.is_ok();
+            status.report(result, "saved");
+            saved.then_some(UITransition::ExitLayer)
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn debt_payment_input_view(
+    frame: &mut Frame,
+    area: Rect,
+    dat: &[InputData],
+    selection_index: usize,
+    debt: Option<usize>,
+    budgr: &Budgr,
+    theme: &Theme,
+) {
+    let fields = dat.len() - 1;
+    let (titles, check) = match debt.and_then(|i| budgr.debts.debts.get(i)) {
+        Some(debt) => (
+            vec![
+                format!("payment on {} ({} outstanding)", debt.name, budgr.outstanding(debt)),
+                "date (YYYY-MM-DD, empty for today)".to_string(),
+            ],
+            parse_payment(dat).map(|_| ()),
+        ),
+        None => (
+            vec!["extra paid each month on top of the scheduled payments".to_string()],
+            optional_number(&dat[0].input, "extra payment").map(|_| ()),
+        ),
+    };
+    let fields_view = titles
+        .iter()
+        .enumerate()
+        .map(|(i, title)| input_field(title, &dat[i], selection_index == i, theme))
+        .collect::<Vec<Paragraph>>();
+    form_view(frame, &form_areas(area, fields), fields_view, dat, selection_index, check, theme);
+}
+
+fn parse_payment(dat: &[InputData]) -> Result<(i64, NaiveDate), String> {
+    let amount = optional_number(&dat[0].input, "payment")?.ok_or("payment is needed".to_string())?;
+    Ok((amount, parse_date(&dat[1].input)?))
+}
+
+// - - - shared pieces - - -
+
+// what a form wants done after an input
+enum FormAction {
+    Submit,
+    Exit,
+}
+
+// a column of text fields with a submit button after them, dat and areas have one entry per field plus the button
+fn form_areas(area: Rect, fields: usize) -> Vec<Rect> {
+    Layout::vertical(vec![Constraint::Length(3); fields + 1]).split(area).to_vec()
+}
+
+// moving between the fields of a form and typing into them
+fn form_update(dat: &mut [InputData], selection_index: &mut usize, input: &UserInput, areas: &[Rect]) -> Option<FormAction> {
+    let submit_index = dat.len() - 1;
+    match input {
+        // clicking a field selects it, clicking the submit button submits
+        UserInput::Click(x, y) => {
+            let clicked = clicked_area(areas, *x, *y)?;
+            *selection_index = clicked;
+            if clicked == submit_index {
+                return Some(FormAction::Submit);
+            }
+        }
+        UserInput::NextSelect | UserInput::NextField if *selection_index < submit_index => *selection_index += 1,
+        UserInput::PrevSelect | UserInput::PrevField if *selection_index > 0 => *selection_index -= 1,
+        // wrap around with tab like most forms
+        UserInput::NextField => *selection_index = 0,
+        UserInput::PrevField => *selection_index = submit_index,
+        input if *selection_index < submit_index && dat[*selection_index].handle(input) => (),
+        UserInput::Esc => return Some(FormAction::Exit),
+        UserInput::Submit if *selection_index == submit_index => return Some(FormAction::Submit),
+        _ => (),
+    }
+    None
+}
+
+// draw the fields and the submit button, which says what is wrong with the input if anything
+fn form_view(
+    frame: &mut Frame,
+    areas: &[Rect],
+    fields: Vec<Paragraph>,
+    dat: &[InputData],
+    selection_index: usize,
+    check: Result<(), String>,
+    theme: &Theme,
+) {
+    let submit_index = dat.len() - 1;
+    let submit_text = match check {
+        Ok(_) => "Submit".to_string(),
+        Err(err) => format!("Submit ({})", err),
+    };
+    let mut submit_button = Paragraph::new(submit_text).style(theme.item());
+    if selection_index == submit_index {
+        submit_button = submit_button.style(theme.highlight()).add_modifier(Modifier::BOLD);
+    }
+
+    for (field, area) in fields.into_iter().zip(areas) {
+        frame.render_widget(field, *area);
+    }
+    frame.render_widget(submit_button, areas[submit_index]);
+    if selection_index < submit_index {
+        frame.set_cursor_position(field_cursor(areas[selection_index], &dat[selection_index]));
+    }
+}
+
+// an account typed in by name, empty for none
+fn parse_account(s: &str, budgr: &Budgr) -> Result<Option<AccountId>, String> {
+    match s.trim() {
+        "" => Ok(None),
+        name => budgr
+            .accounts
+            .find(name)
+            .map(|a| Some(a.id))
+            .ok_or(format!("no account called {}", name)),
+    }
+}
+
+// paths typed or pasted in, separated by commas. file managers often paste quoted paths
+fn parse_paths(s: &str) -> Result<Vec<PathBuf>, String> {
+    s.split(',')
+        .map(|path| path.trim().trim_matches(|c| c == '\'' || c == '"'))
+        .filter(|path| !path.is_empty())
+        .map(|path| {
+            let path = match path.strip_prefix("~/") {
+                Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+                None => PathBuf::from(path),
+            };
+            match path.is_file() {
+                true => Ok(path),
+                false => Err(format!("no such file: {}", path.display())),
+            }
+        })
+        .collect()
+}
+
+// hand every attachment of a purchase to the system's default app for it
+fn open_attachments(purchase: &Purchase) -> Result<(), String> {
+    if purchase.attachments.is_empty() {
+        return Err("nothing attached to this purchase".to_string());
+    }
+    for attachment in purchase.attachments.iter() {
+        open::that_detached(attachment.path())
+            .map_err(|err| format!("could not open {}: {}", attachment.name, err))?;
+    }
+    Ok(())
+}
+
+// an empty field is no number
+fn optional_number(s: &str, what: &str) -> Result<Option<i64>, String> {
+    match s.trim() {
+        "" => Ok(None),
+        s => s.parse::<i64>().map(Some).map_err(|_| format!("{} must be a whole number", what)),
+    }
+}
+
+// keep the selection on a row that exists and scrolled into view, area is where the
+// header and rows are drawn. ratatui does this while drawing, but view draws from a copy
+fn fit_table(state: &mut TableState, len: usize, area: Rect, header_height: u16, row_height: u16) {
+    let Some(selected) = state.selected() else {
+        return;
+    };
+    if len == 0 {
+        state.select(None);
+        return;
+    }
+    let selected = selected.min(len - 1);
+    state.select(Some(selected));
+
+    let visible = (area.height.saturating_sub(header_height) / row_height).max(1) as usize;
+    let offset = state.offset().min(len.saturating_sub(visible));
+    *state.offset_mut() = match offset {
+        offset if selected < offset => selected,
+        offset if selected >= offset + visible => selected + 1 - visible,
+        offset => offset,
+    };
+}
+
+// terminals narrower than this only get room for one pane
+const SPLIT_MIN_WIDTH: u16 = 100;
+// and shorter than this leave out the detail panel
+const DETAIL_MIN_HEIGHT: u16 = 24;
+const DETAIL_HEIGHT: u16 = 8;
+// height of a log or purchase row
+const ROW_HEIGHT: u16 = 2;
+
+// where the logs, the purchases and the detail panel go on wide terminals
+struct Panes {
+    logs: Rect,
+    purchases: Rect,
+    detail: Option<Rect>,
+}
+
+// None when the terminal is too narrow to split
+fn split_panes(area: Rect) -> Option<Panes> {
+    if area.width < SPLIT_MIN_WIDTH {
+        return None;
+    }
+    let [logs, right] = Layout::horizontal([Ratio(2, 5), Ratio(3, 5)]).areas(area);
+    if area.height < DETAIL_MIN_HEIGHT {
+        return Some(Panes { logs, purchases: right, detail: None });
+    }
+    let [purchases, detail] = Layout::vertical([Constraint::Min(0), Constraint::Length(DETAIL_HEIGHT)]).areas(right);
+    Some(Panes { logs, purchases, detail: Some(detail) })
+}
+
+// the focused pane gets the accent coloured border
+fn pane<'a>(title: String, focused: bool, theme: &Theme) -> Block<'a> {
+    let border = match focused {
+        true => theme.accent(),
+        false => theme.item().fg(theme.muted),
+    };
+    Block::bordered().title(title).border_style(border).style(theme.item())
+}
+
+// every log in the given order, the compact version for the side pane leaves out
+// what the detail panel already shows
+fn logs_table<'a>(budgr: &Budgr, order: &[usize], sorts: &ViewSorts, compact: bool, theme: &Theme) -> Table<'a> {
+    let titles = sorts.logs.header(&["log name", "num purchases", "total expense", "last 12 weeks"]);
+    let columns: &[usize] = match compact {
+        true => &[0, 2],
+        false => &[0, 1, 2, 3],
+    };
+
+    let header = columns
+        .iter()
+        .map(|c| Cell::from(titles[*c].clone()))
+        .collect::<Row>()
+        .style(theme.header())
+        .height(2);
+
+    let rows = order.iter().map(|i| &budgr.logs[*i]).enumerate().map(|(i, log)| {
+        let item = [
+            log.name.clone(),
+            log.purchases.len().to_string(),
+            log.get_total().to_string(),
+            log_sparkline(log),
+        ];
+        columns
+            .iter()
+            .map(|c| Cell::from(Text::from(item[*c].clone())))
+            .collect::<Row>()
+            .style(theme.muted_row(i))
+            .height(ROW_HEIGHT)
+    });
+
+    let widths = [
+        Constraint::Fill(1),
+        Constraint::Length(14),
+        Constraint::Length(14),
+        Constraint::Length(SPARKLINE_WEEKS as u16),
+    ];
+    Table::new(rows, columns.iter().map(|c| widths[*c])).header(header)
+}
+
+fn purchases_table<'a>(log: &Log, sorts: &ViewSorts, theme: &Theme) -> Table<'a> {
+    let header = sorts
+        .purchases
+        .header(&["name", "purchase type", "date", "cost"])
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(theme.header())
+        .height(2);
+
+    let rows = sorts
+        .purchase_order(log)
+        .into_iter()
+        .map(|i| &log.purchases[i])
+        .enumerate()
+        .map(|(i, p)| {
+            // a clip marks purchases with receipts, a tick cleared ones and a lock reconciled ones,
+            // tags go on the second line under the name
+            let mut name = p.name.clone();
+            if !p.attachments.is_empty() {
+                name.push_str(" 📎");
+            }
+            match p.cleared {
+                Cleared::Uncleared => (),
+                Cleared::Cleared => name.push_str(" ✓"),
+                Cleared::Reconciled => name.push_str(" 🔒"),
+            }
+            if !p.tags.is_empty() {
+                name = format!("{}\n{}", name, tag_list(&p.tags));
+            }
+            let item = [
+                name,
+                category_label(p),
+                format_date(p.date),
+                p.cost.to_string(),
+            ];
+            item.into_iter()
+                .map(|content| Cell::from(Text::from(content)))
+                .collect::<Row>()
+                .style(theme.row(i))
+                .height(ROW_HEIGHT)
+        });
+
+    Table::new(
+        rows,
+        [
+            Constraint::Fill(1),
+            Constraint::Length(15),
+            Constraint::Length(12),
+            Constraint::Length(10),
+        ],
+    )
+    .header(header)
+}
+
+// totals for a whole log
+fn log_detail<'a>(log: &Log, theme: &Theme) -> Paragraph<'a> {
+    let categories = log
+        .category_totals()
+        .iter()
+        .map(|(category, total)| format!("{} {}", category, total))
+        .collect::<Vec<String>>()
+        .join("  ");
+    let tags = log
+        .tag_totals()
+        .iter()
+        .map(|(tag, total)| format!("#{} {}", tag, total))
+        .collect::<Vec<String>>()
+        .join("  ");
+    let last = log.purchases.iter().filter_map(|p| p.date).max();
+    let lines = vec![
+        Line::from(format!("{} purchases, {} in total", log.purchases.len(), log.get_total())),
+        Line::from(format!("by category: {}", categories)),
+        Line::from(format!("by tag: {}", tags)),
+        Line::from(format!("last purchase: {}", format_date(last))),
+        Line::from(vec![
+            Span::raw("last 12 weeks: "),
+            Span::styled(log_sparkline(log), theme.accent()),
+        ]),
+    ];
+    Paragraph::new(lines).block(pane(format!("{} summary", log.name), false, theme))
+}
+
+// everything about one purchase
+fn purchase_detail<'a>(log: &Log, purchase: &Purchase, budgr: &Budgr, theme: &Theme) -> Paragraph<'a> {
+    let share = match log.get_total() {
+        0 => 0.0,
+        total => purchase.cost as f64 / total as f64 * 100.0,
+    };
+    let category = match purchase.splits.is_empty() {
+        true => format!("category: {}", purchase.category),
+        false => format!(
+            "split: {}",
+            purchase.splits.iter().map(Split::to_string).collect::<Vec<String>>().join(", ")
+        ),
+    };
+    let mut lines = vec![
+        Line::from(vec![
+            Span::raw(match &purchase.payee {
+                Some(payee) if payee != &purchase.name => format!("{} ({})", purchase.name, payee),
+                _ => purchase.name.clone(),
+            }),
+            Span::styled(format!("  {}", tag_list(&purchase.tags)), theme.accent()),
+        ]),
+        Line::from(category),
+        Line::from(match purchase.account {
+            Some(account) => format!("date: {}  paid from: {}", format_date(purchase.date), budgr.accounts.name(account)),
+            None => format!("date: {}", format_date(purchase.date)),
+        }),
+        Line::from(match purchase.cleared {
+            Cleared::Uncleared => format!("cost: {} ({:.1}% of {})", purchase.cost, share, log.name),
+            cleared => format!("cost: {} ({:.1}% of {}), {}", purchase.cost, share, log.name, cleared),
+        }),
+    ];
+    if !purchase.attachments.is_empty() {
+        let names = purchase.attachments.iter().map(|a| a.name.as_str()).collect::<Vec<&str>>();
+        lines.push(Line::from(format!("📎 {} (o to open)", names.join(", "))));
+    }
+    lines.extend(purchase.note.lines().map(|line| Line::from(line.to_string())));
+    Paragraph::new(lines).block(pane("purchase".to_string(), false, theme))
+}
+
+// tags as #one #two
+fn tag_list(tags: &[String]) -> String {
+    tags.iter().map(|tag| format!("#{}", tag)).collect::<Vec<String>>().join(" ")
+}
+
+// the category column, split purchases show their main category and how many others there are
+fn category_label(purchase: &Purchase) -> String {
+    match purchase.splits.len() {
+        0 | 1 => purchase.category.to_string(),
+        n => format!("{} +{}", purchase.category, n - 1),
+    }
+}
+
+// attempt to create a new purchase, invalid input just leaves the form open
+fn submit_purchase(purchase: &Result<PurchaseForm, String>, log: LogId, budgr: &mut Budgr, status: &mut Status) {
+    let form = match purchase {
+        Ok(form) => form,
+        Err(err) => return status.error(err.clone()),
+    };
+    let result = budgr
+        .add_purchase(log, form.name.clone(), form.cost, form.category, form.date)
+        .and_then(|id| {
+            let filled = fill_in_purchase(form, log, id, budgr);
+            // storing an attachment can still fail, take the purchase back out so the form
+            // can be fixed and submitted again without adding it twice
+            if filled.is_err() {
+                if let Some(index) = budgr.log_index(log) {
+                    budgr.logs[index].purchases.retain(|p| p.id != id);
+                }
+            }
+            filled
+        });
+    status.report(result, "added purchase");
+}
+
+// everything on the form past what add_purchase takes
+fn fill_in_purchase(form: &PurchaseForm, log: LogId, id: PurchaseId, budgr: &mut Budgr) -> Result<(), String> {
+    if !form.splits.is_empty() {
+        budgr.split_purchase(log, id, form.splits.clone())?;
+    }
+    if !form.tags.is_empty() || !form.note.is_empty() {
+        budgr.annotate_purchase(log, id, form.tags.clone(), form.note.clone())?;
+    }
+    for path in form.attachments.iter() {
+        budgr.attach(log, id, path)?;
+    }
+    if form.account.is_some() {
+        budgr.assign_account(log, id, form.account)?;
+    }
+    // rules only fill in what was left empty
+    budgr.apply_rules(log, id)
+}
+
+// everything typed into the purchase form, checked
+fn parse_purchase_input(dat: &[InputData], budgr: &Budgr) -> Result<PurchaseForm, String> {
+    let cost = dat[1]
+        .input
+        .trim()
+        .parse::<i64>()
+        .map_err(|_| "cost must be a whole number".to_string())?;
+    let category = match dat[2].input.trim() {
+        "" => PurchaseType::default(),
+        category => category.parse::<PurchaseType>()?,
+    };
+    let date = parse_date(&dat[3].input)?;
+    let splits = parse_splits(&dat[SPLITS_FIELD].input)?;
+    check_splits(cost, &splits)?;
+
+    Ok(PurchaseForm {
+        name: dat[0].input.clone(),
+        cost,
+        category,
+        date,
+        splits,
+        tags: parse_tags(&dat[TAGS_FIELD].input),
+        note: dat[NOTE_FIELD].input.trim_end().to_string(),
+        attachments: parse_paths(&dat[ATTACHMENTS_FIELD].input)?,
+        account: parse_account(&dat[ACCOUNT_FIELD].input, budgr)?,
+    })
+}
+
+// a bordered text box, highlighted when selected
+fn input_field<'a>(title: &'a str, dat: &'a InputData, selected: bool, theme: &Theme) -> Paragraph<'a> {
+    // selected text is drawn un-highlighted so it stands out from the highlighted box
+    let text = match dat.all_selected {
+        true => Text::styled(dat.input.as_str(), theme.item()),
+        false => Text::raw(dat.input.as_str()),
+    };
+    let field = Paragraph::new(text).block(Block::bordered().title(title));
+    match selected {
+        true => field.style(theme.highlight()).add_modifier(Modifier::BOLD),
+        false => field.style(theme.item()),
+    }
+}
+
+// where the terminal cursor goes for a bordered text box, multiline ones can have it on a later line
+fn field_cursor(area: Rect, dat: &InputData) -> Position {
+    let before = dat.input.chars().take(dat.character_pos).collect::<String>();
+    let line = before.matches('\n').count() as u16;
+    let column = before.rsplit('\n').next().unwrap_or_default().chars().count() as u16;
+    let x = area.x + 1 + column;
+    let y = area.y + 1 + line;
+    Position::new(x.min(area.right().saturating_sub(2)), y.min(area.bottom().saturating_sub(2)))
+}
+
+// weekly spending over the last SPARKLINE_WEEKS weeks, drawn with block characters
+const SPARKLINE_WEEKS: i64 = 12;
+
+fn log_sparkline(log: &Log) -> String {
+    let start = today() - chrono::Duration::weeks(SPARKLINE_WEEKS);
+    let mut weeks = [0i64; SPARKLINE_WEEKS as usize];
+    for (date, total) in log.daily_totals().range(start..) {
+        let week = ((*date - start).num_days() / 7).min(SPARKLINE_WEEKS - 1) as usize;
+        weeks[week] += total;
+    }
+
+    let max = weeks.iter().copied().max().unwrap_or(0).max(1);
+    let levels = [
+        symbols::bar::ONE_EIGHTH,
+        symbols::bar::ONE_QUARTER,
+        symbols::bar::THREE_EIGHTHS,
+        symbols::bar::HALF,
+        symbols::bar::FIVE_EIGHTHS,
+        symbols::bar::THREE_QUARTERS,
+        symbols::bar::SEVEN_EIGHTHS,
+        symbols::bar::FULL,
+    ];
+    weeks
+        .iter()
+        .map(|week| match *week {
+            w if w <= 0 => " ",
+            w => levels[((w * 7) / max) as usize],
+        })
+        .collect()
+}
+
+// select the table row under a click, area is where the header and rows are drawn
+// returns true if the row was already selected, so a second click can open it
+fn click_row(state: &mut TableState, area: Rect, header_height: u16, row_height: u16, len: usize, column: u16, row: u16) -> bool {
+    if !area.contains(Position::new(column, row)) || row < area.y + header_height {
+        return false;
+    }
+    let i = state.offset() + ((row - area.y - header_height) / row_height) as usize;
+    if i >= len {
+        return false;
+    }
+    let again = state.selected() == Some(i);
+    state.select(Some(i));
+    again
+}
+
+// index of the area under a click
+fn clicked_area(areas: &[Rect], column: u16, row: u16) -> Option<usize> {
+    areas.iter().position(|area| area.contains(Position::new(column, row)))
+}
+
+fn format_date(date: Option<NaiveDate>) -> String {
+    match date {
+        Some(date) => date.format("%Y-%m-%d").to_string(),
+        None => "-".to_string(),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::status::MessageKind;
+    use ratatui::{backend::TestBackend, buffer::Buffer, Terminal};
+
+    // the buffer's characters, one string per row without trailing spaces
+    pub(crate) fn text(buffer: &Buffer) -> Vec<String> {
+        (0..buffer.area.height)
+            .map(|y| {
+                let row = (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect::<String>();
+                row.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    // a food log with two purchases and an empty rent log, dated long enough ago that
+    // nothing depends on today
+    fn budgr() -> (Budgr, LogId) {
+        let mut budgr = Budgr::new();
+        let food = budgr.new_log("food".to_string()).unwrap();
+        let date = |s| parse_date(s).unwrap();
+        budgr.add_purchase(food, "tesco".to_string(), 40, PurchaseType::Groceries, date("2025-03-03")).unwrap();
+        budgr.add_purchase(food, "cinema".to_string(), 12, PurchaseType::Leisure, date("2025-03-10")).unwrap();
+        budgr.new_log("rent".to_string()).unwrap();
+        (budgr, food)
+    }
+
+    fn render(state: &UIState, budgr: &Budgr, width: u16, height: u16) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|frame| state.view(frame, frame.area(), budgr, &ViewSorts::default(), &Theme::monochrome()))
+            .unwrap();
+        text(terminal.backend().buffer())
+    }
+
+    fn update(state: &mut UIState, input: UserInput, budgr: &mut Budgr, sorts: &mut ViewSorts, status: &mut Status) -> Option<UITransition> {
+        state.update(&input, Rect::new(0, 0, 50, 10), budgr, sorts, status)
+    }
+
+    #[test]
+    fn log_list_snapshot() {
+        let (budgr, _) = budgr();
+        let state = UIState::BudgrShow {
+            state: TableState::new().with_selected(Some(0)),
+        };
+        assert_eq!(
+            render(&state, &budgr, 50, 8),
+            [
+                "┌logs────────────────────────────────────────────┐",
+                "│log n num purchases  total expense  last 12 week│",
+                "│                                                │",
+                "│food  2              52                         │",
+                "│                                                │",
+                "│rent  0              0                          │",
+                "│                                                │",
+                "└────────────────────────────────────────────────┘",
+            ]
+        );
+    }
+
+    #[test]
+    fn log_snapshot() {
+        let (budgr, food) = budgr();
+        let state = UIState::LogShow {
+            log: food,
+            state: TableState::new(),
+        };
+        assert_eq!(
+            render(&state, &budgr, 50, 8),
+            [
+                "┌food────────────────────────────────────────────┐",
+                "│name     purchase type   date         cost      │",
+                "│                                                │",
+                "│tesco    Groceries       2025-03-03   40        │",
+                "│                                                │",
+                "│cinema   Leisure         2025-03-10   12        │",
+                "│                                                │",
+                "└────────────────────────────────────────────────┘",
+            ]
+        );
+    }
+
+    #[test]
+    fn purchase_form_snapshot_says_what_is_wrong() {
+        let (budgr, food) = budgr();
+        let mut form = purchase_form();
+        form[0] = InputData::with_text("bread".to_string());
+        form[1] = InputData::with_text("x".to_string());
+        let state = UIState::PurchaseInput {
+            input_data: form,
+            selection_index: 1,
+            log: food,
+        };
+        let screen = render(&state, &budgr, 50, 36);
+        assert_eq!(
+            screen[..9],
+            [
+                "┌name────────────────────────────────────────────┐",
+                "│bread                                           │",
+                "└────────────────────────────────────────────────┘",
+                "┌cost────────────────────────────────────────────┐",
+                "│x                                               │",
+                "└────────────────────────────────────────────────┘",
+                "┌category (Groceries/Leisure/Bill/School/Other)──┐",
+                "│                                                │",
+                "└────────────────────────────────────────────────┘",
+            ]
+        );
+        assert_eq!(screen[29], "Submit (cost must be a whole number)");
+    }
+
+    #[test]
+    fn opening_a_log_follows_the_sort() {
+        let (mut budgr, _) = budgr();
+        let rent = budgr.logs[1].id;
+        let mut sorts = ViewSorts::default();
+        sorts.logs.cycle_key();
+        sorts.logs.reverse();
+        let mut state = UIState::BudgrShow {
+            state: TableState::new().with_selected(Some(0)),
+        };
+        let transition = update(&mut state, UserInput::Submit, &mut budgr, &mut sorts, &mut Status::default());
+        assert!(matches!(transition, Some(UITransition::OpenLog(id)) if id == rent));
+    }
+
+    #[test]
+    fn deleting_a_purchase_trashes_it_and_says_so() {
+        let (mut budgr, food) = budgr();
+        let mut status = Status::default();
+        let mut state = UIState::LogShow {
+            log: food,
+            state: TableState::new().with_selected(Some(0)),
+        };
+        let transition = update(&mut state, UserInput::Delete, &mut budgr, &mut ViewSorts::default(), &mut status);
+        assert!(transition.is_none());
+        assert_eq!(budgr.log(food).unwrap().purchases.len(), 1);
+        assert_eq!(budgr.trash.purchases[0].purchase.name, "tesco");
+        let message = status.last().unwrap();
+        assert_eq!((message.text.as_str(), message.kind), ("moved purchase to trash", MessageKind::Info));
+    }
+
+    #[test]
+    fn reconciled_purchases_are_not_deleted() {
+        let (mut budgr, food) = budgr();
+        budgr.logs[0].purchases[0].cleared = Cleared::Reconciled;
+        let mut status = Status::default();
+        let mut state = UIState::LogShow {
+            log: food,
+            state: TableState::new().with_selected(Some(0)),
+        };
+        update(&mut state, UserInput::Delete, &mut budgr, &mut ViewSorts::default(), &mut status);
+        assert_eq!(budgr.log(food).unwrap().purchases.len(), 2);
+        assert!(budgr.trash.is_empty());
+        assert_eq!(status.last().unwrap().kind, MessageKind::Error);
+    }
+
+    #[test]
+    fn purchase_form_adds_only_valid_purchases() {
+        let (mut budgr, food) = budgr();
+        let mut status = Status::default();
+        let mut form = purchase_form();
+        form[0] = InputData::with_text("bread".to_string());
+        form[1] = InputData::with_text("x".to_string());
+        let mut state = UIState::PurchaseInput {
+            input_data: form,
+            selection_index: PURCHASE_FIELDS,
+            log: food,
+        };
+
+        update(&mut state, UserInput::Submit, &mut budgr, &mut ViewSorts::default(), &mut status);
+        assert_eq!(budgr.log(food).unwrap().purchases.len(), 2);
+        assert_eq!(status.last().unwrap().text, "cost must be a whole number");
+
+        if let UIState::PurchaseInput { input_data, .. } = &mut state {
+            input_data[1] = InputData::with_text("3".to_string());
+        }
+        update(&mut state, UserInput::Submit, &mut budgr, &mut ViewSorts::default(), &mut status);
+        let bread = budgr.log(food).unwrap().purchases.last().unwrap();
+        assert_eq!((bread.name.as_str(), bread.cost, bread.date), ("bread", 3, Some(today())));
+        assert_eq!(status.last().unwrap().text, "added purchase");
+
+        let transition = update(&mut state, UserInput::Esc, &mut budgr, &mut ViewSorts::default(), &mut status);
+        assert!(matches!(transition, Some(UITransition::ExitLayer)));
+    }
+
+    #[test]
+    fn a_purchase_that_fails_to_attach_is_not_added() {
+        let (mut budgr, food) = budgr();
+        let mut status = Status::default();
+        let form = PurchaseForm {
+            name: "bread".to_string(),
+            cost: 3,
+            category: PurchaseType::Groceries,
+            date: today(),
+            splits: Vec::new(),
+            tags: Vec::new(),
+            note: String::new(),
+            attachments: vec![PathBuf::from("/no/such/receipt.pdf")],
+            account: None,
+        };
+
+        submit_purchase(&Ok(form), food, &mut budgr, &mut status);
+        assert_eq!(budgr.log(food).unwrap().purchases.len(), 2);
+        assert!(status.last().unwrap().text.starts_with("could not attach /no/such/receipt.pdf"));
+    }
+
+    #[test]
+    fn finishing_a_reconciliation_only_finishes_the_current_account() {
+        let (mut budgr, food) = budgr();
+        let card = Account {
+            name: "card".to_string(),
+            statement: Some(Statement {
+                date: parse_date("2025-03-31").unwrap(),
+                balance: 0,
+            }),
+            ..Default::default()
+        };
+        let bank = Account {
+            id: AccountId::new(),
+            name: "bank".to_string(),
+            ..card.clone()
+        };
+        let (card_id, bank_id) = (card.id, bank.id);
+        budgr.save_account(card).unwrap();
+        budgr.save_account(bank).unwrap();
+        let tesco = budgr.logs[0].purchases[0].id;
+        budgr.assign_account(food, tesco, Some(card_id)).unwrap();
+
+        let reconciling = |budgr: &Budgr| [card_id, bank_id].map(|id| budgr.accounts.get(id).unwrap().statement.is_some());
+        // finish with a row selected, and what it said
+        let finish = |row: usize, budgr: &mut Budgr| {
+            let mut status = Status::default();
+            let mut state = UIState::LogShow {
+                log: food,
+                state: TableState::new().with_selected(Some(row)),
+            };
+            update(&mut state, UserInput::FinishReconcile, budgr, &mut ViewSorts::default(), &mut status);
+            status.last().unwrap().text.clone()
+        };
+
+        // cinema has no account, so there is no telling which one is meant
+        assert_eq!(
+            finish(1, &mut budgr),
+            "more than one account is being reconciled, select a purchase from the one to finish"
+        );
+        assert_eq!(reconciling(&budgr), [true, true]);
+        // tesco was paid by card
+        assert_eq!(finish(0, &mut budgr), "reconciled card, 0 purchases locked");
+        assert_eq!(reconciling(&budgr), [false, true]);
+        // only the bank is left
+        assert_eq!(finish(1, &mut budgr), "reconciled bank, 0 purchases locked");
+        assert_eq!(reconciling(&budgr), [false, false]);
+    }
+
+    #[test]
+    fn debt_form_takes_only_a_real_rate() {
+        let (budgr, _) = budgr();
+        let form = |rate: &str| {
+            let mut form = debt_form(None, &budgr);
+            for (field, text) in [(0, "card"), (2, "1000"), (3, rate), (4, "100"), (7, "food")] {
+                form[field] = InputData::with_text(text.to_string());
+            }
+            parse_debt_input(&form, &budgr).map(|debt| debt.rate)
+        };
+
+        assert_eq!(form("6.9%"), Ok(6.9));
+        assert_eq!(form(""), Ok(0.0));
+        for rate in ["inf", "NaN", "1e400"] {
+            assert_eq!(form(rate), Err("interest rate must be a number, like 6.9".to_string()));
+        }
+        assert_eq!(form("-3"), Err("interest rate can't be negative".to_string()));
+    }
+}
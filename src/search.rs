@@ -0,0 +1,293 @@
+//! Finding purchases across every log.
+
+use crate::log::{Budgr, LogId, Purchase, PurchaseId, PurchaseType};
+use chrono::NaiveDate;
+use regex::{Regex, RegexBuilder};
+use std::str::FromStr;
+
+/// How a query matches purchase names.
+pub enum NameMatch {
+    /// The name contains this, ignoring case. Stored lowercase.
+    Substring(String),
+    /// The name matches this regex.
+    Regex(Regex),
+}
+
+impl NameMatch {
+    /// Text wrapped in slashes (`/elec.*bill/`) is a regex, anything else is a plain substring.
+    /// Both ignore case.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        if s.len() >= 2 && s.starts_with('/') && s.ends_with('/') {
+            RegexBuilder::new(&s[1..s.len() - 1])
+                .case_insensitive(true)
+                .build()
+                .map(NameMatch::Regex)
+                .map_err(|err| format!("invalid regex: {}", err))
+        } else {
+            Ok(NameMatch::Substring(s.to_lowercase()))
+        }
+    }
+
+    /// True if the name matches.
+    pub fn is_match(&self, name: &str) -> bool {
+        match self {
+            NameMatch::Substring(sub) => name.to_lowercase().contains(sub.as_str()),
+            NameMatch::Regex(re) => re.is_match(name),
+        }
+    }
+}
+
+/// A filter on purchases.
+///
+/// Every field that is set has to match, an empty query matches everything.
+#[derive(Default)]
+pub struct PurchaseQuery {
+    /// Match on the purchase name or its payee.
+    pub name: Option<NameMatch>,
+    /// Lowest cost to include.
+    pub min_cost: Option<i64>,
+    /// Highest cost to include.
+    pub max_cost: Option<i64>,
+    /// Only purchases with some of their cost in this category.
+    pub category: Option<PurchaseType>,
+    /// First day to include, purchases without a date never match a date filter.
+    pub from: Option<NaiveDate>,
+    /// Last day to include.
+    pub to: Option<NaiveDate>,
+    /// Only purchases with this tag.
+    pub tag: Option<String>,
+}
+
+/// Where a search hit lives in the budgr.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SearchHit {
+    /// The log the purchase is in.
+    pub log: LogId,
+    /// The purchase itself.
+    pub purchase: PurchaseId,
+}
+
+// empty fields are left unset
+fn parse_field<T: FromStr>(s: &str, what: &str) -> Result<Option<T>, String> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Ok(None);
+    }
+    s.parse::<T>()
+        .map(Some)
+        .map_err(|_| format!("invalid {}: {}", what, s))
+}
+
+impl PurchaseQuery {
+    /// Build a query from the raw text of the search fields, empty fields are left unset.
+    pub fn parse(
+        name: &str,
+        min_cost: &str,
+        max_cost: &str,
+        category: &str,
+        from: &str,
+        to: &str,
+        tag: &str,
+    ) -> Result<Self, String> {
+        let name = match name.trim() {
+            "" => None,
+            name => Some(NameMatch::parse(name)?),
+        };
+        let category = match category.trim() {
+            "" => None,
+            category => Some(category.parse::<PurchaseType>()?),
+        };
+
+        Ok(PurchaseQuery {
+            name,
+            min_cost: parse_field(min_cost, "minimum cost")?,
+            max_cost: parse_field(max_cost, "maximum cost")?,
+            category,
+            from: parse_field(from, "start date")?,
+            to: parse_field(to, "end date")?,
+            tag: parse_field(&tag.to_lowercase(), "tag")?,
+        })
+    }
+
+    /// True if the purchase passes every filter.
+    pub fn matches(&self, purchase: &Purchase) -> bool {
+        if let Some(name) = &self.name {
+            let payee = purchase.payee.as_deref().is_some_and(|payee| name.is_match(payee));
+            if !name.is_match(&purchase.name) && !payee {
+                return false;
+            }
+        }
+        if self.min_cost.is_some_and(|min| purchase.cost < min)
+            || self.max_cost.is_some_and(|max| purchase.cost > max)
+        {
+            return false;
+        }
+        if self.category.is_some_and(|c| !purchase.has_category(c)) {
+            return false;
+        }
+        if self.tag.as_ref().is_some_and(|t| !purchase.has_tag(t)) {
+            return false;
+        }
+        if self.from.is_some() || self.to.is_some() {
+            let Some(date) = purchase.date else {
+                return false;
+            };
+            if self.from.is_some_and(|from| date < from) || self.to.is_some_and(|to| date > to) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl Budgr {
+    /// Look through the purchases of every log.
+    pub fn search(&self, query: &PurchaseQuery) -> Vec<SearchHit> {
+        self.logs
+            .iter()
+            .flat_map(|log| {
+                log.purchases
+                    .iter()
+                    .filter(|p| query.matches(p))
+                    .map(move |p| SearchHit {
+                        log: log.id,
+                        purchase: p.id,
+                    })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::Split;
+    use crate::testing::day;
+
+    fn budgr() -> (Budgr, LogId, LogId) {
+        let mut budgr = Budgr::new();
+        let food = budgr.new_log("food".to_string()).unwrap();
+        let bills = budgr.new_log("bills".to_string()).unwrap();
+        for (log, name, cost, category, date) in [
+            (food, "Tesco", 40, PurchaseType::Groceries, "2025-03-03"),
+            (food, "cinema", 12, PurchaseType::Leisure, "2025-03-10"),
+            (food, "tesco express", 8, PurchaseType::Groceries, "2025-03-20"),
+            (bills, "electricity bill", 60, PurchaseType::Bill, "2025-03-01"),
+            (bills, "elec refund tesco", 5, PurchaseType::Bill, "2025-04-01"),
+        ] {
+            budgr.add_purchase(log, name.to_string(), cost, category, day(date)).unwrap();
+        }
+        (budgr, food, bills)
+    }
+
+    fn query(name: &str, min: &str, max: &str, category: &str, from: &str, to: &str) -> PurchaseQuery {
+        PurchaseQuery::parse(name, min, max, category, from, to, "").unwrap()
+    }
+
+    // the names of the hits, along with the log they were found in
+    fn found(budgr: &Budgr, query: &PurchaseQuery) -> Vec<(LogId, String)> {
+        budgr
+            .search(query)
+            .into_iter()
+            .map(|hit| {
+                let log = budgr.log(hit.log).unwrap();
+                let p = log.purchases.iter().find(|p| p.id == hit.purchase).unwrap();
+                (hit.log, p.name.clone())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn plain_text_is_a_substring_and_slashes_a_regex() {
+        let (budgr, food, bills) = budgr();
+        assert_eq!(
+            found(&budgr, &query("TESCO", "", "", "", "", "")),
+            [
+                (food, "Tesco".to_string()),
+                (food, "tesco express".to_string()),
+                (bills, "elec refund tesco".to_string())
+            ]
+        );
+        assert_eq!(
+            found(&budgr, &query("/^tesco$/", "", "", "", "", "")),
+            [(food, "Tesco".to_string())]
+        );
+        assert_eq!(found(&budgr, &query("/elec.*bill/", "", "", "", "", "")).len(), 1);
+        // without the slashes it is looked for literally
+        assert!(found(&budgr, &query("elec.*bill", "", "", "", "", "")).is_empty());
+    }
+
+    #[test]
+    fn the_payee_is_searched_too() {
+        let (mut budgr, food, _) = budgr();
+        let cinema = budgr.logs[0].purchases[1].id;
+        budgr.purchase_mut(food, cinema).unwrap().payee = Some("Odeon".to_string());
+        assert_eq!(found(&budgr, &query("odeon", "", "", "", "", "")), [(food, "cinema".to_string())]);
+    }
+
+    #[test]
+    fn bad_fields_are_reported() {
+        let parse = |name, min, from| PurchaseQuery::parse(name, min, "", "", from, "", "").err();
+        assert!(parse("/(tesco/", "", "").unwrap().starts_with("invalid regex: "));
+        assert_eq!(parse("", "ten", ""), Some("invalid minimum cost: ten".to_string()));
+        assert_eq!(parse("", "", "march"), Some("invalid start date: march".to_string()));
+        assert_eq!(
+            PurchaseQuery::parse("", "", "", "food", "", "", "").err(),
+            Some("unknown purchase type: food".to_string())
+        );
+        // a lone slash is just text
+        assert!(parse("/", "", "").is_none());
+    }
+
+    #[test]
+    fn cost_and_date_ranges_are_inclusive() {
+        let (mut budgr, food, _) = budgr();
+        let names = |q: &PurchaseQuery| found(&budgr, q).into_iter().map(|(_, n)| n).collect::<Vec<String>>();
+        assert_eq!(names(&query("", "8", "40", "", "", "")), ["Tesco", "cinema", "tesco express"]);
+        assert_eq!(names(&query("", "41", "", "", "", "")), ["electricity bill"]);
+        assert_eq!(names(&query("", "", "", "", "2025-03-03", "2025-03-10")), ["Tesco", "cinema"]);
+        assert_eq!(names(&query("", "", "", "", "2025-03-20", "")), ["tesco express", "elec refund tesco"]);
+
+        budgr.logs[1].purchases[1].date = None;
+        assert_eq!(found(&budgr, &query("", "", "", "", "2025-03-20", "")), [(food, "tesco express".to_string())]);
+        assert_eq!(found(&budgr, &query("refund", "", "", "", "", "")).len(), 1);
+    }
+
+    #[test]
+    fn category_filter_looks_at_splits() {
+        let (mut budgr, food, _) = budgr();
+        assert_eq!(found(&budgr, &query("", "", "", "leisure", "", "")), [(food, "cinema".to_string())]);
+
+        let tesco = budgr.logs[0].purchases[0].id;
+        let splits = vec![
+            Split {
+                category: PurchaseType::Groceries,
+                amount: 30,
+            },
+            Split {
+                category: PurchaseType::Leisure,
+                amount: 10,
+            },
+        ];
+        budgr.split_purchase(food, tesco, splits).unwrap();
+        assert_eq!(
+            found(&budgr, &query("", "", "", "Leisure", "", "")),
+            [(food, "Tesco".to_string()), (food, "cinema".to_string())]
+        );
+    }
+
+    #[test]
+    fn hits_point_at_the_purchase_they_found() {
+        let (budgr, food, bills) = budgr();
+        let hits = budgr.search(&query("elec", "", "", "", "", ""));
+        let ids = budgr.logs[1].purchases.iter().map(|p| p.id).collect::<Vec<PurchaseId>>();
+        assert_eq!(
+            hits,
+            [
+                SearchHit { log: bills, purchase: ids[0] },
+                SearchHit { log: bills, purchase: ids[1] }
+            ]
+        );
+        assert_eq!(budgr.search(&PurchaseQuery::default()).iter().filter(|h| h.log == food).count(), 3);
+    }
+}
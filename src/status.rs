@@ -0,0 +1,70 @@
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+// how long a toast stays on screen
+const TOAST_TIME: Duration = Duration::from_secs(3);
+// older toasts are dropped when more pile up than this
+const MAX_TOASTS: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageKind {
+    Info,
+    Error,
+}
+
+#[derive(Clone, Debug)]
+pub struct Message {
+    pub text: String,
+    pub kind: MessageKind,
+    pub shown_at: Instant,
+}
+
+// feedback for the user, the last message stays in the status bar
+// and every message also pops up as a toast for a few seconds
+#[derive(Default)]
+pub struct Status {
+    last: Option<Message>,
+    toasts: Vec<Message>,
+}
+
+impl Status {
+    pub fn push(&mut self, text: impl Into<String>, kind: MessageKind) {
+        let message = Message {
+            text: text.into(),
+            kind,
+            shown_at: Instant::now(),
+        };
+        self.toasts.push(message.clone());
+        if self.toasts.len() > MAX_TOASTS {
+            self.toasts.remove(0);
+        }
+        self.last = Some(message);
+    }
+
+    pub fn info(&mut self, text: impl Into<String>) {
+        self.push(text, MessageKind::Info);
+    }
+
+    pub fn error(&mut self, text: impl Into<String>) {
+        self.push(text, MessageKind::Error);
+    }
+
+    // show success or the error of a budgr call
+    pub fn report<T, E: Display>(&mut self, result: Result<T, E>, success: &str) {
+        match result {
+            Ok(_) => self.info(success),
+            Err(err) => self.error(err.to_string()),
+        }
+    }
+
+    pub fn last(&self) -> Option<&Message> {
+        self.last.as_ref()
+    }
+
+    // toasts that haven't timed out yet, oldest first
+    pub fn toasts(&self) -> impl Iterator<Item = &Message> {
+        self.toasts
+            .iter()
+            .filter(|t| t.shown_at.elapsed() < TOAST_TIME)
+    }
+}
@@ -0,0 +1,45 @@
+use budgr::logfile;
+use crossterm::event::{
+    DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+};
+use crossterm::execute;
+use ratatui::DefaultTerminal;
+use std::io::stdout;
+use std::panic;
+
+// puts the terminal back to normal when dropped, so every way out of main restores it
+pub struct TerminalGuard;
+
+impl TerminalGuard {
+    pub fn init() -> std::io::Result<(Self, DefaultTerminal)> {
+        let terminal = ratatui::init();
+        // from here on the guard restores the terminal even if enabling the rest fails
+        let guard = TerminalGuard;
+        execute!(stdout(), EnableBracketedPaste, EnableMouseCapture)?;
+        install_panic_hook();
+        Ok((guard, terminal))
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+// safe to call more than once
+pub fn restore() {
+    let _ = execute!(stdout(), DisableBracketedPaste, DisableMouseCapture);
+    ratatui::restore();
+}
+
+// restore the terminal before the panic message is printed, otherwise it ends up
+// garbled in raw mode, and keep a copy of the message in the log file
+fn install_panic_hook() {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore();
+        logfile::write(&format!("panic: {}", info));
+        hook(info);
+    }));
+}
@@ -0,0 +1,14 @@
+//! Helpers shared by the unit tests.
+
+use crate::log::today;
+use chrono::{Months, NaiveDate};
+
+/// A date written as yyyy-mm-dd.
+pub(crate) fn day(date: &str) -> NaiveDate {
+    date.parse().unwrap()
+}
+
+/// The same day of the month, `months` months from today.
+pub(crate) fn months_from_today(months: u32) -> NaiveDate {
+    today().checked_add_months(Months::new(months)).unwrap()
+}
@@ -0,0 +1,508 @@
+use dirs::config_dir;
+use ratatui::style::{
+    palette::tailwind::{RED, SLATE},
+    Color, Modifier, Style,
+};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs::{self, File};
+use std::io::{BufReader, ErrorKind};
+use std::path::{Path, PathBuf};
+
+fn get_theme_dir() -> PathBuf {
+    [config_dir().unwrap(), "budgr/themes/".into()]
+        .iter()
+        .collect()
+}
+
+// every colour the ui uses
+// colours are written like ratatui parses them: "red", "lightblue", "#1e293b" or "236"
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Theme {
+    // named after the file when left out
+    #[serde(default)]
+    pub name: String,
+    pub text: Color,
+    pub background: Color,
+    pub muted: Color,
+    pub header_fg: Color,
+    pub header_bg: Color,
+    // table rows alternate between these two
+    pub row_even: Color,
+    pub row_odd: Color,
+    // the highlight is drawn reversed, so highlight_bg ends up as the text colour
+    pub highlight_fg: Color,
+    pub highlight_bg: Color,
+    // charts and other decoration
+    pub accent: Color,
+    // error messages
+    pub error: Color,
+}
+
+// missing fields in a user theme fall back to the dark theme
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Theme {
+            name: "dark".to_string(),
+            text: SLATE.c100,
+            background: SLATE.c900,
+            muted: SLATE.c400,
+            header_fg: SLATE.c100,
+            header_bg: SLATE.c950,
+            row_even: SLATE.c800,
+            row_odd: SLATE.c600,
+            highlight_fg: SLATE.c900,
+            highlight_bg: SLATE.c100,
+            accent: SLATE.c300,
+            error: RED.c400,
+        }
+    }
+
+    pub fn light() -> Self {
+        Theme {
+            name: "light".to_string(),
+            text: SLATE.c900,
+            background: SLATE.c50,
+            muted: SLATE.c600,
+            header_fg: SLATE.c900,
+            header_bg: SLATE.c300,
+            row_even: SLATE.c100,
+            row_odd: SLATE.c200,
+            highlight_fg: SLATE.c700,
+            highlight_bg: SLATE.c50,
+            accent: SLATE.c500,
+            error: RED.c700,
+        }
+    }
+
+    // only uses the 16 basic colours so it looks the same on every terminal
+    pub fn high_contrast() -> Self {
+        Theme {
+            name: "high-contrast".to_string(),
+            text: Color::White,
+            background: Color::Black,
+            muted: Color::White,
+            header_fg: Color::Black,
+            header_bg: Color::Yellow,
+            row_even: Color::Black,
+            row_odd: Color::Black,
+            highlight_fg: Color::Yellow,
+            highlight_bg: Color::Black,
+            accent: Color::Yellow,
+            error: Color::LightRed,
+        }
+    }
+
+    // the terminal's own colours, highlighting only by reversing
+    pub fn monochrome() -> Self {
+        Theme {
+            name: "monochrome".to_string(),
+            text: Color::Reset,
+            background: Color::Reset,
+            muted: Color::Reset,
+            header_fg: Color::Reset,
+            header_bg: Color::Reset,
+            row_even: Color::Reset,
+            row_odd: Color::Reset,
+            highlight_fg: Color::Reset,
+            highlight_bg: Color::Reset,
+            accent: Color::Reset,
+            error: Color::Reset,
+        }
+    }
+
+    // - - - styles used by the frontend - - -
+
+    pub fn item(&self) -> Style {
+        Style::new().fg(self.text).bg(self.background)
+    }
+
+    pub fn header(&self) -> Style {
+        Style::new()
+            .fg(self.header_fg)
+            .bg(self.header_bg)
+            .add_modifier(Modifier::BOLD)
+    }
+
+    pub fn row(&self, i: usize) -> Style {
+        let bg = match i % 2 {
+            0 => self.row_even,
+            _ => self.row_odd,
+        };
+        Style::new().fg(self.text).bg(bg)
+    }
+
+    pub fn muted_row(&self, i: usize) -> Style {
+        self.row(i).fg(self.muted)
+    }
+
+    pub fn highlight(&self) -> Style {
+        Style::new()
+            .add_modifier(Modifier::REVERSED)
+            .fg(self.highlight_fg)
+            .bg(self.highlight_bg)
+    }
+
+    pub fn accent(&self) -> Style {
+        Style::new().fg(self.accent)
+    }
+
+    // bold as well so errors still stand out without colour
+    pub fn error(&self) -> Style {
+        Style::new()
+            .fg(self.error)
+            .bg(self.background)
+            .add_modifier(Modifier::BOLD)
+    }
+
+    // text drawn on top of the accent colour, like bar chart values
+    pub fn on_accent(&self) -> Style {
+        Style::new().fg(self.background).bg(self.accent)
+    }
+
+    // squash every colour down to what the terminal can show
+    pub fn for_terminal(&self, support: ColorSupport) -> Theme {
+        let c = |colour: Color| support.convert(colour);
+        Theme {
+            name: self.name.clone(),
+            text: c(self.text),
+            background: c(self.background),
+            muted: c(self.muted),
+            header_fg: c(self.header_fg),
+            header_bg: c(self.header_bg),
+            row_even: c(self.row_even),
+            row_odd: c(self.row_odd),
+            highlight_fg: c(self.highlight_fg),
+            highlight_bg: c(self.highlight_bg),
+            accent: c(self.accent),
+            error: c(self.error),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorSupport {
+    None,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+// the 16 basic colours with their usual rgb values, used to find the closest match
+const ANSI_16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (128, 0, 0)),
+    (Color::Green, (0, 128, 0)),
+    (Color::Yellow, (128, 128, 0)),
+    (Color::Blue, (0, 0, 128)),
+    (Color::Magenta, (128, 0, 128)),
+    (Color::Cyan, (0, 128, 128)),
+    (Color::Gray, (192, 192, 192)),
+    (Color::DarkGray, (128, 128, 128)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (0, 0, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+// rgb value of a colour from the 256 colour palette
+fn indexed_to_rgb(i: u8) -> (u8, u8, u8) {
+    match i {
+        0..=15 => ANSI_16[i as usize].1,
+        16..=231 => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + n * 40 };
+            let i = i - 16;
+            (level(i / 36), level((i / 6) % 6), level(i % 6))
+        }
+        _ => {
+            let grey = 8 + (i - 232) * 10;
+            (grey, grey, grey)
+        }
+    }
+}
+
+fn rgb_to_256(r: u8, g: u8, b: u8) -> u8 {
+    // greys get the finer greyscale ramp
+    if r == g && g == b {
+        return match r {
+            0..=7 => 16,
+            248..=255 => 231,
+            _ => 232 + (r - 8) / 10,
+        };
+    }
+    let level = |c: u8| ((c as u16 * 5 + 127) / 255) as u8;
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+fn rgb_to_16(r: u8, g: u8, b: u8) -> Color {
+    let distance = |(r2, g2, b2): (u8, u8, u8)| {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2);
+        d(r, r2) + d(g, g2) + d(b, b2)
+    };
+    ANSI_16
+        .iter()
+        .min_by_key(|(_, rgb)| distance(*rgb))
+        .map(|(colour, _)| *colour)
+        .unwrap()
+}
+
+impl ColorSupport {
+    // NO_COLOR (https://no-color.org) turns colour off completely,
+    // otherwise guess from COLORTERM and TERM like most terminal programs do
+    pub fn detect() -> Self {
+        if env::var("NO_COLOR").is_ok_and(|v| !v.is_empty()) {
+            return ColorSupport::None;
+        }
+        if env::var("COLORTERM").is_ok_and(|v| v == "truecolor" || v == "24bit") {
+            return ColorSupport::TrueColor;
+        }
+        match env::var("TERM") {
+            Ok(term) if term == "dumb" => ColorSupport::None,
+            Ok(term) if term.contains("256color") => ColorSupport::Ansi256,
+            _ => ColorSupport::Ansi16,
+        }
+    }
+
+    pub fn convert(&self, colour: Color) -> Color {
+        match (self, colour) {
+            (ColorSupport::None, _) => Color::Reset,
+            (ColorSupport::TrueColor, colour) => colour,
+            (ColorSupport::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(rgb_to_256(r, g, b)),
+            (ColorSupport::Ansi16, Color::Rgb(r, g, b)) => rgb_to_16(r, g, b),
+            (ColorSupport::Ansi16, Color::Indexed(i)) => {
+                let (r, g, b) = indexed_to_rgb(i);
+                rgb_to_16(r, g, b)
+            }
+            (_, colour) => colour,
+        }
+    }
+}
+
+// the built in themes plus any found in ~/.config/budgr/themes/*.json
+pub struct Themes {
+    themes: Vec<Theme>,
+    current: usize,
+    support: ColorSupport,
+    // the current theme converted for the terminal
+    active: Theme,
+    // set once the user switches theme, so a theme forced by NO_COLOR is never saved
+    changed: bool,
+    // theme files that couldn't be read, shown on the status bar at startup
+    skipped: Vec<String>,
+}
+
+impl Themes {
+    pub fn load(name: &str) -> Self {
+        Themes::load_from(&get_theme_dir(), name, ColorSupport::detect())
+    }
+
+    fn load_from(dir: &Path, name: &str, support: ColorSupport) -> Self {
+        if support == ColorSupport::None {
+            return Themes::plain();
+        }
+
+        let mut themes = vec![Theme::dark(), Theme::light(), Theme::high_contrast()];
+        let (user_themes, skipped) = read_themes(dir);
+        for theme in user_themes {
+            // a user theme with the same name replaces the built in one
+            themes.retain(|t| t.name != theme.name);
+            themes.push(theme);
+        }
+
+        let current = themes.iter().position(|t| t.name == name).unwrap_or(0);
+        let active = themes[current].for_terminal(support);
+        Themes {
+            themes,
+            current,
+            support,
+            active,
+            changed: false,
+            skipped,
+        }
+    }
+
+    // only the monochrome theme, for terminals without colour and for tests
+    pub fn plain() -> Self {
+        let active = Theme::monochrome();
+        Themes {
+            themes: vec![active.clone()],
+            current: 0,
+            support: ColorSupport::None,
+            active,
+            changed: false,
+            skipped: Vec::new(),
+        }
+    }
+
+    pub fn skipped(&self) -> &[String] {
+        &self.skipped
+    }
+
+    pub fn current(&self) -> &Theme {
+        &self.active
+    }
+
+    pub fn next(&mut self) {
+        if self.themes.len() < 2 {
+            return;
+        }
+        self.current = (self.current + 1) % self.themes.len();
+        self.active = self.themes[self.current].for_terminal(self.support);
+        self.changed = true;
+    }
+
+    // the name of the theme the user switched to, if they did
+    pub fn picked(&self) -> Option<&str> {
+        match self.changed {
+            true => Some(&self.themes[self.current].name),
+            false => None,
+        }
+    }
+}
+
+// every *.json theme in the folder in file name order, and why the ones that couldn't be read were skipped
+fn read_themes(dir: &Path) -> (Vec<Theme>, Vec<String>) {
+    let mut paths = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect::<Vec<PathBuf>>(),
+        Err(err) if err.kind() == ErrorKind::NotFound => return (Vec::new(), Vec::new()),
+        Err(err) => return (Vec::new(), vec![format!("could not read themes in {}: {}", dir.display(), err)]),
+    };
+    paths.sort();
+
+    let mut themes = Vec::new();
+    let mut skipped = Vec::new();
+    for path in paths {
+        match read_theme(&path) {
+            Ok(theme) => themes.push(theme),
+            Err(err) => skipped.push(format!("skipped theme {}: {}", path.display(), err)),
+        }
+    }
+    (themes, skipped)
+}
+
+fn read_theme(path: &Path) -> Result<Theme, String> {
+    let file = File::open(path).map_err(|err| err.to_string())?;
+    let mut theme: Theme = serde_json::from_reader(BufReader::new(file)).map_err(|err| err.to_string())?;
+    // without a name it would take the dark theme's and replace it
+    if theme.name.is_empty() {
+        theme.name = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+    }
+    Ok(theme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexed_colours_map_to_their_rgb_values() {
+        assert_eq!(indexed_to_rgb(1), (128, 0, 0));
+        assert_eq!(indexed_to_rgb(16), (0, 0, 0));
+        assert_eq!(indexed_to_rgb(21), (0, 0, 255));
+        assert_eq!(indexed_to_rgb(196), (255, 0, 0));
+        assert_eq!(indexed_to_rgb(231), (255, 255, 255));
+        assert_eq!(indexed_to_rgb(232), (8, 8, 8));
+        assert_eq!(indexed_to_rgb(255), (238, 238, 238));
+    }
+
+    #[test]
+    fn rgb_goes_to_the_nearest_of_256() {
+        // greys use the greyscale ramp, its ends fall back to black and white in the cube
+        assert_eq!(rgb_to_256(0, 0, 0), 16);
+        assert_eq!(rgb_to_256(128, 128, 128), 244);
+        assert_eq!(rgb_to_256(255, 255, 255), 231);
+        assert_eq!(rgb_to_256(255, 0, 0), 196);
+        assert_eq!(rgb_to_256(0, 0, 255), 21);
+        assert_eq!(rgb_to_256(95, 135, 175), 109);
+        for i in [16, 21, 196, 231] {
+            let (r, g, b) = indexed_to_rgb(i);
+            assert_eq!(rgb_to_256(r, g, b), i);
+        }
+    }
+
+    #[test]
+    fn rgb_goes_to_the_nearest_of_16() {
+        assert_eq!(rgb_to_16(250, 10, 10), Color::LightRed);
+        assert_eq!(rgb_to_16(120, 0, 0), Color::Red);
+        assert_eq!(rgb_to_16(200, 200, 200), Color::Gray);
+        assert_eq!(rgb_to_16(10, 10, 10), Color::Black);
+        assert_eq!(rgb_to_16(0, 120, 130), Color::Cyan);
+    }
+
+    #[test]
+    fn colours_are_squashed_to_what_the_terminal_shows() {
+        let rgb = Color::Rgb(255, 0, 0);
+        assert_eq!(ColorSupport::TrueColor.convert(rgb), rgb);
+        assert_eq!(ColorSupport::Ansi256.convert(rgb), Color::Indexed(196));
+        assert_eq!(ColorSupport::Ansi16.convert(rgb), Color::LightRed);
+        assert_eq!(ColorSupport::Ansi16.convert(Color::Indexed(21)), Color::LightBlue);
+        assert_eq!(ColorSupport::Ansi256.convert(Color::Indexed(21)), Color::Indexed(21));
+        for support in [ColorSupport::Ansi16, ColorSupport::Ansi256, ColorSupport::TrueColor] {
+            assert_eq!(support.convert(Color::Blue), Color::Blue);
+        }
+        for colour in [rgb, Color::Indexed(21), Color::Blue] {
+            assert_eq!(ColorSupport::None.convert(colour), Color::Reset);
+        }
+    }
+
+    #[test]
+    fn no_color_turns_every_theme_off() {
+        // nothing else in the tests reads these
+        let saved = ["NO_COLOR", "COLORTERM"].map(|var| (var, env::var(var)));
+        env::set_var("COLORTERM", "truecolor");
+        env::set_var("NO_COLOR", "");
+        assert_eq!(ColorSupport::detect(), ColorSupport::TrueColor);
+        env::set_var("NO_COLOR", "1");
+        assert_eq!(ColorSupport::detect(), ColorSupport::None);
+        for (var, value) in saved {
+            match value {
+                Ok(value) => env::set_var(var, value),
+                Err(_) => env::remove_var(var),
+            }
+        }
+
+        let mut themes = Themes::load_from(Path::new("/no/such/themes"), "light", ColorSupport::None);
+        assert_eq!(themes.current().name, "monochrome");
+        themes.next();
+        assert_eq!(themes.current().name, "monochrome");
+        assert_eq!(themes.picked(), None);
+    }
+
+    #[test]
+    fn user_themes_are_named_after_their_file_and_bad_ones_skipped() {
+        let dir = env::temp_dir().join(format!("budgr-themes-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("broken.json"), "{ not json").unwrap();
+        fs::write(dir.join("light.json"), r#"{ "name": "light", "accent": "red" }"#).unwrap();
+        fs::write(dir.join("ocean.json"), r##"{ "text": "#000080" }"##).unwrap();
+        fs::write(dir.join("notes.txt"), "not a theme").unwrap();
+
+        let themes = Themes::load_from(&dir, "ocean", ColorSupport::TrueColor);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let names = themes.themes.iter().map(|t| t.name.as_str()).collect::<Vec<&str>>();
+        assert_eq!(names, ["dark", "high-contrast", "light", "ocean"]);
+        assert_eq!(themes.themes[2].accent, Color::Red);
+        // the built in dark theme is still there and fills in what ocean leaves out
+        assert_eq!(themes.themes[0].text, Theme::dark().text);
+        assert_eq!(themes.current().name, "ocean");
+        assert_eq!(themes.current().text, Color::Rgb(0, 0, 128));
+        assert_eq!(themes.current().accent, Theme::dark().accent);
+        assert_eq!(themes.skipped().len(), 1);
+        assert!(themes.skipped()[0].starts_with(&format!("skipped theme {}", dir.join("broken.json").display())));
+    }
+}
@@ -0,0 +1,153 @@
+//! Deleted logs and purchases, kept until they are restored or purged.
+
+use crate::log::{Log, LogId, Purchase};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 60 * 60 * 24;
+
+/// Seconds since the unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// A deleted log.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TrashedLog {
+    /// The log as it was when deleted.
+    pub log: Log,
+    /// When it was deleted, in seconds since the unix epoch.
+    pub deleted_at: u64,
+}
+
+/// A purchase deleted from a log.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TrashedPurchase {
+    /// Id of the log the purchase was removed from, used to put it back.
+    #[serde(default)]
+    pub log_id: LogId,
+    /// Name of the log the purchase was removed from, used to put it back.
+    pub log_name: String,
+    /// The purchase as it was when deleted.
+    pub purchase: Purchase,
+    /// When it was deleted, in seconds since the unix epoch.
+    pub deleted_at: u64,
+}
+
+/// Deleted logs and purchases wait here until they are restored or purged.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Trash {
+    /// Deleted logs, oldest first.
+    pub logs: Vec<TrashedLog>,
+    /// Deleted purchases, oldest first.
+    pub purchases: Vec<TrashedPurchase>,
+}
+
+impl Trash {
+    /// True if nothing has been deleted.
+    pub fn is_empty(&self) -> bool {
+        self.logs.is_empty() && self.purchases.is_empty()
+    }
+
+    /// Trashed logs and purchases together.
+    pub fn len(&self) -> usize {
+        self.logs.len() + self.purchases.len()
+    }
+
+    /// Trash a log, timestamped now.
+    pub fn add_log(&mut self, log: Log) {
+        self.logs.push(TrashedLog {
+            log,
+            deleted_at: now(),
+        });
+    }
+
+    /// Trash a purchase from a log, timestamped now.
+    pub fn add_purchase(&mut self, log_id: LogId, log_name: String, purchase: Purchase) {
+        self.purchases.push(TrashedPurchase {
+            log_id,
+            log_name,
+            purchase,
+            deleted_at: now(),
+        });
+    }
+
+    /// Permanently drop everything that has been in the trash for longer than `max_age_days`,
+    /// 0 keeps everything. Returns how many entries were purged.
+    pub fn purge_older_than(&mut self, max_age_days: u64) -> usize {
+        if max_age_days == 0 {
+            return 0;
+        }
+
+        let cutoff = now().saturating_sub(max_age_days * SECONDS_PER_DAY);
+        let before = self.len();
+        self.logs.retain(|l| l.deleted_at >= cutoff);
+        self.purchases.retain(|p| p.deleted_at >= cutoff);
+
+        before - self.len()
+    }
+}
+
+/// Human readable time since deletion, e.g. "3d ago".
+pub fn format_age(deleted_at: u64) -> String {
+    let secs = now().saturating_sub(deleted_at);
+    match secs {
+        _ if secs < 60 => "just now".to_string(),
+        _ if secs < 60 * 60 => format!("{}m ago", secs / 60),
+        _ if secs < SECONDS_PER_DAY => format!("{}h ago", secs / (60 * 60)),
+        _ => format!("{}d ago", secs / SECONDS_PER_DAY),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log::{today, PurchaseType};
+
+    // a trash with one log and one purchase, deleted the given number of days ago
+    fn trash_aged(log_days: u64, purchase_days: u64) -> Trash {
+        let mut log = Log::default();
+        let id = log.add_purchase("bread".to_string(), 3, PurchaseType::Groceries, today());
+        let purchase = log.purchases.remove(log.purchase_index(id).unwrap());
+
+        let mut trash = Trash::default();
+        trash.add_log(log.clone());
+        trash.add_purchase(log.id, log.name, purchase);
+        trash.logs[0].deleted_at = now() - log_days * SECONDS_PER_DAY;
+        trash.purchases[0].deleted_at = now() - purchase_days * SECONDS_PER_DAY;
+        trash
+    }
+
+    #[test]
+    fn purge_drops_only_entries_past_the_age() {
+        let mut trash = trash_aged(40, 10);
+        assert_eq!(trash.purge_older_than(30), 1);
+        assert!(trash.logs.is_empty());
+        assert_eq!(trash.purchases.len(), 1);
+    }
+
+    #[test]
+    fn purge_keeps_entries_exactly_at_the_age() {
+        let mut trash = trash_aged(30, 30);
+        assert_eq!(trash.purge_older_than(30), 0);
+        assert_eq!(trash.len(), 2);
+    }
+
+    #[test]
+    fn zero_days_keeps_everything() {
+        let mut trash = trash_aged(10_000, 10_000);
+        assert_eq!(trash.purge_older_than(0), 0);
+        assert_eq!(trash.len(), 2);
+    }
+
+    #[test]
+    fn format_age_rounds_down_to_the_largest_unit() {
+        assert_eq!(format_age(now()), "just now");
+        assert_eq!(format_age(now() - 5 * 60), "5m ago");
+        assert_eq!(format_age(now() - 3 * 60 * 60), "3h ago");
+        assert_eq!(format_age(now() - 2 * SECONDS_PER_DAY), "2d ago");
+    }
+}
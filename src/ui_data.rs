@@ -1,51 +1,391 @@
-use crate::frontend::UI;
-use ratatui::style::{palette::tailwind::SLATE, Color};
-use ratatui::widgets::{ListState, TableState};
+use budgr::account::AccountId;
+use budgr::log::{Budgr, Log, LogId, PurchaseId, PurchaseType};
+use budgr::report::Period;
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::cmp::Ordering;
+use ratatui::widgets::TableState;
 
-const TEXT_FG_COLOR: Color = SLATE.c200;
-const BG_COLOR: Color = SLATE.c900;
 
 pub enum UIState {
     BudgrShow { state: TableState },
-    LogShow { index: usize, state: TableState },
-    PurchaseInput { input_data: Vec<InputData>, selection_index: usize, log_index: usize },
-    Quit,
+    LogShow { log: LogId, state: TableState },
+    PurchaseInput { input_data: Vec<InputData>, selection_index: usize, log: LogId },
+    TrashShow { state: TableState },
+    Search { input_data: Vec<InputData>, selection_index: usize, state: TableState },
+    // None shows every log together
+    Stats { log: Option<LogId> },
+    Report { log: Option<LogId>, period: Period, state: TableState },
+    Reimbursements { state: TableState },
+    Rules { state: TableState },
+    Payees { state: TableState },
+    // None adds a new rule or payee, otherwise the index of the one being edited
+    RuleInput { input_data: Vec<InputData>, selection_index: usize, rule: Option<usize> },
+    PayeeInput { input_data: Vec<InputData>, selection_index: usize, payee: Option<usize> },
+    AttachInput { input_data: Vec<InputData>, selection_index: usize, log: LogId, purchase: PurchaseId },
+    Accounts { state: TableState },
+    // None adds a new account
+    AccountInput { input_data: Vec<InputData>, selection_index: usize, account: Option<AccountId> },
+    TransferInput { input_data: Vec<InputData>, selection_index: usize },
+    // reconciling goes back to the log it was started from
+    ReconcileInput { input_data: Vec<InputData>, selection_index: usize, log: LogId },
+    // month is the first day of the month shown
+    Envelopes { month: NaiveDate, state: TableState },
+    // None sets the month's income instead of a category's assignment
+    EnvelopeInput { input_data: Vec<InputData>, selection_index: usize, month: NaiveDate, category: Option<PurchaseType> },
+    Goals { state: TableState },
+    // None adds a new goal, otherwise the index of the one being edited
+    GoalInput { input_data: Vec<InputData>, selection_index: usize, goal: Option<usize> },
+    Debts { state: TableState },
+    // None adds a new debt, otherwise the index of the one being edited
+    DebtInput { input_data: Vec<InputData>, selection_index: usize, debt: Option<usize> },
+    // a payment on the debt at that index, or with None the extra paid each month in payoff plans
+    DebtPaymentInput { input_data: Vec<InputData>, selection_index: usize, debt: Option<usize> },
 }
 
 pub enum UITransition {
-    OpenLog(usize),
-    NewLog,
-    ExitApp,
+    OpenLog(LogId),
+    OpenTrash,
+    OpenSearch,
+    OpenPurchase(LogId, PurchaseId),
+    OpenStats(Option<LogId>),
+    OpenReport(Option<LogId>),
+    OpenReimbursements,
+    OpenRules,
+    OpenPayees,
+    EditRule(Option<usize>),
+    EditPayee(Option<usize>),
+    AttachFile(LogId, PurchaseId),
+    OpenAccounts,
+    EditAccount(Option<AccountId>),
+    // the account the money comes from, if one is picked
+    NewTransfer(Option<AccountId>),
+    // the account to reconcile first, if there is an obvious one
+    Reconcile(Option<AccountId>),
+    OpenEnvelopes,
+    EditEnvelope(NaiveDate, Option<PurchaseType>),
+    OpenGoals,
+    EditGoal(Option<usize>),
+    OpenDebts,
+    EditDebt(Option<usize>),
+    PayDebt(Option<usize>),
     ExitLayer,
     NewPurchase,
 }
 
-#[derive(Debug)]
+// what the user asked for, keys are turned into these by the keymap
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
 pub enum UserInput {
     Next,
     Prev,
     NextSelect,
     PrevSelect,
+    NextField,
+    PrevField,
     Char(char),
+    Paste(String),
+    // a left click at (column, row), screens turn it into a selection
+    Click(u16, u16),
     Submit,
     Esc,
     Backspace,
+    DeleteForward,
+    DeleteWord,
+    Home,
+    End,
+    WordLeft,
+    WordRight,
+    SelectAll,
+    Add,
+    Delete,
+    Restore,
+    Trash,
+    Search,
+    Stats,
+    Report,
+    Reimbursements,
+    Rules,
+    Payees,
+    Attach,
+    OpenAttachments,
+    Accounts,
+    Transfer,
+    ToggleCleared,
+    Reconcile,
+    FinishReconcile,
+    Unlock,
+    Envelopes,
+    Income,
+    Goals,
+    Debts,
+    Pay,
+    ExtraPayment,
+    Newline,
+    CycleSort,
+    ReverseSort,
+    WeekPeriod,
+    MonthPeriod,
+    YearPeriod,
+    Help,
+    CycleTheme,
+    Quit,
     None,
 }
 
-pub enum InputMode {
-    Editing,
-    Viewing,
+// line editing keys every text box understands
+const TEXT_EDITING: [(UserInput, &str); 10] = [
+    (UserInput::Next, "cursor right"),
+    (UserInput::Prev, "cursor left"),
+    (UserInput::WordRight, "next word"),
+    (UserInput::WordLeft, "previous word"),
+    (UserInput::Home, "start of line"),
+    (UserInput::End, "end of line"),
+    (UserInput::Backspace, "delete character"),
+    (UserInput::DeleteForward, "delete character under cursor"),
+    (UserInput::DeleteWord, "delete previous word"),
+    (UserInput::SelectAll, "select all"),
+];
+
+impl UIState {
+    // screens that are typing into a text box get plain characters instead of key bindings
+    pub fn accepts_text(&self) -> bool {
+        match self {
+            // the last entry is the submit button
+            UIState::PurchaseInput { input_data, selection_index, .. }
+            | UIState::RuleInput { input_data, selection_index, .. }
+            | UIState::PayeeInput { input_data, selection_index, .. }
+            | UIState::AttachInput { input_data, selection_index, .. }
+            | UIState::AccountInput { input_data, selection_index, .. }
+            | UIState::TransferInput { input_data, selection_index }
+            | UIState::ReconcileInput { input_data, selection_index, .. }
+            | UIState::EnvelopeInput { input_data, selection_index, .. }
+            | UIState::GoalInput { input_data, selection_index, .. }
+            | UIState::DebtInput { input_data, selection_index, .. }
+            | UIState::DebtPaymentInput { input_data, selection_index, .. } => *selection_index + 1 < input_data.len(),
+            UIState::Search { input_data, selection_index, .. } => *selection_index < input_data.len(),
+            _ => false,
+        }
+    }
+
+    // the actions this screen responds to, with what they do here
+    pub fn actions(&self) -> Vec<(UserInput, &'static str)> {
+        let mut actions = match self {
+            UIState::BudgrShow { .. } => vec![
+                (UserInput::NextSelect, "next log"),
+                (UserInput::PrevSelect, "previous log"),
+                (UserInput::Submit, "open log"),
+                (UserInput::Delete, "move log to trash"),
+                (UserInput::Trash, "open trash"),
+                (UserInput::Search, "search purchases"),
+                (UserInput::Stats, "charts for all logs"),
+                (UserInput::Report, "reports for all logs"),
+                (UserInput::Reimbursements, "outstanding reimbursements"),
+                (UserInput::Rules, "payees and rules"),
+                (UserInput::Accounts, "accounts and balances"),
+                (UserInput::Envelopes, "envelope budget"),
+                (UserInput::Goals, "savings goals"),
+                (UserInput::Debts, "debts and loans"),
+                (UserInput::CycleSort, "change sort column"),
+                (UserInput::ReverseSort, "reverse sort"),
+                (UserInput::Esc, "quit"),
+            ],
+            UIState::LogShow { .. } => vec![
+                (UserInput::NextSelect, "next purchase"),
+                (UserInput::PrevSelect, "previous purchase"),
+                (UserInput::Add, "new purchase"),
+                (UserInput::Delete, "move purchase to trash"),
+                (UserInput::Attach, "attach a file"),
+                (UserInput::OpenAttachments, "open attached files"),
+                (UserInput::ToggleCleared, "tick off as cleared"),
+                (UserInput::Reconcile, "reconcile against a statement"),
+                (UserInput::FinishReconcile, "finish reconciling"),
+                (UserInput::Unlock, "unlock a reconciled purchase"),
+                (UserInput::Stats, "charts for this log"),
+                (UserInput::Report, "reports for this log"),
+                (UserInput::CycleSort, "change sort column"),
+                (UserInput::ReverseSort, "reverse sort"),
+                (UserInput::Esc, "back"),
+            ],
+            UIState::PurchaseInput { .. } => {
+                let mut actions = vec![
+                    (UserInput::NextSelect, "next field"),
+                    (UserInput::PrevSelect, "previous field"),
+                    (UserInput::NextField, "next field"),
+                    (UserInput::PrevField, "previous field"),
+                    (UserInput::Submit, "add purchase (on submit)"),
+                    (UserInput::Newline, "new line in the note"),
+                    (UserInput::Esc, "back"),
+                ];
+                actions.extend(TEXT_EDITING);
+                actions
+            }
+            UIState::TrashShow { .. } => vec![
+                (UserInput::NextSelect, "next item"),
+                (UserInput::PrevSelect, "previous item"),
+                (UserInput::Restore, "restore"),
+                (UserInput::Delete, "delete forever"),
+                (UserInput::Esc, "back"),
+            ],
+            UIState::Search { .. } => {
+                let mut actions = vec![
+                    (UserInput::NextSelect, "next field / result"),
+                    (UserInput::PrevSelect, "previous field / result"),
+                    (UserInput::NextField, "next field"),
+                    (UserInput::PrevField, "previous field"),
+                    (UserInput::Submit, "go to results / open purchase"),
+                    (UserInput::Esc, "back"),
+                ];
+                actions.extend(TEXT_EDITING);
+                actions
+            }
+            UIState::Stats { .. } => vec![(UserInput::Esc, "back")],
+            UIState::Report { .. } => vec![
+                (UserInput::NextSelect, "next period"),
+                (UserInput::PrevSelect, "previous period"),
+                (UserInput::WeekPeriod, "weekly"),
+                (UserInput::MonthPeriod, "monthly"),
+                (UserInput::YearPeriod, "yearly"),
+                (UserInput::Esc, "back"),
+            ],
+            UIState::Reimbursements { .. } => vec![
+                (UserInput::NextSelect, "next purchase"),
+                (UserInput::PrevSelect, "previous purchase"),
+                (UserInput::Esc, "back"),
+            ],
+            UIState::Rules { .. } => vec![
+                (UserInput::NextSelect, "next rule"),
+                (UserInput::PrevSelect, "previous rule"),
+                (UserInput::Add, "new rule"),
+                (UserInput::Submit, "edit rule"),
+                (UserInput::Delete, "delete rule"),
+                (UserInput::Payees, "payees"),
+                (UserInput::Esc, "back"),
+            ],
+            UIState::Payees { .. } => vec![
+                (UserInput::NextSelect, "next payee"),
+                (UserInput::PrevSelect, "previous payee"),
+                (UserInput::Add, "new payee"),
+                (UserInput::Submit, "edit payee"),
+                (UserInput::Delete, "delete payee"),
+                (UserInput::Esc, "back to rules"),
+            ],
+            UIState::Envelopes { .. } => vec![
+                (UserInput::NextSelect, "next category"),
+                (UserInput::PrevSelect, "previous category"),
+                (UserInput::Next, "next month"),
+                (UserInput::Prev, "previous month"),
+                (UserInput::Submit, "assign to category"),
+                (UserInput::Income, "set the month's income"),
+                (UserInput::Esc, "back"),
+            ],
+            UIState::Debts { .. } => vec![
+                (UserInput::NextSelect, "next debt"),
+                (UserInput::PrevSelect, "previous debt"),
+                (UserInput::Add, "new debt"),
+                (UserInput::Submit, "edit debt"),
+                (UserInput::Pay, "record a payment"),
+                (UserInput::ExtraPayment, "set the extra paid each month"),
+                (UserInput::Delete, "delete debt"),
+                (UserInput::Esc, "back"),
+            ],
+            UIState::Goals { .. } => vec![
+                (UserInput::NextSelect, "next goal"),
+                (UserInput::PrevSelect, "previous goal"),
+                (UserInput::Add, "new goal"),
+                (UserInput::Submit, "edit goal"),
+                (UserInput::Delete, "delete goal"),
+                (UserInput::Esc, "back"),
+            ],
+            UIState::Accounts { .. } => vec![
+                (UserInput::NextSelect, "next account"),
+                (UserInput::PrevSelect, "previous account"),
+                (UserInput::Add, "new account"),
+                (UserInput::Submit, "edit account"),
+                (UserInput::Delete, "delete account"),
+                (UserInput::Transfer, "transfer between accounts"),
+                (UserInput::Esc, "back"),
+            ],
+            UIState::RuleInput { .. }
+            | UIState::PayeeInput { .. }
+            | UIState::AttachInput { .. }
+            | UIState::AccountInput { .. }
+            | UIState::TransferInput { .. }
+            | UIState::ReconcileInput { .. }
+            | UIState::EnvelopeInput { .. }
+            | UIState::GoalInput { .. }
+            | UIState::DebtInput { .. }
+            | UIState::DebtPaymentInput { .. } => {
+                let mut actions = vec![
+                    (UserInput::NextSelect, "next field"),
+                    (UserInput::PrevSelect, "previous field"),
+                    (UserInput::NextField, "next field"),
+                    (UserInput::PrevField, "previous field"),
+                    (UserInput::Submit, "save (on submit)"),
+                    (UserInput::Esc, "back"),
+                ];
+                actions.extend(TEXT_EDITING);
+                actions
+            }
+        };
+        actions.push((UserInput::Help, "show / hide this help"));
+        actions.push((UserInput::CycleTheme, "next colour theme"));
+        actions.push((UserInput::Quit, "save and quit"));
+        actions
+    }
+
+    // the actions worth a reminder in the status bar, moving around and editing text are left out
+    pub fn hints(&self) -> Vec<(UserInput, &'static str)> {
+        const OBVIOUS: [UserInput; 6] = [
+            UserInput::NextSelect,
+            UserInput::PrevSelect,
+            UserInput::NextField,
+            UserInput::PrevField,
+            UserInput::CycleTheme,
+            UserInput::Quit,
+        ];
+        self.actions()
+            .into_iter()
+            .filter(|(action, _)| {
+                !OBVIOUS.contains(action) && !TEXT_EDITING.iter().any(|(a, _)| a == action)
+            })
+            .collect()
+    }
+
+    // the log this screen is about, None for screens covering every log
+    pub fn log_id(&self) -> Option<LogId> {
+        match self {
+            UIState::LogShow { log, .. }
+            | UIState::PurchaseInput { log, .. }
+            | UIState::AttachInput { log, .. }
+            | UIState::ReconcileInput { log, .. } => Some(*log),
+            UIState::Stats { log } | UIState::Report { log, .. } => *log,
+            _ => None,
+        }
+    }
 }
 
 // data needed to create an input box
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct InputData {
     pub input: String,
     pub character_pos: usize,
+    // after select all the next edit replaces the whole input
+    pub all_selected: bool,
+    // keeps newlines instead of flattening them, for notes
+    pub multiline: bool,
 }
 
 impl InputData {
+    // an input already holding some text, with the cursor at the end
+    pub fn with_text(input: String) -> Self {
+        let character_pos = input.chars().count();
+        InputData {
+            input,
+            character_pos,
+            ..Default::default()
+        }
+    }
+
     // - - - string input stuff - - -
     // mostly stolen from ratatui example
 
@@ -64,6 +404,7 @@ impl InputData {
     }
 
     pub fn enter_char(&mut self, new_char: char) {
+        self.take_selection();
         let index = self.byte_index();
         self.input.insert(index, new_char);
         self.move_cursor_right();
@@ -78,6 +419,9 @@ impl InputData {
     }
 
     pub fn delete_char(&mut self) {
+        if self.take_selection() {
+            return;
+        }
         // TODO: reverse this if statement
         let is_not_cursor_leftmost = self.character_pos != 0;
 
@@ -93,7 +437,381 @@ impl InputData {
         }
     }
 
-    fn reset_cursor(&mut self) {
+    // delete the character under the cursor
+    pub fn delete_char_forward(&mut self) {
+        if self.take_selection() {
+            return;
+        }
+        if self.character_pos < self.input.chars().count() {
+            let index = self.byte_index();
+            self.input.remove(index);
+        }
+    }
+
+    // delete back to the start of the previous word
+    pub fn delete_word(&mut self) {
+        if self.take_selection() {
+            return;
+        }
+        let end = self.character_pos;
+        self.move_word_left();
+        let start = self.character_pos;
+        self.input = self
+            .input
+            .chars()
+            .take(start)
+            .chain(self.input.chars().skip(end))
+            .collect();
+    }
+
+    pub fn move_cursor_home(&mut self) {
         self.character_pos = 0;
     }
+
+    pub fn move_cursor_end(&mut self) {
+        self.character_pos = self.input.chars().count();
+    }
+
+    // skip whitespace then the word before the cursor
+    pub fn move_word_left(&mut self) {
+        let chars = self.input.chars().collect::<Vec<char>>();
+        let mut pos = self.character_pos;
+        while pos > 0 && chars[pos - 1].is_whitespace() {
+            pos -= 1;
+        }
+        while pos > 0 && !chars[pos - 1].is_whitespace() {
+            pos -= 1;
+        }
+        self.character_pos = pos;
+    }
+
+    // skip the word under the cursor then the whitespace after it
+    pub fn move_word_right(&mut self) {
+        let chars = self.input.chars().collect::<Vec<char>>();
+        let mut pos = self.character_pos;
+        while pos < chars.len() && !chars[pos].is_whitespace() {
+            pos += 1;
+        }
+        while pos < chars.len() && chars[pos].is_whitespace() {
+            pos += 1;
+        }
+        self.character_pos = pos;
+    }
+
+    // pasted text goes in at the cursor, newlines are flattened unless the input is multiline
+    pub fn insert_str(&mut self, text: &str) {
+        for c in text.chars() {
+            match c {
+                '\r' => (),
+                '\n' if self.multiline => self.enter_char('\n'),
+                '\n' | '\t' => self.enter_char(' '),
+                c => self.enter_char(c),
+            }
+        }
+    }
+
+    pub fn select_all(&mut self) {
+        self.all_selected = !self.input.is_empty();
+        self.move_cursor_end();
+    }
+
+    // clears the input if everything was selected, returns whether it did
+    fn take_selection(&mut self) -> bool {
+        if !self.all_selected {
+            return false;
+        }
+        self.all_selected = false;
+        self.input.clear();
+        self.character_pos = 0;
+        true
+    }
+
+    // apply an editing action, returns false if the input wasn't an edit
+    pub fn handle(&mut self, input: &UserInput) -> bool {
+        match input {
+            UserInput::Char(c) => self.enter_char(*c),
+            UserInput::Paste(text) => self.insert_str(text),
+            UserInput::Newline if self.multiline => self.enter_char('\n'),
+            UserInput::Backspace => self.delete_char(),
+            UserInput::DeleteForward => self.delete_char_forward(),
+            UserInput::DeleteWord => self.delete_word(),
+            UserInput::SelectAll => self.select_all(),
+            // moving the cursor drops the selection
+            UserInput::Next => self.move_cursor_right(),
+            UserInput::Prev => self.move_cursor_left(),
+            UserInput::Home => self.move_cursor_home(),
+            UserInput::End => self.move_cursor_end(),
+            UserInput::WordLeft => self.move_word_left(),
+            UserInput::WordRight => self.move_word_right(),
+            _ => return false,
+        }
+        if !matches!(input, UserInput::SelectAll) {
+            self.all_selected = false;
+        }
+        true
+    }
+}
+
+// - - - sorting - - -
+// sorting only changes the order rows are shown in, the logs themselves stay in insertion order
+
+pub trait SortKey: Copy + PartialEq + 'static {
+    const ALL: &'static [Self];
+    // which table column the key sorts by, for the header indicator
+    fn column(&self) -> usize;
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LogSortKey {
+    Name,
+    Count,
+    Total,
+}
+
+impl SortKey for LogSortKey {
+    const ALL: &'static [Self] = &[LogSortKey::Name, LogSortKey::Count, LogSortKey::Total];
+    fn column(&self) -> usize {
+        match self {
+            LogSortKey::Name => 0,
+            LogSortKey::Count => 1,
+            LogSortKey::Total => 2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PurchaseSortKey {
+    Name,
+    Category,
+    Date,
+    Cost,
+}
+
+impl SortKey for PurchaseSortKey {
+    const ALL: &'static [Self] = &[
+        PurchaseSortKey::Name,
+        PurchaseSortKey::Category,
+        PurchaseSortKey::Date,
+        PurchaseSortKey::Cost,
+    ];
+    fn column(&self) -> usize {
+        match self {
+            PurchaseSortKey::Name => 0,
+            PurchaseSortKey::Category => 1,
+            PurchaseSortKey::Date => 2,
+            PurchaseSortKey::Cost => 3,
+        }
+    }
+}
+
+// no key means insertion order
+#[derive(Clone, Copy, Debug)]
+pub struct SortOrder<K: SortKey> {
+    pub key: Option<K>,
+    pub descending: bool,
+}
+
+impl<K: SortKey> Default for SortOrder<K> {
+    fn default() -> Self {
+        SortOrder {
+            key: None,
+            descending: false,
+        }
+    }
+}
+
+impl<K: SortKey> SortOrder<K> {
+    // insertion order -> first key -> ... -> last key -> insertion order
+    pub fn cycle_key(&mut self) {
+        self.key = match self.key {
+            None => K::ALL.first().copied(),
+            Some(key) => {
+                let i = K::ALL.iter().position(|k| *k == key).unwrap_or(0);
+                K::ALL.get(i + 1).copied()
+            }
+        };
+    }
+
+    pub fn reverse(&mut self) {
+        self.descending = !self.descending;
+    }
+
+    // put an arrow after the title of the sorted column
+    pub fn header(&self, titles: &[&str]) -> Vec<String> {
+        let arrow = match self.descending {
+            true => "▼",
+            false => "▲",
+        };
+        titles
+            .iter()
+            .enumerate()
+            .map(|(i, title)| match self.key {
+                Some(key) if key.column() == i => format!("{} {}", title, arrow),
+                _ => title.to_string(),
+            })
+            .collect()
+    }
+
+    // sort a list of indices with a comparison for the current key, rows with equal keys
+    // stay in insertion order either way. without a key, descending is newest first
+    pub fn apply(&self, indices: &mut [usize], cmp: impl Fn(K, usize, usize) -> Ordering) {
+        match (self.key, self.descending) {
+            (Some(key), false) => indices.sort_by(|a, b| cmp(key, *a, *b)),
+            (Some(key), true) => indices.sort_by(|a, b| cmp(key, *a, *b).reverse()),
+            (None, false) => (),
+            (None, true) => indices.reverse(),
+        }
+    }
+}
+
+// the sort of every view, kept while moving between views
+#[derive(Default)]
+pub struct ViewSorts {
+    pub logs: SortOrder<LogSortKey>,
+    pub purchases: SortOrder<PurchaseSortKey>,
+}
+
+impl ViewSorts {
+    // indices into budgr.logs in the order they are shown
+    pub fn log_order(&self, budgr: &Budgr) -> Vec<usize> {
+        let logs = &budgr.logs;
+        let mut order = (0..logs.len()).collect::<Vec<usize>>();
+        self.logs.apply(&mut order, |key, a, b| match key {
+            LogSortKey::Name => logs[a].name.to_lowercase().cmp(&logs[b].name.to_lowercase()),
+            LogSortKey::Count => logs[a].purchases.len().cmp(&logs[b].purchases.len()),
+            LogSortKey::Total => logs[a].get_total().cmp(&logs[b].get_total()),
+        });
+        order
+    }
+
+    // indices into log.purchases in the order they are shown
+    pub fn purchase_order(&self, log: &Log) -> Vec<usize> {
+        let p = &log.purchases;
+        let mut order = (0..p.len()).collect::<Vec<usize>>();
+        self.purchases.apply(&mut order, |key, a, b| match key {
+            PurchaseSortKey::Name => p[a].name.to_lowercase().cmp(&p[b].name.to_lowercase()),
+            PurchaseSortKey::Category => p[a].category.cmp(&p[b].category),
+            PurchaseSortKey::Date => p[a].date.cmp(&p[b].date),
+            PurchaseSortKey::Cost => p[a].cost.cmp(&p[b].cost),
+        });
+        order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // rows 0 and 2 share a cost, so only their relative order tells a stable sort apart
+    fn sorted(descending: bool) -> Vec<usize> {
+        let costs = [5, 9, 5, 1];
+        let sort = SortOrder {
+            key: Some(PurchaseSortKey::Cost),
+            descending,
+        };
+        let mut order = (0..costs.len()).collect::<Vec<usize>>();
+        sort.apply(&mut order, |_, a, b| costs[a].cmp(&costs[b]));
+        order
+    }
+
+    #[test]
+    fn reversing_a_sort_keeps_ties_in_insertion_order() {
+        assert_eq!(sorted(false), vec![3, 0, 2, 1]);
+        assert_eq!(sorted(true), vec![1, 0, 2, 3]);
+    }
+
+    #[test]
+    fn descending_without_a_key_is_newest_first() {
+        let sort = SortOrder::<PurchaseSortKey> {
+            key: None,
+            descending: true,
+        };
+        let mut order = vec![0, 1, 2];
+        sort.apply(&mut order, |_, _, _| Ordering::Equal);
+        assert_eq!(order, vec![2, 1, 0]);
+    }
+
+    // an input holding `text` with the cursor at the end, after the edits are applied
+    fn edited(text: &str, edits: &[UserInput]) -> InputData {
+        let mut input = InputData::with_text(text.to_string());
+        for edit in edits {
+            assert!(input.handle(edit));
+        }
+        input
+    }
+
+    #[test]
+    fn word_moves_skip_whitespace_then_a_word() {
+        let mut input = edited("pay  the rent", &[UserInput::WordLeft]);
+        assert_eq!(input.character_pos, 9);
+        input.handle(&UserInput::WordLeft);
+        assert_eq!(input.character_pos, 5);
+        input.handle(&UserInput::WordLeft);
+        input.handle(&UserInput::WordLeft);
+        assert_eq!(input.character_pos, 0);
+
+        input.handle(&UserInput::WordRight);
+        assert_eq!(input.character_pos, 5);
+        input.handle(&UserInput::WordRight);
+        input.handle(&UserInput::WordRight);
+        assert_eq!(input.character_pos, 13);
+    }
+
+    #[test]
+    fn delete_forward_removes_the_character_under_the_cursor() {
+        let input = edited("café au", &[UserInput::Home, UserInput::Next, UserInput::Next, UserInput::Next, UserInput::DeleteForward]);
+        assert_eq!((input.input.as_str(), input.character_pos), ("caf au", 3));
+        // nothing to delete at the end
+        let input = edited("café", &[UserInput::DeleteForward]);
+        assert_eq!(input.input, "café");
+    }
+
+    #[test]
+    fn delete_word_removes_back_to_the_start_of_the_word() {
+        let input = edited("groceries for  ", &[UserInput::DeleteWord]);
+        assert_eq!((input.input.as_str(), input.character_pos), ("groceries ", 10));
+        let input = edited("one two three", &[UserInput::WordLeft, UserInput::DeleteWord]);
+        assert_eq!((input.input.as_str(), input.character_pos), ("one three", 4));
+        let input = edited("", &[UserInput::DeleteWord]);
+        assert_eq!(input.input, "");
+    }
+
+    #[test]
+    fn pasted_newlines_are_kept_only_in_multiline_inputs() {
+        let paste = UserInput::Paste("milk\r\neggs\tbread\n".to_string());
+        let input = edited("", std::slice::from_ref(&paste));
+        assert_eq!((input.input.as_str(), input.character_pos), ("milk eggs bread ", 16));
+
+        let mut note = InputData {
+            multiline: true,
+            ..Default::default()
+        };
+        note.handle(&paste);
+        assert_eq!(note.input, "milk\neggs bread\n");
+    }
+
+    #[test]
+    fn typing_after_select_all_replaces_everything() {
+        let input = edited("old name", &[UserInput::SelectAll, UserInput::Char('n')]);
+        assert_eq!((input.input.as_str(), input.character_pos, input.all_selected), ("n", 1, false));
+        let input = edited("old name", &[UserInput::SelectAll, UserInput::Paste("new".to_string())]);
+        assert_eq!(input.input, "new");
+        let input = edited("old name", &[UserInput::SelectAll, UserInput::Backspace]);
+        assert_eq!(input.input, "");
+        // moving the cursor keeps the text
+        let input = edited("old name", &[UserInput::SelectAll, UserInput::Home, UserInput::Char('x')]);
+        assert_eq!(input.input, "xold name");
+    }
+
+    #[test]
+    fn home_and_end_count_characters_not_bytes() {
+        let mut input = edited("żółw €5", &[UserInput::Home, UserInput::Char('ę')]);
+        assert_eq!((input.input.as_str(), input.character_pos), ("ężółw €5", 1));
+        input.handle(&UserInput::End);
+        assert_eq!(input.character_pos, 8);
+        input.handle(&UserInput::Char('!'));
+        input.handle(&UserInput::Prev);
+        input.handle(&UserInput::Backspace);
+        assert_eq!(input.input, "ężółw €!");
+    }
 }